@@ -48,7 +48,7 @@ if [[ $missing -ne 0 ]]; then
 fi
 
 # --- Build the binary in release mode --------------------------------------
-cargo build --release
+cargo build --release --features onnxruntime
 
 BINARY_NAME="verse"
 BINARY_PATH="target/release/$BINARY_NAME"
@@ -0,0 +1,70 @@
+// User settings in config.json, next to auth.json in the config dir. Every
+// field is optional; a missing file gives the defaults. Example:
+//
+//   { "limits": { "max_cycles": 500000000, "timeout_secs": 7200, "max_journal_bytes": 10485760 } }
+//
+// `limits` bounds the verifier-supplied guests `verse prove` runs; a null or 0
+// limit is switched off, and the matching `verse prove` flag overrides it.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub limits: Limits,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    pub max_cycles: Option<u64>,
+    pub timeout_secs: Option<u64>,
+    pub max_journal_bytes: Option<u64>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_cycles: Some(500_000_000),
+            timeout_secs: Some(2 * 60 * 60),
+            max_journal_bytes: Some(10 * 1024 * 1024),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Invalid config file ({}): {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Failed to read config file ({}): {}", path.display(), e)),
+        }
+    }
+}
+
+impl Limits {
+    // Environment variables Zk-host reads the limits from; 0 means no limit.
+    pub fn env_vars(&self) -> [(&'static str, String); 3] {
+        let value = |v: Option<u64>| v.unwrap_or(0).to_string();
+        [
+            ("VERSE_MAX_CYCLES", value(self.max_cycles)),
+            ("VERSE_TIMEOUT_SECS", value(self.timeout_secs)),
+            ("VERSE_MAX_JOURNAL_BYTES", value(self.max_journal_bytes)),
+        ]
+    }
+
+    pub fn describe(&self) -> String {
+        let show = |v: Option<u64>, unit: &str| match v.filter(|&n| n > 0) {
+            Some(n) => format!("{}{}", n, unit),
+            None => "none".to_string(),
+        };
+        format!(
+            "max cycles {}, timeout {}, max journal {}",
+            show(self.max_cycles, ""),
+            show(self.timeout_secs, "s"),
+            show(self.max_journal_bytes, " bytes")
+        )
+    }
+}
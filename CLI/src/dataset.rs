@@ -0,0 +1,97 @@
+// Datasets for the local onnxruntime commands (`verse model check`,
+// `verse model eval`), read with any format readers.rs supports (chosen by
+// extension). Each record holds the model's features, optionally followed by
+// a label; missing feature cells are NaN. Labels are kept as text; the caller
+// maps them to class indexes or targets.
+
+use crate::readers::Format;
+
+pub struct Dataset {
+    pub rows: Vec<Vec<f64>>,
+    // one per row, or none when the file has no label column
+    pub labels: Option<Vec<String>>,
+}
+
+// Label text; numeric labels are normalised so "2.0" and "2" agree.
+fn label_text(cell: &str) -> String {
+    match cell.parse::<f64>() {
+        Ok(v) if v.fract() == 0.0 => format!("{}", v as i64),
+        Ok(v) => v.to_string(),
+        Err(_) => cell.to_string(),
+    }
+}
+
+pub fn load_dataset(path: &str, width: usize) -> Result<Dataset, String> {
+    let raw = Format::detect(path, None)?.reader(b',', None).read(path)?;
+
+    let mut rows = Vec::with_capacity(raw.records.len());
+    let mut labels = Vec::with_capacity(raw.records.len());
+    for (i, record) in raw.records.iter().enumerate() {
+        let label = match record.len() {
+            n if n == width => None,
+            n if n == width + 1 => Some(label_text(&record[width])),
+            n => {
+                return Err(format!(
+                    "{} row {}: {} values, expected {} features (plus an optional label)",
+                    path,
+                    i + 1,
+                    n,
+                    width
+                ));
+            }
+        };
+        let features: Vec<f64> = record[..width]
+            .iter()
+            .map(|c| if c.is_empty() { Ok(f64::NAN) } else { c.parse::<f64>() })
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("{} row {}: non-numeric feature", path, i + 1))?;
+        rows.push(features);
+        labels.push(label);
+    }
+    if rows.is_empty() {
+        return Err(format!("{} has no rows", path));
+    }
+    let labels = if labels.iter().all(Option::is_some) {
+        Some(labels.into_iter().map(Option::unwrap_or_default).collect())
+    } else if labels.iter().all(Option::is_none) {
+        None
+    } else {
+        return Err(format!("{}: some rows have a label and some do not", path));
+    };
+    Ok(Dataset { rows, labels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(name: &str, csv: &str, width: usize) -> Result<Dataset, String> {
+        let path = std::env::temp_dir().join(format!("verse-dataset-{}-{}.csv", std::process::id(), name));
+        fs::write(&path, csv).unwrap();
+        let dataset = load_dataset(path.to_str().unwrap(), width);
+        fs::remove_file(&path).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn reads_features_and_optional_labels() {
+        let dataset = load("labels", "1.5,,2.0\n-1,4,1\n", 2).unwrap();
+        assert_eq!(dataset.rows[0][0], 1.5);
+        assert!(dataset.rows[0][1].is_nan());
+        assert_eq!(dataset.rows[1], [-1.0, 4.0]);
+        assert_eq!(dataset.labels.unwrap(), ["2", "1"]);
+
+        assert!(load("no-labels", "1,2\n3,4\n", 2).unwrap().labels.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        let err = load("mixed", "1,2,0\n3,4\n", 2).err().unwrap();
+        assert!(err.contains("some rows have a label and some do not"), "{}", err);
+        let err = load("width", "1,2,3,4\n", 2).err().unwrap();
+        assert!(err.contains("row 1: 4 values, expected 2 features"), "{}", err);
+        let err = load("text", "1,x\n", 2).err().unwrap();
+        assert!(err.contains("row 1: non-numeric feature"), "{}", err);
+    }
+}
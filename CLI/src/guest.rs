@@ -0,0 +1,261 @@
+// Verifier guest workspace: `verse init` writes it from templates compiled
+// into the binary, and `verse request` embeds the verifier's dataset in its
+// guest source before building it. Layout:
+//
+//   Cargo.toml, rust-toolchain.toml, .gitignore
+//   methods/                   risc0-build crate embedding the guest
+//   methods/guest/src/main.rs  the guest; get_dataset() / get_dataset_tree() hold the data
+//   host/src/main.rs           exporter writing guest_elf and guest_id to the workspace root
+//
+// The guest runs every model kind; `--kind` only picks which starter dataset
+// it is created with.
+
+use crate::ingest::Task;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const DEFAULT_DIR: &str = "verse-guest";
+pub const GUEST_MAIN: &str = "methods/guest/src/main.rs";
+// Written by `cargo run --release` in the workspace root (see host/src/main.rs).
+pub const ELF_FILE: &str = "guest_elf";
+pub const ID_FILE: &str = "guest_id";
+
+const FILES: [(&str, &str); 10] = [
+    ("Cargo.toml", include_str!("../templates/guest/Cargo.toml.txt")),
+    ("rust-toolchain.toml", include_str!("../templates/guest/rust-toolchain.toml.txt")),
+    (".gitignore", include_str!("../templates/guest/gitignore.txt")),
+    ("host/Cargo.toml", include_str!("../templates/guest/host/Cargo.toml.txt")),
+    ("host/src/main.rs", include_str!("../templates/guest/host/src/main.rs.txt")),
+    ("methods/Cargo.toml", include_str!("../templates/guest/methods/Cargo.toml.txt")),
+    ("methods/build.rs", include_str!("../templates/guest/methods/build.rs.txt")),
+    ("methods/src/lib.rs", include_str!("../templates/guest/methods/src/lib.rs.txt")),
+    ("methods/guest/Cargo.toml", include_str!("../templates/guest/methods/guest/Cargo.toml.txt")),
+    (GUEST_MAIN, include_str!("../templates/guest/methods/guest/src/main.rs.txt")),
+];
+
+const DATASET_FN: &str = "fn get_dataset() -> Vec<(Vec<f32>, f32)>";
+const DATASET_TREE_FN: &str = "fn get_dataset_tree() -> Vec<Sample>";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    // both starter datasets
+    All,
+    // linear, multiple, polynomial and logistic regression, MLP regressors
+    Regression,
+    // softmax regression and MLP classifiers
+    Classification,
+    // decision trees and ensembles
+    Tree,
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "all" => Ok(Kind::All),
+            "regression" => Ok(Kind::Regression),
+            "classification" => Ok(Kind::Classification),
+            "tree" => Ok(Kind::Tree),
+            other => Err(format!(
+                "Unknown guest kind '{}' (expected all, regression, classification or tree)",
+                other
+            )),
+        }
+    }
+}
+
+impl Kind {
+    // The starter dataset the guest keeps; None keeps both.
+    fn task(&self) -> Option<Task> {
+        match self {
+            Kind::All => None,
+            Kind::Regression => Some(Task::Regression),
+            Kind::Classification | Kind::Tree => Some(Task::Classification),
+        }
+    }
+}
+
+// Writes the workspace into `dir`, which must be empty or missing unless
+// `force` is set (then the scaffold's files are overwritten and nothing else
+// is touched). Returns the files written.
+pub fn init(dir: &Path, kind: Kind, force: bool) -> Result<Vec<PathBuf>, String> {
+    if !force
+        && let Ok(mut entries) = fs::read_dir(dir)
+        && entries.next().is_some()
+    {
+        return Err(format!("{} is not empty (use --force to overwrite the guest files)", dir.display()));
+    }
+
+    let mut written = Vec::with_capacity(FILES.len());
+    for (name, content) in FILES {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = if name == GUEST_MAIN { starter_guest(content, kind)? } else { content.to_string() };
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+// The guest source with the other kind's starter dataset emptied.
+fn starter_guest(content: &str, kind: Kind) -> Result<String, String> {
+    match kind.task() {
+        None => Ok(content.to_string()),
+        Some(Task::Regression) => replace_function(content, DATASET_TREE_FN, &dataset_fn(DATASET_TREE_FN, "")),
+        Some(Task::Classification) => replace_function(content, DATASET_FN, &dataset_fn(DATASET_FN, "")),
+    }
+}
+
+// Replaces get_dataset() and get_dataset_tree() in the workspace's guest with
+// `rows` (last column is label). Returns the guest source path.
+pub fn embed_dataset(dir: &Path, rows: &[Vec<f32>], task: Task) -> Result<PathBuf, String> {
+    let guest_main = dir.join(GUEST_MAIN);
+    let content = fs::read_to_string(&guest_main).map_err(|e| {
+        format!(
+            "No guest workspace at {} ({}: {}); create one with `verse init`",
+            dir.display(),
+            guest_main.display(),
+            e
+        )
+    })?;
+    let (rows_fn, samples_fn) = generate_dataset_code(rows, task)?;
+    let content = replace_function(&content, DATASET_FN, &rows_fn)?;
+    let content = replace_function(&content, DATASET_TREE_FN, &samples_fn)?;
+    fs::write(&guest_main, content)
+        .map_err(|e| format!("Failed to write guest main ({}): {}", guest_main.display(), e))?;
+    Ok(guest_main)
+}
+
+// Rust literal for a feature or target value; whole numbers keep a ".0" so
+// they type as floats. Values are finite: build_rows rejects inf and NaN.
+fn float_literal(v: f32) -> String {
+    let s = v.to_string();
+    if s.contains('.') { s } else { format!("{}.0", s) }
+}
+
+fn dataset_fn(signature: &str, body: &str) -> String {
+    if body.is_empty() {
+        format!("{} {{\n    Vec::new()\n}}\n", signature)
+    } else {
+        format!("{} {{\n    vec![\n{}\n    ]\n}}\n", signature, body)
+    }
+}
+
+// Generate Rust code for get_dataset() and get_dataset_tree() from 2D rows
+// (last column is label). The task's function gets the rows and the other one
+// is left empty: regression rows keep the label as an f32 target,
+// classification rows carry it as a u32 class index.
+fn generate_dataset_code(rows: &[Vec<f32>], task: Task) -> Result<(String, String), String> {
+    let mut feat_len: Option<usize> = None;
+    let mut items: Vec<String> = Vec::with_capacity(rows.len());
+    for (i, r) in rows.iter().enumerate() {
+        if r.len() < 2 {
+            return Err(format!("Row {} has fewer than 2 columns", i + 1));
+        }
+        let flen = r.len() - 1;
+        if let Some(expected) = feat_len {
+            if expected != flen {
+                return Err(format!(
+                    "Inconsistent columns at row {}: expected {} features before label, got {}",
+                    i + 1,
+                    expected,
+                    flen
+                ));
+            }
+        } else {
+            feat_len = Some(flen);
+        }
+        let (features, label) = r.split_at(flen);
+        let feats_str = features.iter().map(|&v| float_literal(v)).collect::<Vec<_>>().join(", ");
+        items.push(match task {
+            Task::Regression => format!("        (vec![{}], {}),", feats_str, float_literal(label[0])),
+            Task::Classification => format!("        Sample {{ features: vec![{}], expected: {} }},", feats_str, label[0] as u32),
+        });
+    }
+    let body = items.join("\n");
+    Ok(match task {
+        Task::Regression => (dataset_fn(DATASET_FN, &body), dataset_fn(DATASET_TREE_FN, "")),
+        Task::Classification => (dataset_fn(DATASET_FN, ""), dataset_fn(DATASET_TREE_FN, &body)),
+    })
+}
+
+// Replace the function starting with `signature` (up to the next lone '}')
+// with `new_fn`.
+fn replace_function(content: &str, signature: &str, new_fn: &str) -> Result<String, String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with(signature))
+        .ok_or_else(|| format!("Failed to find `{}` in the guest source", signature))?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim() == "}")
+        .map(|i| start + 1 + i)
+        .ok_or_else(|| format!("Failed to find the end of `{}` in the guest source", signature))?;
+
+    // Rebuild file: lines before start, then new_fn, then lines after end
+    let mut new_content = String::new();
+    for line in &lines[..start] {
+        new_content.push_str(line);
+        new_content.push('\n');
+    }
+    new_content.push_str(new_fn);
+    for line in &lines[end + 1..] {
+        new_content.push_str(line);
+        new_content.push('\n');
+    }
+    Ok(new_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Code lines of a guest source, without comments or blank lines.
+    fn code_lines(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty() && !l.trim_start().starts_with("//"))
+            .collect()
+    }
+
+    // The template is ZK-guest's guest without its commented-out code; a fix
+    // to one must be made to the other.
+    #[test]
+    fn guest_template_matches_zk_guest() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../ZK-guest/methods/guest/src/main.rs");
+        // only checkable from a repository checkout, not a packaged crate
+        let Ok(zk_guest) = fs::read_to_string(path) else { return };
+        let template = FILES.iter().find(|(name, _)| *name == GUEST_MAIN).unwrap().1;
+        let (template, zk_guest) = (code_lines(template), code_lines(&zk_guest));
+        if let Some(i) = (0..template.len().max(zk_guest.len())).find(|&i| template.get(i) != zk_guest.get(i)) {
+            panic!(
+                "guest template differs from {} at code line {}: {:?} vs {:?}",
+                path,
+                i + 1,
+                template.get(i),
+                zk_guest.get(i)
+            );
+        }
+    }
+
+    // The dataset functions must stay replaceable.
+    #[test]
+    fn embedded_guest_keeps_replaceable_datasets() {
+        let guest = FILES.iter().find(|(name, _)| *name == GUEST_MAIN).unwrap().1;
+        for kind in [Kind::All, Kind::Regression, Kind::Classification, Kind::Tree] {
+            starter_guest(guest, kind).unwrap();
+        }
+        let rows = vec![vec![1.0, 2.0, 0.0], vec![3.0, 4.5, 1.0]];
+        let (rows_fn, samples_fn) = generate_dataset_code(&rows, Task::Classification).unwrap();
+        let embedded = replace_function(guest, DATASET_FN, &rows_fn).unwrap();
+        let embedded = replace_function(&embedded, DATASET_TREE_FN, &samples_fn).unwrap();
+        assert!(embedded.contains("Sample { features: vec![3.0, 4.5], expected: 1 },"));
+        assert!(embedded.contains("fn main()"));
+    }
+}
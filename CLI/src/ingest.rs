@@ -0,0 +1,481 @@
+// Dataset ingestion for `verse request --dataset`: reads the verifier's
+// dataset in any format readers.rs supports, picks the feature and label
+// columns, applies the missing-value policy and prints a per-column summary
+// before the rows are embedded in the guest.
+//
+// Columns are named by header (or Parquet / NDJSON column) name or by 0-based
+// index; without --feature-columns every column except the label is a
+// feature, in file order, and without --label-column the label is the last
+// column. Rows come out as features
+// followed by the label, the layout the guest's get_dataset() expects.
+//
+// The task decides which guest function the rows are embedded in:
+// classification rows go into get_dataset_tree() with the label as a class
+// index, regression rows into get_dataset() with the label as a target.
+
+use crate::readers::Format;
+use crate::schema::{FieldType, Schema};
+use std::str::FromStr;
+
+// Cells treated as missing, compared case-insensitively after trimming.
+const MISSING_MARKERS: [&str; 6] = ["", "na", "n/a", "nan", "null", "?"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MissingPolicy {
+    Fail,
+    Drop,
+    Constant(f64),
+    Mean,
+}
+
+impl FromStr for MissingPolicy {
+    type Err = String;
+
+    // fail | drop | mean | constant=<value>
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "fail" => Ok(MissingPolicy::Fail),
+            "drop" => Ok(MissingPolicy::Drop),
+            "mean" => Ok(MissingPolicy::Mean),
+            other => match other.strip_prefix("constant=") {
+                Some(v) => v
+                    .trim()
+                    .parse()
+                    .map(MissingPolicy::Constant)
+                    .map_err(|_| format!("Invalid constant in missing-value policy: '{}'", v)),
+                None => Err(format!(
+                    "Unknown missing-value policy '{}' (expected fail, drop, mean or constant=<value>)",
+                    other
+                )),
+            },
+        }
+    }
+}
+
+// Detection only calls integer labels classes when there are few of them and
+// each repeats at least once on average.
+const MAX_DETECTED_CLASSES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Task {
+    Classification,
+    Regression,
+}
+
+impl FromStr for Task {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "classification" => Ok(Task::Classification),
+            "regression" => Ok(Task::Regression),
+            other => Err(format!("Unknown task '{}' (expected classification or regression)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Task::Classification => write!(f, "classification"),
+            Task::Regression => write!(f, "regression"),
+        }
+    }
+}
+
+impl Task {
+    // Task for a dataset's labels, with the reason it was picked: the schema's
+    // label type when it settles it, then the model's class labels, then the
+    // label values themselves.
+    pub fn detect(schema: Option<&Schema>, class_labels: &[String], labels: &[f32]) -> (Task, &'static str) {
+        match schema.map(|s| &s.label.ty) {
+            Some(FieldType::Categorical(_) | FieldType::Bool) => return (Task::Classification, "the schema's label is categorical"),
+            Some(FieldType::Float) => return (Task::Regression, "the schema's label is a float"),
+            Some(FieldType::Int) | None => {}
+        }
+        if !class_labels.is_empty() {
+            return (Task::Classification, "the model publishes class labels");
+        }
+        let mut distinct: Vec<f32> = labels.to_vec();
+        distinct.sort_by(f32::total_cmp);
+        distinct.dedup();
+        let integral = labels.iter().all(|&y| y >= 0.0 && y.fract() == 0.0);
+        if integral && distinct.len() <= MAX_DETECTED_CLASSES && labels.len() >= 2 * distinct.len() {
+            (Task::Classification, "the labels are a few repeated integers")
+        } else {
+            (Task::Regression, "the labels look like continuous targets")
+        }
+    }
+
+    // Classification labels must be class indexes, below the number of
+    // classes when the model publishes its class labels.
+    pub fn check_labels(&self, class_labels: &[String], labels: &[f32]) -> Result<(), String> {
+        if *self == Task::Regression {
+            return Ok(());
+        }
+        for (i, &y) in labels.iter().enumerate() {
+            if !(y >= 0.0 && y.fract() == 0.0) {
+                return Err(format!("Data row {}: label {} is not a class index", i + 1, y));
+            }
+            if !class_labels.is_empty() && y as usize >= class_labels.len() {
+                return Err(format!(
+                    "Data row {}: class {} is out of range; the model has {} classes ({})",
+                    i + 1,
+                    y,
+                    class_labels.len(),
+                    class_labels.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A label cell as a number: a class label's index when the cell names one of
+// the model's classes, otherwise the cell parsed as a number.
+pub fn label_value(cell: &str, class_labels: &[String]) -> Result<f32, String> {
+    let cell = cell.trim();
+    if let Some(i) = class_labels.iter().position(|c| c == cell) {
+        return Ok(i as f32);
+    }
+    cell.parse::<f32>().map_err(|_| {
+        if class_labels.is_empty() {
+            format!("label '{}' is not a number", cell)
+        } else {
+            format!("label '{}' is not a number or one of the classes {}", cell, class_labels.join(", "))
+        }
+    })
+}
+
+pub struct IngestOptions<'a> {
+    pub path: &'a str,
+    pub format: Format,
+    pub delimiter: u8,
+    // whether a CSV starts with a header; None guesses
+    pub header: Option<bool>,
+    pub label_column: Option<&'a str>,
+    pub feature_columns: Vec<String>,
+    pub missing: MissingPolicy,
+}
+
+// Selected columns, features then label, with missing values resolved.
+pub struct Table {
+    // column names from the header, or "col<index>" without one
+    pub names: Vec<String>,
+    pub has_header: bool,
+    pub rows: Vec<Vec<String>>,
+}
+
+pub fn is_missing(cell: &str) -> bool {
+    let cell = cell.trim().to_ascii_lowercase();
+    MISSING_MARKERS.contains(&cell.as_str())
+}
+
+// Parses a delimiter flag: a single character, or "tab".
+pub fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("Delimiter must be a single ASCII character or 'tab', got '{}'", s)),
+    }
+}
+
+fn column_index(spec: &str, names: &[String], has_header: bool) -> Result<usize, String> {
+    if has_header
+        && let Some(i) = names.iter().position(|n| n == spec)
+    {
+        return Ok(i);
+    }
+    match spec.parse::<usize>() {
+        Ok(i) if i < names.len() => Ok(i),
+        _ => Err(format!("Column '{}' not found (columns: {})", spec, names.join(", "))),
+    }
+}
+
+pub fn load_table(opts: &IngestOptions) -> Result<Table, String> {
+    let raw = opts.format.reader(opts.delimiter, opts.header).read(opts.path)?;
+    let records = raw.records;
+    if records.is_empty() {
+        return Err(format!("{} has no rows", opts.path));
+    }
+
+    let has_header = raw.names.is_some();
+    let width = raw.names.as_ref().map_or(records[0].len(), Vec::len);
+    let names: Vec<String> = raw.names.unwrap_or_else(|| (0..width).map(|i| format!("col{}", i)).collect());
+    if let Some((i, r)) = records.iter().enumerate().find(|(_, r)| r.len() != width) {
+        return Err(format!(
+            "{} data row {}: {} columns, expected {}",
+            opts.path,
+            i + 1,
+            r.len(),
+            width
+        ));
+    }
+
+    let label = match opts.label_column {
+        Some(spec) => column_index(spec, &names, has_header)?,
+        None => width - 1,
+    };
+    let features: Vec<usize> = if opts.feature_columns.is_empty() {
+        (0..width).filter(|&i| i != label).collect()
+    } else {
+        opts.feature_columns
+            .iter()
+            .map(|c| column_index(c, &names, has_header))
+            .collect::<Result<_, _>>()?
+    };
+    if features.is_empty() {
+        return Err("No feature columns selected".into());
+    }
+    if let Some(i) = (1..features.len()).find(|&i| features[..i].contains(&features[i])) {
+        return Err(format!("Column '{}' is listed more than once in the feature columns", names[features[i]]));
+    }
+    if features.contains(&label) {
+        return Err(format!("Column '{}' is both a feature and the label", names[label]));
+    }
+    let columns: Vec<usize> = features.iter().copied().chain(std::iter::once(label)).collect();
+
+    let mut rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|r| columns.iter().map(|&c| r[c].clone()).collect())
+        .collect();
+    let label_pos = columns.len() - 1;
+    let selected: Vec<String> = columns.iter().map(|&c| names[c].clone()).collect();
+
+    // a missing label cannot be imputed: the row is dropped unless the policy is fail
+    let before = rows.len();
+    let first_missing = rows.iter().position(|r| r.iter().any(|c| is_missing(c)));
+    match (&opts.missing, first_missing) {
+        (MissingPolicy::Fail, Some(i)) => {
+            let col = rows[i].iter().position(|c| is_missing(c)).unwrap_or(0);
+            return Err(format!(
+                "{} data row {}: missing value in column '{}' (choose a missing-value policy other than fail)",
+                opts.path,
+                i + 1,
+                selected[col]
+            ));
+        }
+        (MissingPolicy::Drop, _) => rows.retain(|r| !r.iter().any(|c| is_missing(c))),
+        _ => rows.retain(|r| !is_missing(&r[label_pos])),
+    }
+    if rows.len() < before {
+        println!("Dropped {} row(s) with missing values", before - rows.len());
+    }
+    if rows.is_empty() {
+        return Err("No rows left after handling missing values".into());
+    }
+
+    for col in 0..label_pos {
+        let fill = match opts.missing {
+            MissingPolicy::Constant(v) => {
+                let has_missing = rows.iter().any(|r| is_missing(&r[col]));
+                if has_missing && rows.iter().any(|r| !is_missing(&r[col]) && r[col].parse::<f64>().is_err()) {
+                    return Err(format!(
+                        "Column '{}' is not numeric; a constant fill only applies to numeric columns (use drop)",
+                        selected[col]
+                    ));
+                }
+                v
+            }
+            MissingPolicy::Mean => {
+                let missing = rows.iter().filter(|r| is_missing(&r[col])).count();
+                if missing == 0 {
+                    continue;
+                }
+                let values: Vec<f64> = rows
+                    .iter()
+                    .filter(|r| !is_missing(&r[col]))
+                    .filter_map(|r| r[col].parse::<f64>().ok())
+                    .collect();
+                if values.len() + missing != rows.len() {
+                    return Err(format!("Column '{}' is not numeric; mean imputation needs numbers", selected[col]));
+                }
+                if values.is_empty() {
+                    return Err(format!("Column '{}' has no values to take a mean of", selected[col]));
+                }
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+            _ => continue,
+        };
+        for row in rows.iter_mut() {
+            if is_missing(&row[col]) {
+                row[col] = fill.to_string();
+            }
+        }
+    }
+
+    Ok(Table { names: selected, has_header, rows })
+}
+
+impl Table {
+    // Numeric feature rows for a model without a schema: every feature cell
+    // must be a number. The label column is left out.
+    pub fn numeric_features(&self) -> Result<Vec<Vec<f32>>, String> {
+        let label_pos = self.names.len() - 1;
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row[..label_pos]
+                    .iter()
+                    .zip(self.names.iter())
+                    .map(|(cell, name)| {
+                        cell.parse::<f32>()
+                            .map_err(|_| format!("Data row {}, column '{}': '{}' is not a number", i + 1, name, cell))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn print_summary(&self) {
+        println!("\nColumn summary ({} rows):", self.rows.len());
+        println!("  {:<20} {:<8} {:>14} {:>14} {:>14}", "column", "role", "min", "mean", "max");
+        let label_pos = self.names.len() - 1;
+        for (col, name) in self.names.iter().enumerate() {
+            let role = if col == label_pos { "label" } else { "feature" };
+            let values: Vec<f64> = self.rows.iter().filter_map(|r| r[col].parse::<f64>().ok()).collect();
+            if values.len() == self.rows.len() {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                println!("  {:<20} {:<8} {:>14.4} {:>14.4} {:>14.4}", name, role, min, mean, max);
+            } else {
+                let mut levels: Vec<&str> = self.rows.iter().map(|r| r[col].as_str()).collect();
+                levels.sort_unstable();
+                levels.dedup();
+                let shown: Vec<&str> = levels.iter().copied().take(5).collect();
+                let more = if levels.len() > shown.len() { ", ..." } else { "" };
+                println!(
+                    "  {:<20} {:<8} {} distinct: {}{}",
+                    name,
+                    role,
+                    levels.len(),
+                    shown.join(", "),
+                    more
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const CSV: &str = "age,region,bmi,y\n30,n,20.5,1\n,s,22,0\n50,s,?,1\n40,n,24,\n";
+
+    fn load(name: &str, csv: &str, configure: impl FnOnce(&mut IngestOptions)) -> Result<Table, String> {
+        let path = std::env::temp_dir().join(format!("verse-ingest-{}-{}.csv", std::process::id(), name));
+        fs::write(&path, csv).unwrap();
+        let path_str = path.to_str().unwrap().to_string();
+        let mut opts = IngestOptions {
+            path: &path_str,
+            format: Format::Csv,
+            delimiter: b',',
+            header: None,
+            label_column: None,
+            feature_columns: Vec::new(),
+            missing: MissingPolicy::Drop,
+        };
+        configure(&mut opts);
+        let table = load_table(&opts);
+        fs::remove_file(&path).unwrap();
+        table
+    }
+
+    #[test]
+    fn maps_columns_by_name_or_index() {
+        let table = load("names", CSV, |_| {}).unwrap();
+        assert!(table.has_header);
+        assert_eq!(table.names, ["age", "region", "bmi", "y"]);
+        assert_eq!(table.rows, [["30", "n", "20.5", "1"]]);
+
+        let table = load("pick", CSV, |o| {
+            o.label_column = Some("0");
+            o.feature_columns = vec!["bmi".into(), "3".into()];
+        })
+        .unwrap();
+        assert_eq!(table.names, ["bmi", "y", "age"]);
+        // rows missing a feature or the label go under the drop policy
+        assert_eq!(table.rows, [["20.5", "1", "30"]]);
+    }
+
+    #[test]
+    fn rejects_bad_column_selections() {
+        let err = load("twice", CSV, |o| o.feature_columns = vec!["age".into(), "0".into()]).err().unwrap();
+        assert!(err.contains("Column 'age' is listed more than once"), "{}", err);
+        let err = load("both", CSV, |o| o.feature_columns = vec!["age".into(), "y".into()]).err().unwrap();
+        assert!(err.contains("Column 'y' is both a feature and the label"), "{}", err);
+        let err = load("unknown", CSV, |o| o.label_column = Some("weight")).err().unwrap();
+        assert!(err.contains("Column 'weight' not found (columns: age, region, bmi, y)"), "{}", err);
+        let err = load("ragged", "1,2,3\n4,5\n", |_| {}).err().unwrap();
+        assert!(err.contains("data row 2: 2 columns, expected 3"), "{}", err);
+    }
+
+    #[test]
+    fn reads_headerless_files_by_index() {
+        let table = load("headerless", "1,2,0\n3,4,1\n", |o| o.label_column = Some("0")).unwrap();
+        assert!(!table.has_header);
+        assert_eq!(table.names, ["col1", "col2", "col0"]);
+        assert_eq!(table.rows, [["2", "0", "1"], ["4", "1", "3"]]);
+
+        // a forced header turns the first row into names
+        let table = load("forced", "1,2,0\n3,4,1\n", |o| o.header = Some(true)).unwrap();
+        assert_eq!(table.names, ["1", "2", "0"]);
+        assert_eq!(table.rows.len(), 1);
+    }
+
+    #[test]
+    fn applies_missing_value_policies() {
+        let err = load("fail", CSV, |o| o.missing = MissingPolicy::Fail).err().unwrap();
+        assert!(err.contains("data row 2: missing value in column 'age'"), "{}", err);
+
+        // the label-less last row is dropped under every other policy
+        let numeric = |name: &str, missing: MissingPolicy| {
+            load(name, CSV, |o| {
+                o.feature_columns = vec!["age".into(), "bmi".into()];
+                o.missing = missing;
+            })
+            .unwrap()
+            .rows
+        };
+        assert_eq!(numeric("drop", MissingPolicy::Drop), [["30", "20.5", "1"]]);
+        assert_eq!(
+            numeric("mean", MissingPolicy::Mean),
+            [["30", "20.5", "1"], ["40", "22", "0"], ["50", "21.25", "1"]]
+        );
+        assert_eq!(
+            numeric("constant", MissingPolicy::Constant(-1.0)),
+            [["30", "20.5", "1"], ["-1", "22", "0"], ["50", "-1", "1"]]
+        );
+    }
+
+    #[test]
+    fn imputes_only_numeric_columns() {
+        let csv = "region,age,y\nn,30,1\n,40,0\ns,,1\n";
+        let err = load("mean-text", csv, |o| o.missing = MissingPolicy::Mean).err().unwrap();
+        assert!(err.contains("Column 'region' is not numeric; mean imputation"), "{}", err);
+        let err = load("constant-text", csv, |o| o.missing = MissingPolicy::Constant(0.0)).err().unwrap();
+        assert!(err.contains("Column 'region' is not numeric; a constant fill"), "{}", err);
+
+        // a text column without gaps is left alone
+        let table = load("constant-gaps", csv.replace("\n,40", "\ns,40").as_str(), |o| {
+            o.missing = MissingPolicy::Constant(0.0)
+        })
+        .unwrap();
+        assert_eq!(table.rows[2], ["s", "0", "1"]);
+    }
+
+    #[test]
+    fn parses_policies_and_delimiters() {
+        assert_eq!("constant= 2.5".parse::<MissingPolicy>().unwrap(), MissingPolicy::Constant(2.5));
+        assert!("constant=x".parse::<MissingPolicy>().unwrap_err().contains("Invalid constant"));
+        assert!("zero".parse::<MissingPolicy>().unwrap_err().contains("Unknown missing-value policy 'zero'"));
+        assert_eq!(parse_delimiter("tab").unwrap(), b'\t');
+        assert_eq!(parse_delimiter(";").unwrap(), b';');
+        assert!(parse_delimiter(";;").is_err());
+        assert!(is_missing(" NaN ") && is_missing("?") && !is_missing("0"));
+    }
+}
@@ -0,0 +1,130 @@
+// Ed25519 signing keys for proof envelopes, one JSON file per key in the
+// config dir's keys/ directory (readable by the owner only):
+//
+//   { "name": "default", "public_key": "<hex>", "secret_key": "<hex>", "created_at": 1700000000 }
+//
+// `verse prove` signs with the key named by --key ("default" unless given);
+// `verse keys export --publish` stores its public key on the server, where
+// `verse verify` fetches it to check the signature.
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_KEY: &str = "default";
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    name: String,
+    public_key: String,
+    secret_key: String,
+    created_at: u64,
+}
+
+pub struct KeyPair {
+    pub name: String,
+    pub signing_key: SigningKey,
+    pub created_at: u64,
+}
+
+impl KeyPair {
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().as_bytes())
+    }
+}
+
+// Key names become file names, so keep them to a safe alphabet.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid key name '{}' (use letters, digits, '-' and '_')", name));
+    }
+    Ok(())
+}
+
+fn key_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+// Creates a new key pair in `dir`; an existing key of that name is only
+// replaced when `force` is set.
+pub fn generate(dir: &Path, name: &str, force: bool) -> Result<KeyPair, String> {
+    check_name(name)?;
+    let path = key_path(dir, name);
+    if !force && path.exists() {
+        return Err(format!(
+            "Key '{}' already exists ({}); use --force to replace it",
+            name,
+            path.display()
+        ));
+    }
+    let mut secret = [0u8; 32];
+    getrandom::getrandom(&mut secret).map_err(|e| format!("Failed to gather randomness: {}", e))?;
+    let pair = KeyPair {
+        name: name.to_string(),
+        signing_key: SigningKey::from_bytes(&secret),
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+    };
+    let file = KeyFile {
+        name: pair.name.clone(),
+        public_key: pair.public_key_hex(),
+        secret_key: hex::encode(secret),
+        created_at: pair.created_at,
+    };
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_vec_pretty(&file).expect("serialize key");
+    let write = || -> std::io::Result<()> {
+        let mut out = fs::File::create(&path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = out.metadata()?.permissions();
+            perms.set_mode(0o600);
+            out.set_permissions(perms)?;
+        }
+        out.write_all(&json)
+    };
+    write().map_err(|e| format!("Failed to write key to {}: {}", path.display(), e))?;
+    Ok(pair)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<KeyPair, String> {
+    check_name(name)?;
+    let path = key_path(dir, name);
+    let data = match fs::read_to_string(&path) {
+        Ok(d) => d,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(format!("No signing key '{}'; create one with `verse keys generate {}`", name, name));
+        }
+        Err(e) => return Err(format!("Failed to read key file ({}): {}", path.display(), e)),
+    };
+    let file: KeyFile =
+        serde_json::from_str(&data).map_err(|e| format!("Invalid key file ({}): {}", path.display(), e))?;
+    let secret: [u8; 32] = hex::decode(&file.secret_key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("Invalid secret key in {}", path.display()))?;
+    let pair = KeyPair { name: file.name, signing_key: SigningKey::from_bytes(&secret), created_at: file.created_at };
+    if pair.public_key_hex() != file.public_key {
+        return Err(format!("Public key in {} does not match its secret key", path.display()));
+    }
+    Ok(pair)
+}
+
+// Every key in `dir`, sorted by name; a missing directory has none.
+pub fn list(dir: &Path) -> Result<Vec<KeyPair>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+        .collect();
+    names.sort();
+    names.iter().map(|name| load(dir, name)).collect()
+}
@@ -1,3 +1,22 @@
+mod config;
+#[cfg_attr(not(feature = "onnxruntime"), allow(dead_code))]
+mod dataset;
+mod guest;
+mod ingest;
+mod keys;
+#[cfg(feature = "onnxruntime")]
+mod model_check;
+#[cfg(feature = "onnxruntime")]
+mod model_eval;
+mod onnx_import;
+#[cfg(feature = "onnxruntime")]
+mod onnx_runtime;
+mod readers;
+mod report;
+mod schema;
+#[cfg_attr(not(feature = "onnxruntime"), allow(dead_code))]
+mod tree_eval;
+
 use clap::{Arg, Command};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -6,7 +25,7 @@ use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
 use serde_json::Value;
-use risc0_zkvm::Receipt;
+use verse_common::{envelope, preprocess, receipt};
 
 #[derive(Serialize)]
 struct RegisterRequest<'a> {
@@ -49,6 +68,17 @@ fn auth_path() -> PathBuf {
     config_dir().join("auth.json")
 }
 
+// Exit code Zk-host uses when a run trips one of its limits.
+const LIMIT_EXIT_CODE: i32 = 3;
+
+fn config_path() -> PathBuf {
+    config_dir().join("config.json")
+}
+
+fn keys_dir() -> PathBuf {
+    config_dir().join("keys")
+}
+
 fn save_auth(auth: &AuthStore) -> std::io::Result<()> {
     let dir = config_dir();
     fs::create_dir_all(&dir)?;
@@ -75,125 +105,116 @@ fn load_auth() -> Result<AuthStore, String> {
     Ok(auth)
 }
 
-// Load a CSV file into a 2D array (Vec<Vec<f32>>). Skips empty lines and optional header row.
-fn load_csv_as_2d(path: &str) -> Result<Vec<Vec<f32>>, String> {
-    let text = fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read CSV file ({}): {}", path, e))?;
-    let mut rows: Vec<Vec<f32>> = Vec::new();
-    for (i, line) in text.lines().enumerate() {
-        let line = line.trim();
-        if line.is_empty() { continue; }
-        if i == 0 {
-            // If first line has any non-float token, treat it as header and skip
-            let toks: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
-            if toks.iter().any(|t| t.parse::<f32>().is_err()) {
-                continue;
-            }
-        }
-        let mut row: Vec<f32> = Vec::new();
-        for tok in line.split(',').map(|s| s.trim()) {
-            match tok.parse::<f32>() {
-                Ok(v) => row.push(v),
-                Err(e) => return Err(format!("Line {}: failed to parse '{}' as f32: {}", i + 1, tok, e)),
-            }
-        }
-        if !row.is_empty() { rows.push(row); }
-    }
-    if rows.is_empty() { return Err("CSV contained no numeric rows".into()); }
-    Ok(rows)
+// String array field from an API response; missing or malformed gives [].
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|x| x.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
 }
 
-// Generate Rust code for get_dataset() from 2D rows (last column is label)
-fn generate_get_dataset_code(rows: &[Vec<f32>]) -> Result<String, String> {
-    if rows.is_empty() { return Err("No rows to generate dataset".into()); }
-    let mut feat_len: Option<usize> = None;
-    let mut items: Vec<String> = Vec::with_capacity(rows.len());
-    for (i, r) in rows.iter().enumerate() {
-        if r.len() < 2 { return Err(format!("Row {} has fewer than 2 columns", i + 1)); }
-        let flen = r.len() - 1;
-        if let Some(expected) = feat_len {
-            if expected != flen { return Err(format!("Inconsistent columns at row {}: expected {} features before label, got {}", i + 1, expected, flen)); }
-        } else {
-            feat_len = Some(flen);
-        }
-        let (features, label) = r.split_at(flen);
-        let feats_str = features
+// Comma-separated names from a flag, trimmed; empty entries are dropped.
+fn split_names(s: Option<&String>) -> Vec<String> {
+    s.map(|s| s.split(',').map(str::trim).filter(|n| !n.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+// A request's image ID from its proofHash: eight u32s, either a JSON array or
+// a string like "[1,2,...]" or "1,2,...".
+fn parse_image_id(v: Option<&Value>) -> Result<[u32; 8], String> {
+    let parse_err = || "Invalid or missing proofHash in response; expected a comma-separated 8 u32 values or JSON array string.".to_string();
+    let nums: Vec<u32> = match v {
+        Some(Value::String(s)) => s
+            .trim()
+            .trim_matches(|c| c == '[' || c == ']')
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse::<u32>().map_err(|_| parse_err()))
+            .collect::<Result<_, _>>()?,
+        Some(Value::Array(arr)) => arr
             .iter()
-            .map(|v| {
-                let s = v.to_string();
-                if s.contains('.') { s } else { format!("{}.0", s) }
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let lbl_s = {
-            let s = label[0].to_string();
-            if s.contains('.') { s } else { format!("{}.0", s) }
-        };
-        items.push(format!("        (vec![{}], {}),", feats_str, lbl_s));
-    }
-    let body = items.join("\n");
-    let code = format!(
-        "fn get_dataset() -> Vec<(Vec<f32>, f32)> {{\n    vec![\n{}\n    ]\n}}\n",
-        body
-    );
-    Ok(code)
+            .map(|v| v.as_u64().and_then(|n| u32::try_from(n).ok()).ok_or_else(parse_err))
+            .collect::<Result<_, _>>()?,
+        _ => return Err(parse_err()),
+    };
+    nums.try_into().map_err(|_| parse_err())
 }
 
-// Replace the get_dataset() function implementation in a template file.
-fn write_dataset_to_template(template_path: &str, rows: &[Vec<f32>]) -> Result<(), String> {
-    let mut content = fs::read_to_string(template_path)
-        .map_err(|e| format!("Failed to read template ({}): {}", template_path, e))?;
-    let new_fn = generate_get_dataset_code(rows)?;
-
-    // Find existing get_dataset signature line index boundaries and replace up to the next lone '}'
-    let lines: Vec<&str> = content.lines().collect();
-    let mut start_idx: Option<usize> = None;
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim_start().starts_with("fn get_dataset() -> Vec<(Vec<f32>, f32)>") {
-            start_idx = Some(i);
-            break;
-        }
+// A model as published by GET /api/model/.
+fn fetch_model(base: &str, model_id: &str) -> Result<Value, String> {
+    let endpoint = format!("{}/api/model/", base);
+    let resp = reqwest::blocking::Client::new()
+        .get(endpoint)
+        .send()
+        .map_err(|e| format!("HTTP request error: {}", e))?;
+    let status = resp.status();
+    let body = resp.text().map_err(|e| format!("Failed to read response body: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Model lookup failed ({}): {}", status, body));
     }
-    let start = start_idx.ok_or("Failed to find get_dataset() in template")?;
-    // Find the closing brace line for this function
-    let mut end = None;
-    for i in start+1..lines.len() {
-        if lines[i].trim() == "}" {
-            end = Some(i);
-            break;
-        }
-    }
-    let end = end.ok_or("Failed to find end of get_dataset() in template")?;
-
-    // Rebuild file: lines before start, then new_fn, then lines after end
-    let before = lines[..start].join("\n");
-    let after = if end + 1 < lines.len() { lines[end+1..].join("\n") } else { String::new() };
-    let mut new_content = String::new();
-    if !before.is_empty() { new_content.push_str(&before); new_content.push('\n'); }
-    new_content.push_str(&new_fn);
-    if !after.is_empty() { new_content.push_str(&after); new_content.push('\n'); }
-
-    fs::write(template_path, new_content)
-        .map_err(|e| format!("Failed to write template ({}): {}", template_path, e))?
-        ;
-    Ok(())
+    let models: Value = serde_json::from_str(&body).map_err(|e| format!("Failed to parse models: {}", e))?;
+    models
+        .as_array()
+        .and_then(|items| items.iter().find(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id)))
+        .cloned()
+        .ok_or_else(|| format!("Model {} not found", model_id))
 }
 
-// Copy the updated template into the actual guest code location.
-// Expects guest_dir to be the workspace root of the guest (containing `methods/guest/src/main.rs`).
-fn copy_template_to_guest(template_path: &str, guest_dir: &str) -> Result<PathBuf, String> {
-    let content = fs::read_to_string(template_path)
-        .map_err(|e| format!("Failed to read template ({}): {}", template_path, e))?;
-    let guest_main = PathBuf::from(guest_dir).join("methods/guest/src/main.rs");
-    if !guest_main.exists() {
-        return Err(format!(
-            "Guest main not found at {} (dir was '{}'). Ensure the path is correct.",
-            guest_main.display(), guest_dir
-        ));
-    }
-    fs::write(&guest_main, content)
-        .map_err(|e| format!("Failed to write guest main ({}): {}", guest_main.display(), e))?;
-    Ok(guest_main)
+// The rows embedded in the guest: features then label. The table is checked
+// against the model's schema when it publishes one (a vectorFormat written
+// before the schema language is free text and skipped), and its categorical
+// columns are encoded with the model's preprocessing spec when there is one.
+// Without a schema, label cells naming one of the model's classes become that
+// class's index. Values that are not finite (inf, NaN, or too large for f32)
+// are rejected with their row and column.
+fn build_rows(
+    table: &ingest::Table,
+    schema: Option<&schema::Schema>,
+    preprocessing: Option<&preprocess::Preprocessing>,
+    class_labels: &[String],
+) -> Result<Vec<Vec<f32>>, String> {
+    let checked = match schema {
+        Some(schema) => {
+            println!("\nChecking dataset against the model's schema:");
+            schema.print();
+            Some(schema.validate(table.has_header.then_some(table.names.as_slice()), &table.rows)?)
+        }
+        None => {
+            println!("Model's vectorFormat is legacy free text, not a schema; dataset is not validated.");
+            None
+        }
+    };
+
+    let label_pos = table.names.len() - 1;
+    let features = match (preprocessing, checked.as_ref()) {
+        (Some(preprocessing), _) => {
+            println!("\nEncoding features with the model's preprocessing (scaling runs in the guest):");
+            preprocessing.print();
+            preprocessing.encode_rows(&table.names[..label_pos], &table.rows)?
+        }
+        (None, Some(rows)) => rows.iter().map(|r| r[..label_pos].to_vec()).collect(),
+        (None, None) => table.numeric_features()?,
+    };
+    features
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut row)| {
+            let label = match checked.as_ref() {
+                Some(rows) => rows[i][label_pos],
+                None => ingest::label_value(&table.rows[i][label_pos], class_labels)
+                    .map_err(|e| format!("Data row {}: {}", i + 1, e))?,
+            };
+            row.push(label);
+            // inf and NaN have no float literal in the generated guest source.
+            // Encoded rows are not in column order, but their features are
+            // already checked by encode_rows, so only the label can be left.
+            if let Some(j) = row.iter().position(|v| !v.is_finite()) {
+                let column = if j + 1 == row.len() { &table.names[label_pos] } else { &table.names[j] };
+                return Err(format!("Data row {}, column '{}': {} is not a finite number", i + 1, column, row[j]));
+            }
+            Ok(row)
+        })
+        .collect()
 }
 
 fn pretty_print_models(body: &str) {
@@ -208,11 +229,22 @@ fn pretty_print_models(body: &str) {
                 let id = item.get("id").and_then(|v| v.as_str()).unwrap_or("-");
                 let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("-");
                 let vector = item.get("vectorFormat").and_then(|v| v.as_str()).unwrap_or("-");
+                let features = string_list(item.get("featureNames"));
+                let classes = string_list(item.get("classLabels"));
                 let created = item.get("createdAt").and_then(|v| v.as_str()).unwrap_or("-");
                 let updated = item.get("updatedAt").and_then(|v| v.as_str()).unwrap_or("-");
                 println!("\n{}. {}", i + 1, name);
                 println!("   id:           {}", id);
                 println!("   vectorFormat: {}", vector);
+                if !features.is_empty() {
+                    println!("   features:     {}", features.join(", "));
+                }
+                if !classes.is_empty() {
+                    println!("   classes:      {}", classes.join(", "));
+                }
+                if let Some(columns) = item.get("preprocessing").and_then(|p| p.get("columns")).and_then(|c| c.as_array()) {
+                    println!("   preprocessing: {} column(s)", columns.len());
+                }
                 println!("   createdAt:    {}", created);
                 println!("   updatedAt:    {}", updated);
             }
@@ -376,6 +408,30 @@ fn main() {
                 .help("Sets your name")
                 .value_name("NAME"),
         )
+        .subcommand(
+            Command::new("init")
+                .about("Create a verifier guest workspace (methods crate, guest crate, host exporter, toolchain) ready for `verse request`")
+                .arg(
+                    Arg::new("dir")
+                        .help("Directory to create the workspace in")
+                        .value_name("DIR")
+                        .default_value(guest::DEFAULT_DIR),
+                )
+                .arg(
+                    Arg::new("kind")
+                        .long("kind")
+                        .help("Starter dataset the guest is created with: regression, classification, tree or all; every kind runs every model and `verse request --dataset` replaces the data")
+                        .value_name("KIND")
+                        .value_parser(["all", "regression", "classification", "tree"])
+                        .default_value("all"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Overwrite the guest files in a non-empty directory")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
         .subcommand(
             Command::new("request")
                 .about("Build the ZK guest and send a validation request for a model")
@@ -399,9 +455,9 @@ fn main() {
                     Arg::new("dir")
                         .short('d')
                         .long("dir")
-                        .help("Path to the ZK-guest workspace directory")
+                        .help("Path to the guest workspace directory (create one with `verse init`)")
                         .value_name("PATH")
-                        .default_value("../ZK-guest"),
+                        .default_value(guest::DEFAULT_DIR),
                 )
                 .arg(
                     Arg::new("elf")
@@ -413,9 +469,67 @@ fn main() {
                 .arg(
                     Arg::new("dataset")
                         .long("dataset")
-                        .help("Path to a dataset CSV file to load as a 2D array")
-                        .value_name("CSV_PATH")
+                        .help("Path to a dataset (CSV, NDJSON, Parquet, .npy or .npz) to load as a 2D array; checked against the model's vectorFormat schema")
+                        .value_name("PATH")
                         .required(false),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Dataset format: csv, ndjson, parquet, npy or npz (default: from the file extension)")
+                        .value_name("FORMAT")
+                        .requires("dataset"),
+                )
+                .arg(
+                    Arg::new("label-column")
+                        .long("label-column")
+                        .help("Label column, by header name or 0-based index (default: last column)")
+                        .value_name("COLUMN")
+                        .requires("dataset"),
+                )
+                .arg(
+                    Arg::new("feature-columns")
+                        .long("feature-columns")
+                        .help("Comma-separated feature columns in vector order, by name or index (default: all but the label)")
+                        .value_name("COLUMNS")
+                        .requires("dataset"),
+                )
+                .arg(
+                    Arg::new("delimiter")
+                        .long("delimiter")
+                        .help("Field delimiter: a single character, or 'tab'")
+                        .value_name("CHAR")
+                        .default_value(","),
+                )
+                .arg(
+                    Arg::new("header")
+                        .long("header")
+                        .help("The CSV's first row is a header (default: guessed, a header when none of its cells is a number)")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("no-header")
+                        .requires("dataset"),
+                )
+                .arg(
+                    Arg::new("no-header")
+                        .long("no-header")
+                        .help("The CSV's first row is data, even when none of its cells is a number (e.g. all categorical)")
+                        .action(clap::ArgAction::SetTrue)
+                        .requires("dataset"),
+                )
+                .arg(
+                    Arg::new("missing")
+                        .long("missing")
+                        .help("Missing values (empty, NA, NaN, null, ?): fail, drop, mean or constant=<value>; rows missing the label are dropped unless fail")
+                        .value_name("POLICY")
+                        .default_value("fail"),
+                )
+                .arg(
+                    Arg::new("task")
+                        .long("task")
+                        .help("classification (labels embedded as class indexes for tree and classifier models) or regression (numeric targets); detected from the schema, class labels and label values when omitted")
+                        .value_name("TASK")
+                        .value_parser(["classification", "regression"])
+                        .requires("dataset"),
                 ),
         )
         .subcommand(
@@ -457,7 +571,7 @@ fn main() {
                     Arg::new("model-id")
                         .long("model-id")
                         .short('m')
-                        .help("The model ID associated with the request (for info only)")
+                        .help("The model ID associated with the request; must match the request and is recorded in the signed envelope")
                         .value_name("MODEL_ID")
                         .required(true),
                 )
@@ -482,11 +596,61 @@ fn main() {
                         .help("Path to a dataset CSV file to include with the request")
                         .value_name("CSV_PATH")
                         .required(false),
+                )
+                .arg(
+                    Arg::new("max-cycles")
+                        .long("max-cycles")
+                        .help("Stop the guest after this many cycles (0 = no limit; default from limits.max_cycles in config.json)")
+                        .value_name("CYCLES")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .help("Wall-clock limit for executing and proving, in seconds (0 = no limit; default from limits.timeout_secs)")
+                        .value_name("SECS")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("max-journal-bytes")
+                        .long("max-journal-bytes")
+                        .help("Reject guests that commit a larger journal (0 = no limit; default from limits.max_journal_bytes)")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("receipt-kind")
+                        .long("receipt-kind")
+                        .help("composite keeps the prover's per-segment receipt; succinct compresses it locally into a single constant-size STARK, which is much smaller to store and download")
+                        .value_name("KIND")
+                        .value_parser(["composite", "succinct"])
+                        .default_value("composite"),
+                )
+                .arg(
+                    Arg::new("receipt-encoding")
+                        .long("receipt-encoding")
+                        .help("How the receipt is written and uploaded: bincode or cbor (compact binary with a format header, saved as proof.receipt) or json (proof.json)")
+                        .value_name("ENCODING")
+                        .value_parser(["bincode", "cbor", "json"])
+                        .default_value("bincode"),
+                )
+                .arg(
+                    Arg::new("key")
+                        .long("key")
+                        .help("Name of the signing key (see `verse keys`) the proof envelope is signed with")
+                        .value_name("NAME")
+                        .default_value(keys::DEFAULT_KEY),
+                )
+                .arg(
+                    Arg::new("execute-only")
+                        .long("execute-only")
+                        .help("Dry run: execute the ELF with your model spec and report cycles, segments, journal size, decoded outputs and an estimated proving time; no receipt is produced or uploaded")
+                        .action(clap::ArgAction::SetTrue),
                 ),
         )
         .subcommand(
             Command::new("verify")
-                .about("Download the proof JSON for a validation request and verify it using RISC Zero")
+                .about("Download the proof for a validation request (binary or JSON receipt) and verify it using RISC Zero")
                 .arg(
                     Arg::new("request-id")
                         .long("request-id")
@@ -498,11 +662,48 @@ fn main() {
                 .arg(
                     Arg::new("out")
                         .long("out")
-                        .help("Optional path to save downloaded proof.json; defaults to ./proof.json")
+                        .help("Optional path to save the downloaded receipt; defaults to ./proof.receipt, or ./proof.json for a JSON receipt")
                         .value_name("FILE")
                         .required(false),
                 ),
         )
+        .subcommand(
+            Command::new("keys")
+                .about("Manage the Ed25519 keys `verse prove` signs proof envelopes with")
+                .subcommand(
+                    Command::new("generate")
+                        .about("Create a signing key pair in the config dir")
+                        .arg(
+                            Arg::new("name")
+                                .help("Key name")
+                                .value_name("NAME")
+                                .default_value(keys::DEFAULT_KEY),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .help("Replace an existing key of the same name (proofs signed with it will no longer match a republished key)")
+                                .action(clap::ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(Command::new("list").about("List your signing keys and their public keys"))
+                .subcommand(
+                    Command::new("export")
+                        .about("Print a key's public key (hex), or publish it as your account's key so verifiers can check your proofs")
+                        .arg(
+                            Arg::new("name")
+                                .help("Key name")
+                                .value_name("NAME")
+                                .default_value(keys::DEFAULT_KEY),
+                        )
+                        .arg(
+                            Arg::new("publish")
+                                .long("publish")
+                                .help("Store the public key on the server (replaces the one published before)")
+                                .action(clap::ArgAction::SetTrue),
+                        ),
+                ),
+        )
         .subcommand(
             Command::new("model")
                 .about("Manage your models")
@@ -521,7 +722,7 @@ fn main() {
                     Command::new("new")
                         .about("Create a new model")
                         .long_about(
-                            "Create a new model for the authenticated user.\n\nFields:\n  - vectorFormat: The size/order of the input vector and which feature goes at each index.\n  - name: A short name for the model (e.g., Skin cancer prediction).\n  - description: More about how the model was trained and what its predictions mean.\n\nExample JSON body that the API receives:\n{\n  \"vectorFormat\": \"len=3; x[0]=age, x[1]=bmi, x[2]=bp\",\n  \"name\": \"Skin cancer prediction\",\n  \"description\": \"Trained on dermatoscopic images; outputs malignancy probability.\"\n}"
+                            "Create a new model for the authenticated user.\n\nFields:\n  - vectorFormat: A schema for the input vector and label: ordered fields `name: type [min, max]` separated by `;`, with the label last and marked `label`. Types are float, int, bool and cat(level|level|...); ranges are optional and either bound may be left out. verse request checks datasets against it.\n  - name: A short name for the model (e.g., Skin cancer prediction).\n  - description: More about how the model was trained and what its predictions mean.\n  - featureNames: Input names, in vector order; taken from the schema when omitted.\n  - classLabels: Class names, in class index order; taken from a cat label when omitted. verify reports show these instead of indices.\n  - preprocessing: Optional encoders (one_hot, ordinal, hashing) and scalers (standard, min_max) per raw column; verse request encodes the verifier's dataset with it and the guest applies the scaling.\n\nExample JSON body that the API receives:\n{\n  \"vectorFormat\": \"age: int [0, 120]; bmi: float [10,]; bp: float; label diagnosis: cat(benign|malignant)\",\n  \"name\": \"Skin cancer prediction\",\n  \"description\": \"Trained on dermatoscopic images; outputs malignancy probability.\",\n  \"featureNames\": [\"age\", \"bmi\", \"bp\"],\n  \"classLabels\": [\"benign\", \"malignant\"]\n}"
                         )
                         .after_help(
                             "Tip: ensure you are logged in (verse login) so your JWT is available for Authorization."
@@ -529,7 +730,7 @@ fn main() {
                         .arg(
                             Arg::new("vector-format")
                                 .long("vector-format")
-                                .help("Vector format description, e.g. feature index mapping")
+                                .help("Input schema, e.g. 'age: int [0, 120]; bmi: float; label diagnosis: cat(benign|malignant)'")
                                 .value_name("FORMAT")
                                 .required(true),
                         )
@@ -546,12 +747,136 @@ fn main() {
                                 .help("Description: how the model was trained and what its predictions mean")
                                 .value_name("TEXT")
                                 .required(false),
+                        )
+                        .arg(
+                            Arg::new("feature-names")
+                                .long("feature-names")
+                                .help("Comma-separated input names in vector order, e.g. age,bmi,bp")
+                                .value_name("NAMES")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("class-labels")
+                                .long("class-labels")
+                                .help("Comma-separated class names in class index order, for classifiers")
+                                .value_name("NAMES")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("preprocessing")
+                                .long("preprocessing")
+                                .help("JSON file with the encoders and scalers from raw dataset columns to the model input")
+                                .value_name("FILE")
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Convert an ONNX model into a model spec for Zk-host")
+                        .long_about(
+                            "Convert an ONNX export into the model spec JSON read by Zk-host.\n\nSupported: TreeEnsembleClassifier/Regressor, LinearRegressor, LinearClassifier and Gemm/MatMul + Add networks with Relu, Sigmoid, Tanh and Softmax.\nTree models also get a tree ensemble document written next to the spec.\nA report of the graph's ops is printed; nothing is written if any op is unsupported."
+                        )
+                        .arg(
+                            Arg::new("onnx")
+                                .long("onnx")
+                                .help("Path to the ONNX model")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("out")
+                                .long("out")
+                                .help("Where to write the model spec")
+                                .value_name("FILE")
+                                .default_value("model_spec.json"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("check")
+                        .about("Check that a tree model spec agrees with its ONNX model")
+                        .long_about(
+                            "Run a dataset through onnxruntime and through the tree JSON the ZK guest evaluates, and compare labels and probabilities (or regression outputs) row by row.\n\n--spec is a tree JSON or a model spec with tree_path, as written by verse model import.\nDataset rows hold the model's features, optionally followed by a label column, which is ignored.\nThe first divergent row is reported with its decision path; the exit code is 1 if any row diverges."
+                        )
+                        .arg(
+                            Arg::new("onnx")
+                                .long("onnx")
+                                .help("Path to the ONNX model")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("spec")
+                                .long("spec")
+                                .help("Tree JSON, or a model spec with tree_path")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("dataset")
+                                .long("dataset")
+                                .help("Dataset of feature rows (CSV, NDJSON, Parquet, .npy or .npz)")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("tolerance")
+                                .long("tolerance")
+                                .help("Largest allowed difference per probability or output")
+                                .value_name("EPS")
+                                .value_parser(clap::value_parser!(f64))
+                                .default_value("1e-5"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("eval")
+                        .about("Evaluate an ONNX model on a dataset with onnxruntime (no proof)")
+                        .long_about(
+                            "Run a dataset through an ONNX model with onnxruntime, in batches, and print the same metric report verse verify prints for a proof.\n\nInput and output names are read from the model. Rows hold the features followed by the label: CSV rows, NDJSON {\"features\": [...], \"label\": ...} objects or arrays, Parquet columns, a 2-D .npy array, or X and y arrays in an .npz."
+                        )
+                        .arg(
+                            Arg::new("onnx")
+                                .long("onnx")
+                                .help("Path to the ONNX model")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("dataset")
+                                .long("dataset")
+                                .help("Dataset (.csv, .ndjson, .jsonl, .parquet, .npy, .npz)")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("batch-size")
+                                .long("batch-size")
+                                .help("Rows per onnxruntime call")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(usize))
+                                .default_value("256"),
                         ),
                 ),
         )
         .get_matches();
 
     match matches.subcommand() {
+        Some(("init", sub_m)) => {
+            let dir = sub_m.get_one::<String>("dir").map(String::as_str).unwrap_or(guest::DEFAULT_DIR);
+            let kind: guest::Kind = match sub_m.get_one::<String>("kind").map(String::as_str).unwrap_or("all").parse() {
+                Ok(k) => k,
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+            };
+            match guest::init(std::path::Path::new(dir), kind, sub_m.get_flag("force")) {
+                Ok(files) => {
+                    for f in files.iter() {
+                        println!("  created {}", f.display());
+                    }
+                    println!("\nGuest workspace ready in {}. Embed your dataset and send a request with:", dir);
+                    println!("  verse request --model-id <MODEL_ID> --dataset <FILE> --dir {}", dir);
+                }
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+            }
+        }
         Some(("request", sub_m)) => {
             if sub_m.get_flag("list") {
                 let auth = match load_auth() { Ok(a) => a, Err(e) => { eprintln!("{}", e); std::process::exit(1); } };
@@ -585,44 +910,106 @@ fn main() {
             let dir = sub_m
                 .get_one::<String>("dir")
                 .map(String::as_str)
-                .unwrap_or("ZK-guest");
+                .unwrap_or(guest::DEFAULT_DIR);
 
             let explicit_elf = sub_m.get_one::<String>("elf").map(String::as_str);
             let dataset_path = sub_m.get_one::<String>("dataset").map(String::as_str);
 
-            // If dataset path provided, load it now as 2D array
-            if let Some(csv_path) = dataset_path {
-                match load_csv_as_2d(csv_path) {
+            // If dataset path provided, load it now as 2D array, checked against
+            // the model's published schema when it has one
+            if let Some(data_path) = dataset_path {
+                let ingest_opts = ingest::IngestOptions {
+                    path: data_path,
+                    format: match readers::Format::detect(data_path, sub_m.get_one::<String>("format").map(String::as_str)) {
+                        Ok(f) => f,
+                        Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                    },
+                    delimiter: match ingest::parse_delimiter(sub_m.get_one::<String>("delimiter").map(String::as_str).unwrap_or(",")) {
+                        Ok(d) => d,
+                        Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                    },
+                    header: match (sub_m.get_flag("header"), sub_m.get_flag("no-header")) {
+                        (true, _) => Some(true),
+                        (_, true) => Some(false),
+                        _ => None,
+                    },
+                    label_column: sub_m.get_one::<String>("label-column").map(String::as_str),
+                    feature_columns: split_names(sub_m.get_one::<String>("feature-columns")),
+                    missing: match sub_m.get_one::<String>("missing").map(String::as_str).unwrap_or("fail").parse() {
+                        Ok(p) => p,
+                        Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                    },
+                };
+                let table = match ingest::load_table(&ingest_opts) {
+                    Ok(t) => t,
+                    Err(e) => { eprintln!("Failed to load dataset: {}", e); std::process::exit(1); }
+                };
+                table.print_summary();
+                let url = std::env::var("VERSE_API_URL").unwrap_or_else(|_| "http://127.0.0.1:8000".to_string());
+                let model = match fetch_model(url.trim_end_matches('/'), model_id) {
+                    Ok(m) => m,
+                    Err(e) => { eprintln!("Failed to fetch the model: {}", e); std::process::exit(1); }
+                };
+                let schema = match model.get("vectorFormat").and_then(|v| v.as_str()) {
+                    Some(f) if !schema::is_legacy(f) => match schema::Schema::parse(f) {
+                        Ok(s) => Some(s),
+                        Err(e) => { eprintln!("The model's published schema is invalid: {}", e); std::process::exit(1); }
+                    },
+                    _ => None,
+                };
+                let preprocessing = match model.get("preprocessing").filter(|v| !v.is_null()) {
+                    Some(v) => match preprocess::Preprocessing::parse(&v.to_string()) {
+                        Ok(p) => Some(p),
+                        Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                    },
+                    None => None,
+                };
+                let class_labels = string_list(model.get("classLabels"));
+                let loaded = build_rows(&table, schema.as_ref(), preprocessing.as_ref(), &class_labels);
+                match loaded {
                     Ok(rows) => {
-                        let cols = rows.get(0).map(|r| r.len()).unwrap_or(0);
+                        let labels: Vec<f32> = rows.iter().map(|r| r[r.len() - 1]).collect();
+                        let task = match sub_m.get_one::<String>("task") {
+                            Some(t) => match t.parse::<ingest::Task>() {
+                                Ok(task) => { println!("Task: {} (--task)", task); task }
+                                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                            },
+                            None => {
+                                let (task, reason) = ingest::Task::detect(schema.as_ref(), &class_labels, &labels);
+                                println!("Task: {} (detected: {}; override with --task)", task, reason);
+                                task
+                            }
+                        };
+                        if let Err(e) = task.check_labels(&class_labels, &labels) {
+                            eprintln!("Dataset does not fit a {} task: {}", task, e);
+                            std::process::exit(1);
+                        }
+                        let cols = rows.first().map(|r| r.len()).unwrap_or(0);
                         println!(
                             "Loaded dataset: {} rows x {} cols from {}",
                             rows.len(),
                             cols,
-                            csv_path
+                            data_path
                         );
                         // Optional preview
                         let preview = rows.iter().take(3);
                         for (i, r) in preview.enumerate() {
                             println!("  row {:>3}: {:?}", i, r);
                         }
-                        // Write into template get_dataset() so guest can embed the dataset
-                        let template_path = {
-                            let base = env!("CARGO_MANIFEST_DIR");
-                            format!("{}/template.txt", base)
-                        };
-                        match write_dataset_to_template(&template_path, &rows) {
-                            Ok(_) => println!("Updated {} with get_dataset() from the loaded CSV.", template_path),
-                            Err(e) => { eprintln!("Failed to update template: {}", e); std::process::exit(1); }
-                        }
-                        // Copy the updated template into the guest code so it will be used on build/run
-                        match copy_template_to_guest(&template_path, dir) {
-                            Ok(guest_main) => println!("Copied template into guest: {}", guest_main.display()),
-                            Err(e) => { eprintln!("Failed to copy template into guest: {}", e); std::process::exit(1); }
+                        // Write into the guest's dataset functions so it embeds the dataset
+                        match guest::embed_dataset(std::path::Path::new(dir), &rows, task) {
+                            Ok(guest_main) => {
+                                let target = match task {
+                                    ingest::Task::Classification => "get_dataset_tree()",
+                                    ingest::Task::Regression => "get_dataset()",
+                                };
+                                println!("Updated {} in {} with the loaded dataset.", target, guest_main.display())
+                            }
+                            Err(e) => { eprintln!("Failed to update the guest: {}", e); std::process::exit(1); }
                         }
                     }
                     Err(e) => {
-                        eprintln!("Failed to load dataset CSV: {}", e);
+                        eprintln!("Failed to load dataset: {}", e);
                         std::process::exit(1);
                     }
                 }
@@ -649,18 +1036,18 @@ fn main() {
             let elf_path: PathBuf = if let Some(p) = explicit_elf {
                 PathBuf::from(p)
             } else {
-                PathBuf::from("../ZK-guest/LinearRegression_exported")
+                PathBuf::from(dir).join(guest::ELF_FILE)
             };
 
             if !elf_path.exists() { eprintln!("ELF file not found: {}", elf_path.display()); std::process::exit(1); }
 
-            let id_path = PathBuf::from(dir).join("LinearRegression_ID_exported");
+            let id_path = PathBuf::from(dir).join(guest::ID_FILE);
             let hash_value = match fs::read_to_string(&id_path) {
                 Ok(s) => s.trim().to_string(),
                 Err(e) => {
                     eprintln!(
-                        "Failed to read hash value from {}: {}. Ensure the guest wrote 'LinearRegression_ID_exported'.",
-                        id_path.display(), e
+                        "Failed to read hash value from {}: {}. Ensure the host exporter wrote '{}'.",
+                        id_path.display(), e, guest::ID_FILE
                     );
                     std::process::exit(1);
                 }
@@ -818,7 +1205,7 @@ fn main() {
             }
         }
         Some(("prove", sub_m)) => {
-            let _model_id = sub_m
+            let model_id = sub_m
                 .get_one::<String>("model-id")
                 .map(String::as_str)
                 .expect("--model-id is required");
@@ -859,6 +1246,27 @@ fn main() {
                 Ok(v) => v,
                 Err(e) => { eprintln!("Failed to parse request info JSON: {}", e); std::process::exit(1); }
             };
+            if let Some(request_model) = info_json.get("modelId").and_then(|v| v.as_str())
+                && request_model != model_id
+            {
+                eprintln!("Request {} is for model {}, not {}", request_id, request_model, model_id);
+                std::process::exit(1);
+            }
+            let image_id = match parse_image_id(info_json.get("proofHash")) {
+                Ok(id) => id,
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+            };
+            let execute_only = sub_m.get_flag("execute-only");
+            // Load the signing key up front so a missing key fails before proving
+            let signing_key = if execute_only {
+                None
+            } else {
+                let name = sub_m.get_one::<String>("key").map(String::as_str).unwrap_or(keys::DEFAULT_KEY);
+                match keys::load(&keys_dir(), name) {
+                    Ok(k) => Some(k),
+                    Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                }
+            };
             let elf_key_or_url = info_json.get("elfFileUrl").and_then(|v| v.as_str()).unwrap_or("");
             if elf_key_or_url.is_empty() { eprintln!("Request has no elfFileUrl"); std::process::exit(1); }
             let save_dir = PathBuf::from(zk_host_dir);
@@ -907,38 +1315,76 @@ fn main() {
             if let Err(e) = std::io::copy(&mut resp, &mut out) { eprintln!("Failed to save ELF to {}: {}", save_path.display(), e); std::process::exit(1); }
             println!("Saved ELF to {}", save_path.display());
 
-            println!("Starting prover in {}. When prompted for 'Enter path to guest ELF file:', type: guest-elf", save_dir.display());
-            let status = std::process::Command::new("cargo")
-                .arg("run")
-                .arg("--release")
-                .current_dir(&save_dir)
-                .status();
+            let mut limits = match config::Config::load(&config_path()) {
+                Ok(c) => c.limits,
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+            };
+            if let Some(&n) = sub_m.get_one::<u64>("max-cycles") { limits.max_cycles = Some(n); }
+            if let Some(&n) = sub_m.get_one::<u64>("timeout") { limits.timeout_secs = Some(n); }
+            if let Some(&n) = sub_m.get_one::<u64>("max-journal-bytes") { limits.max_journal_bytes = Some(n); }
+            println!("Limits: {}", limits.describe());
+            if execute_only {
+                println!("Starting executor (no proof) in {}. When prompted for 'Enter path to guest ELF file:', type: guest-elf", save_dir.display());
+            } else {
+                println!("Starting prover in {}. When prompted for 'Enter path to guest ELF file:', type: guest-elf", save_dir.display());
+            }
+            let mut host = std::process::Command::new("cargo");
+            host.arg("run").arg("--release").current_dir(&save_dir).envs(limits.env_vars());
+            host.env("VERSE_RECEIPT_KIND", sub_m.get_one::<String>("receipt-kind").map(String::as_str).unwrap_or("composite"));
+            let encoding = sub_m.get_one::<String>("receipt-encoding").map(String::as_str).unwrap_or("bincode");
+            host.env("VERSE_RECEIPT_ENCODING", encoding);
+            if execute_only {
+                host.env("VERSE_EXECUTE_ONLY", "1");
+            }
+            let status = host.status();
             match status {
                 Ok(s) => {
                     if !s.success() {
-                        if let Some(code) = s.code() { eprintln!("Prover exited with code {}", code); }
-                        else { eprintln!("Prover terminated by signal"); }
+                        match s.code() {
+                            Some(LIMIT_EXIT_CODE) => eprintln!("Prover stopped: a resource limit tripped (see the message above)"),
+                            Some(code) => eprintln!("Prover exited with code {}", code),
+                            None => eprintln!("Prover terminated by signal"),
+                        }
                         std::process::exit(1);
                     }
                 }
                 Err(e) => { eprintln!("Failed to start prover: {}", e); std::process::exit(1); }
             }
+            if execute_only {
+                println!("Execute-only run finished; no receipt was produced or uploaded.");
+                std::process::exit(0);
+            }
 
-            let proof_path = save_dir.join("proof.json");
+            // clap only accepts known encodings
+            let proof_name = encoding.parse::<receipt::Encoding>().unwrap_or_default().file_name();
+            let proof_path = save_dir.join(proof_name);
             if !proof_path.exists() {
                 eprintln!("Expected proof file not found at {}", proof_path.display());
                 std::process::exit(1);
             }
-            println!("Uploading proof from {}...", proof_path.display());
+            let receipt_bytes = match fs::read(&proof_path) {
+                Ok(b) => b,
+                Err(e) => { eprintln!("Failed to read proof file: {}", e); std::process::exit(1); }
+            };
+            let key = signing_key.expect("signing key is loaded unless --execute-only");
+            let header = envelope::Header {
+                request_id: request_id.to_string(),
+                model_id: model_id.to_string(),
+                image_id,
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+                tool_version: format!("verse {}", env!("CARGO_PKG_VERSION")),
+                public_key: [0; 32],
+            };
+            let sealed = envelope::seal(header, receipt_bytes, &key.signing_key);
+            let envelope_path = save_dir.join(envelope::FILE_NAME);
+            if let Err(e) = fs::write(&envelope_path, &sealed) { eprintln!("Failed to write {}: {}", envelope_path.display(), e); std::process::exit(1); }
+            println!("Signed {} with key '{}' ({})", proof_name, key.name, key.public_key_hex());
+            println!("Uploading proof envelope from {}...", envelope_path.display());
 
             let put_endpoint = format!("{}/api/model/proof/{}", base, request_id);
-            let proof_file = match fs::File::open(&proof_path) {
-                Ok(f) => f,
-                Err(e) => { eprintln!("Failed to open proof file: {}", e); std::process::exit(1); }
-            };
-            let proof_part = reqwest::blocking::multipart::Part::reader(proof_file)
-                .file_name("proof.json".to_string())
-                .mime_str("application/json").unwrap();
+            let proof_part = reqwest::blocking::multipart::Part::bytes(sealed)
+                .file_name(envelope::FILE_NAME)
+                .mime_str("application/octet-stream").unwrap();
             let form = reqwest::blocking::multipart::Form::new()
                 .part("json_file", proof_part);
 
@@ -966,10 +1412,7 @@ fn main() {
                 .get_one::<String>("request-id")
                 .map(String::as_str)
                 .expect("--request-id is required");
-            let out_path = sub_m
-                .get_one::<String>("out")
-                .map(String::as_str)
-                .unwrap_or("proof.json");
+            let out_path = sub_m.get_one::<String>("out").map(String::as_str);
 
             let auth = match load_auth() { Ok(a) => a, Err(e) => { eprintln!("{}", e); std::process::exit(1); } };
             let url = std::env::var("VERSE_API_URL").unwrap_or_else(|_| "http://127.0.0.1:8000".to_string());
@@ -1005,55 +1448,140 @@ fn main() {
             };
 
             println!("Downloading proof from: {}", download_url);
-            let mut resp = match client.get(&download_url).send() { Ok(r) => r, Err(e) => { eprintln!("Download request error: {}", e); std::process::exit(1); } };
+            let resp = match client.get(&download_url).send() { Ok(r) => r, Err(e) => { eprintln!("Download request error: {}", e); std::process::exit(1); } };
             if !resp.status().is_success() {
                 let status = resp.status();
                 let body = resp.text().unwrap_or_default();
                 eprintln!("Failed to download proof ({}): {}", status, body);
                 std::process::exit(1);
             }
-            let mut out = match fs::File::create(out_path) { Ok(f) => f, Err(e) => { eprintln!("Failed to create output file: {}", e); std::process::exit(1); } };
-            if let Err(e) = std::io::copy(&mut resp, &mut out) { eprintln!("Failed to save proof: {}", e); std::process::exit(1); }
+            let data = match resp.bytes() { Ok(b) => b.to_vec(), Err(e) => { eprintln!("Failed to read proof: {}", e); std::process::exit(1); } };
+            let signed = envelope::is_envelope(&data);
+            let out_path = out_path.map(str::to_string).unwrap_or_else(|| {
+                if signed { envelope::FILE_NAME.to_string() } else { format!("proof.{}", if receipt::is_binary(&data) { "receipt" } else { "json" }) }
+            });
+            if let Err(e) = fs::write(&out_path, &data) { eprintln!("Failed to save proof to {}: {}", out_path, e); std::process::exit(1); }
             println!("Saved proof to {}", out_path);
 
-            // Parse proofHash into [u32; 8]
-            let method_id_arr: [u32; 8] = {
-                // proofHash is provided as a string HASH_ID; expect a comma-separated list or JSON array-like string
-                let ph_val = info_json.get("proofHash").cloned().unwrap_or(Value::Null);
-                let parse_err = || {
-                    eprintln!("Invalid or missing proofHash in response; expected a comma-separated 8 u32 values or JSON array string.");
-                    std::process::exit(1);
-                };
-                let mut nums: Vec<u32> = Vec::new();
-                match ph_val {
-                    Value::String(s) => {
-                        // Accept formats like: "[1,2,3,4,5,6,7,8]" or "1,2,3,4,5,6,7,8"
-                        let s = s.trim().trim_matches(|c| c == '[' || c == ']');
-                        for part in s.split(',') {
-                            let p = part.trim();
-                            if p.is_empty() { continue; }
-                            match p.parse::<u32>() { Ok(n) => nums.push(n), Err(_) => parse_err() }
-                        }
-                    }
-                    Value::Array(arr) => {
-                        for v in arr { match v.as_u64() { Some(n) if n <= u32::MAX as u64 => nums.push(n as u32), _ => parse_err() } }
-                    }
-                    _ => parse_err(),
-                }
-                if nums.len() != 8 { parse_err(); }
-                [nums[0], nums[1], nums[2], nums[3], nums[4], nums[5], nums[6], nums[7]]
+            let method_id_arr = match parse_image_id(info_json.get("proofHash")) {
+                Ok(id) => id,
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
             };
 
-            // Deserialize receipt from saved proof
-            let data = match fs::read_to_string(out_path) { Ok(s) => s, Err(e) => { eprintln!("Failed to read proof file: {}", e); std::process::exit(1); } };
-            let receipt: Receipt = match serde_json::from_str(&data) { Ok(r) => r, Err(e) => { eprintln!("Failed to parse receipt JSON: {}", e); std::process::exit(1); } };
+            // Check who signed the proof before looking at the receipt inside.
+            // Once the owner has published a key, only envelopes signed with it
+            // are accepted; otherwise a stripped signature would still pass.
+            // Without a key the receipt is still verified, with a warning.
+            let owner_key = match info_json.get("model").and_then(|m| m.get("ownerPublicKey")).and_then(|v| v.as_str()) {
+                Some(k) if !k.is_empty() => match envelope::parse_public_key(k) {
+                    Ok(k) => Some(k),
+                    Err(e) => { eprintln!("Model owner's published key is invalid: {}", e); std::process::exit(1); }
+                },
+                _ => None,
+            };
+            let model_id = info_json.get("modelId").and_then(|v| v.as_str()).unwrap_or("");
+            let receipt_bytes = match envelope::open_for_owner(&data, owner_key.as_ref()).and_then(|(opened, inner)| {
+                if let envelope::Opened::Signed(h) | envelope::Opened::Unchecked(h) = &opened {
+                    h.check(request_id, model_id, method_id_arr)?;
+                }
+                Ok((opened, inner))
+            }) {
+                Ok((envelope::Opened::Signed(header), inner)) => {
+                    println!(
+                        "Signed by the model owner (key {}) at Unix time {} with {}",
+                        hex::encode(header.public_key),
+                        header.timestamp,
+                        header.tool_version
+                    );
+                    inner
+                }
+                Ok((envelope::Opened::Unchecked(header), inner)) => {
+                    println!(
+                        "⚠️  Signature not checked: the model owner has published no key (envelope claims key {})",
+                        hex::encode(header.public_key)
+                    );
+                    inner
+                }
+                Ok((envelope::Opened::Unsigned, inner)) => {
+                    println!("⚠️  Proof is not signed and the model owner has not published a signing key; who produced it cannot be checked");
+                    inner
+                }
+                Err(e) => { println!("❌ Signature check failed: {}", e); std::process::exit(1); }
+            };
+            let (receipt, encoding) = match receipt::decode(&receipt_bytes) {
+                Ok(r) => r,
+                Err(e) => { eprintln!("Failed to parse receipt: {}", e); std::process::exit(1); }
+            };
 
             // Verify
+            println!("Receipt kind: {} ({} encoding, {} bytes)", receipt::kind_name(&receipt), encoding, receipt_bytes.len());
             match receipt.verify(method_id_arr) {
-                Ok(_) => { println!("✅ Proof verified successfully!"); std::process::exit(0); }
+                Ok(_) => {
+                    println!("✅ Proof verified successfully! (checked a {} receipt)", receipt::kind_name(&receipt));
+                    let class_labels = string_list(info_json.get("model").and_then(|m| m.get("classLabels")));
+                    if !report::print_journal_report(&receipt.journal.bytes, &class_labels) {
+                        println!("Journal: {} bytes (layout not recognised; no metric report)", receipt.journal.bytes.len());
+                    }
+                    std::process::exit(0);
+                }
                 Err(e) => { println!("❌ Verification failed: {:?}", e); std::process::exit(1); }
             }
         }
+        Some(("keys", sub_m)) => match sub_m.subcommand() {
+            Some(("generate", sub_gen)) => {
+                let name = sub_gen.get_one::<String>("name").map(String::as_str).unwrap_or(keys::DEFAULT_KEY);
+                match keys::generate(&keys_dir(), name, sub_gen.get_flag("force")) {
+                    Ok(key) => {
+                        println!("Created key '{}' in {}", key.name, keys_dir().display());
+                        println!("Public key: {}", key.public_key_hex());
+                        println!("Publish it so verifiers can check your proofs: verse keys export {} --publish", key.name);
+                    }
+                    Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                }
+            }
+            Some(("list", _)) => match keys::list(&keys_dir()) {
+                Ok(list) if list.is_empty() => println!("No signing keys. Create one with `verse keys generate`."),
+                Ok(list) => {
+                    for key in list.iter() {
+                        println!("{:<16} {}  (created at Unix time {})", key.name, key.public_key_hex(), key.created_at);
+                    }
+                }
+                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+            },
+            Some(("export", sub_export)) => {
+                let name = sub_export.get_one::<String>("name").map(String::as_str).unwrap_or(keys::DEFAULT_KEY);
+                let key = match keys::load(&keys_dir(), name) {
+                    Ok(k) => k,
+                    Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                };
+                if !sub_export.get_flag("publish") {
+                    println!("{}", key.public_key_hex());
+                    std::process::exit(0);
+                }
+                let auth = match load_auth() { Ok(a) => a, Err(e) => { eprintln!("{}", e); std::process::exit(1); } };
+                let url = std::env::var("VERSE_API_URL").unwrap_or_else(|_| "http://127.0.0.1:8000".to_string());
+                let endpoint = format!("{}/api/users/public-key", url.trim_end_matches('/'));
+                let payload = serde_json::json!({ "publicKey": key.public_key_hex() });
+                match reqwest::blocking::Client::new()
+                    .put(&endpoint)
+                    .header(AUTHORIZATION, format!("Bearer {}", auth.access_token))
+                    .json(&payload)
+                    .send()
+                {
+                    Ok(resp) => {
+                        let status = resp.status();
+                        let body = resp.text().unwrap_or_default();
+                        if !status.is_success() { eprintln!("Publishing key failed ({}): {}", status, body); std::process::exit(1); }
+                        println!("Published key '{}' ({}) as your account's public key", key.name, key.public_key_hex());
+                    }
+                    Err(e) => { eprintln!("HTTP request error: {}", e); std::process::exit(1); }
+                }
+            }
+            _ => {
+                eprintln!("Use: verse keys generate [NAME] [--force] | verse keys list | verse keys export [NAME] [--publish]");
+                std::process::exit(2);
+            }
+        },
         Some(("model", sub_m)) => {
             let url = std::env::var("VERSE_API_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:8000".to_string());
@@ -1126,7 +1654,15 @@ fn main() {
                 }
                 Some(("new", sub_new)) => {
                     #[derive(Serialize)]
-                    struct ModelCreate<'a> { vectorFormat: &'a str, name: &'a str, description: Option<&'a str> }
+                    #[serde(rename_all = "camelCase")]
+                    struct ModelCreate<'a> {
+                        vector_format: &'a str,
+                        name: &'a str,
+                        description: Option<&'a str>,
+                        feature_names: Vec<String>,
+                        class_labels: Vec<String>,
+                        preprocessing: Option<preprocess::Preprocessing>,
+                    }
 
                     let auth = match load_auth() {
                         Ok(a) => a,
@@ -1137,7 +1673,56 @@ fn main() {
                     let name = sub_new.get_one::<String>("name").map(String::as_str).expect("--name is required");
                     let description = sub_new.get_one::<String>("description").map(String::as_str);
 
-                    let payload = ModelCreate { vectorFormat: vector_format, name, description };
+                    let schema = match schema::Schema::parse(vector_format) {
+                        Ok(s) => s,
+                        Err(e) => { eprintln!("Invalid --vector-format schema: {}", e); std::process::exit(1); }
+                    };
+
+                    // names default to the schema's; given explicitly they must agree with it
+                    let mut feature_names = split_names(sub_new.get_one::<String>("feature-names"));
+                    let mut class_labels = split_names(sub_new.get_one::<String>("class-labels"));
+                    if feature_names.is_empty() {
+                        feature_names = schema.feature_names();
+                    } else if feature_names != schema.feature_names() {
+                        eprintln!("--feature-names {:?} do not match the schema's features {:?}", feature_names, schema.feature_names());
+                        std::process::exit(1);
+                    }
+                    if class_labels.is_empty() {
+                        class_labels = schema.class_labels();
+                    } else if matches!(schema.label.ty, schema::FieldType::Categorical(_)) && class_labels != schema.class_labels() {
+                        eprintln!("--class-labels {:?} do not match the schema's label levels {:?}", class_labels, schema.class_labels());
+                        std::process::exit(1);
+                    }
+
+                    let preprocessing = match sub_new.get_one::<String>("preprocessing") {
+                        Some(path) => {
+                            let parsed = fs::read_to_string(path)
+                                .map_err(|e| format!("Failed to read {}: {}", path, e))
+                                .and_then(|text| preprocess::Preprocessing::parse(&text));
+                            match parsed {
+                                Ok(p) => Some(p),
+                                Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+                            }
+                        }
+                        None => None,
+                    };
+                    // preprocessing reads raw columns, which the schema names
+                    if let Some(p) = preprocessing.as_ref() {
+                        let names = schema.feature_names();
+                        if let Some(col) = p.columns.iter().find(|c| !names.contains(&c.name)) {
+                            eprintln!("Preprocessing column '{}' is not a feature of the schema", col.name);
+                            std::process::exit(1);
+                        }
+                    }
+
+                    let payload = ModelCreate {
+                        vector_format,
+                        name,
+                        description,
+                        feature_names,
+                        class_labels,
+                        preprocessing,
+                    };
                     let endpoint = format!("{}/api/model", base);
                     println!("Creating model '{}'...", name);
                     match client
@@ -1160,8 +1745,76 @@ fn main() {
                         Err(e) => { eprintln!("HTTP request error: {}", e); std::process::exit(1); }
                     }
                 }
+                Some(("import", sub_import)) => {
+                    let onnx_path = sub_import.get_one::<String>("onnx").map(String::as_str).expect("--onnx is required");
+                    let out_path = PathBuf::from(sub_import.get_one::<String>("out").map(String::as_str).unwrap_or("model_spec.json"));
+
+                    let mut result = match onnx_import::import_onnx(onnx_path) {
+                        Ok(r) => r,
+                        Err(e) => { eprintln!("Import failed: {}", e); std::process::exit(1); }
+                    };
+                    result.report.print();
+                    if !result.report.unsupported.is_empty() {
+                        eprintln!("Unsupported ops in {}; no spec written.", onnx_path);
+                        std::process::exit(1);
+                    }
+
+                    if let Some(tree) = result.tree.as_ref() {
+                        // tree_path is resolved relative to the spec by Zk-host
+                        let stem = out_path.file_stem().and_then(|s| s.to_str()).unwrap_or("model_spec");
+                        let tree_name = format!("{}.tree.json", stem);
+                        let tree_path = out_path.with_file_name(&tree_name);
+                        let text = serde_json::to_string_pretty(tree).expect("serialize tree");
+                        if let Err(e) = fs::write(&tree_path, text) {
+                            eprintln!("Failed to write {}: {}", tree_path.display(), e);
+                            std::process::exit(1);
+                        }
+                        result.spec["tree_path"] = Value::String(tree_name);
+                        println!("Tree ensemble saved to {}", tree_path.display());
+                    }
+
+                    let text = serde_json::to_string_pretty(&result.spec).expect("serialize spec");
+                    if let Err(e) = fs::write(&out_path, text) {
+                        eprintln!("Failed to write {}: {}", out_path.display(), e);
+                        std::process::exit(1);
+                    }
+                    println!("Model spec saved to {}", out_path.display());
+                    std::process::exit(0);
+                }
+                #[cfg(feature = "onnxruntime")]
+                Some(("check", sub_check)) => {
+                    let opts = model_check::CheckOptions {
+                        onnx_path: sub_check.get_one::<String>("onnx").map(String::as_str).expect("--onnx is required"),
+                        spec_path: sub_check.get_one::<String>("spec").map(String::as_str).expect("--spec is required"),
+                        dataset_path: sub_check.get_one::<String>("dataset").map(String::as_str).expect("--dataset is required"),
+                        tolerance: *sub_check.get_one::<f64>("tolerance").unwrap_or(&1e-5),
+                    };
+                    match model_check::run_check(&opts) {
+                        Ok(true) => std::process::exit(0),
+                        Ok(false) => std::process::exit(1),
+                        Err(e) => { eprintln!("Check failed: {}", e); std::process::exit(1); }
+                    }
+                }
+                #[cfg(feature = "onnxruntime")]
+                Some(("eval", sub_eval)) => {
+                    let opts = model_eval::EvalOptions {
+                        onnx_path: sub_eval.get_one::<String>("onnx").map(String::as_str).expect("--onnx is required"),
+                        dataset_path: sub_eval.get_one::<String>("dataset").map(String::as_str).expect("--dataset is required"),
+                        batch_size: *sub_eval.get_one::<usize>("batch-size").unwrap_or(&256),
+                    };
+                    if let Err(e) = model_eval::run_eval(&opts) {
+                        eprintln!("Evaluation failed: {}", e);
+                        std::process::exit(1);
+                    }
+                    std::process::exit(0);
+                }
+                #[cfg(not(feature = "onnxruntime"))]
+                Some(("check" | "eval", _)) => {
+                    eprintln!("This verse was built without onnxruntime; rebuild it with `cargo build --features onnxruntime` to use verse model check and verse model eval");
+                    std::process::exit(1);
+                }
                 _ => {
-                    eprintln!("Use: verse model list | verse model new --vector-format <FORMAT> --name <NAME> [--description <TEXT>]");
+                    eprintln!("Use: verse model list | verse model new --vector-format <FORMAT> --name <NAME> [--description <TEXT>] [--feature-names <NAMES>] [--class-labels <NAMES>] [--preprocessing <FILE>] | verse model import --onnx <FILE> [--out <FILE>] | verse model check --onnx <FILE> --spec <FILE> --dataset <FILE> | verse model eval --onnx <FILE> --dataset <FILE>");
                     std::process::exit(2);
                 }
             }
@@ -0,0 +1,161 @@
+// `verse model check`: run a dataset through onnxruntime and through the native
+// tree evaluator (the guest's traversal, see tree_eval.rs) and compare them row
+// by row, so owners know the tree JSON they prove matches the ONNX model they
+// ship. Classifiers are compared on the predicted class and every probability,
+// regressors on every target, within a tolerance (onnxruntime works in f32).
+//
+// --spec is a tree JSON or a model spec with tree_path (relative to the spec).
+// The dataset is read as in dataset.rs; labels are ignored.
+
+use crate::dataset::load_dataset;
+use crate::onnx_runtime::{OnnxModel, OnnxRow};
+use crate::tree_eval::{Evaluation, TreeModel};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use verse_common::report;
+use verse_common::tree::{takes_true_branch, SplitMode};
+
+const BATCH_ROWS: usize = 1024;
+
+pub struct CheckOptions<'a> {
+    pub onnx_path: &'a str,
+    pub spec_path: &'a str,
+    pub dataset_path: &'a str,
+    pub tolerance: f64,
+}
+
+// Loads the tree JSON named by --spec; returns it with the model kind when the
+// spec says (model_type 9 is the regressor).
+fn load_tree(spec_path: &str) -> Result<(String, Option<bool>), String> {
+    let text = fs::read_to_string(spec_path).map_err(|e| format!("Failed to read {}: {}", spec_path, e))?;
+    let doc: Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON ({}): {}", spec_path, e))?;
+    let Some(model_type) = doc.get("model_type").and_then(|v| v.as_u64()) else {
+        return Ok((text, None));
+    };
+    if !matches!(model_type, 5 | 8 | 9) {
+        return Err(format!("Model type {} is not a tree model", model_type));
+    }
+    let tree_path = doc
+        .get("tree_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("{} has no tree_path", spec_path))?;
+    let tree_path = match Path::new(spec_path).parent() {
+        Some(dir) if Path::new(tree_path).is_relative() => dir.join(tree_path),
+        _ => Path::new(tree_path).to_path_buf(),
+    };
+    let tree_json = fs::read_to_string(&tree_path)
+        .map_err(|e| format!("Failed to read {}: {}", tree_path.display(), e))?;
+    Ok((tree_json, Some(model_type != 9)))
+}
+
+fn argmax(scores: &[f64]) -> Option<usize> {
+    report::argmax(scores).map(|(i, _)| i)
+}
+
+fn mode_name(mode: SplitMode) -> &'static str {
+    match mode {
+        SplitMode::BranchLeq => "<=",
+        SplitMode::BranchLt => "<",
+        SplitMode::BranchGte => ">=",
+        SplitMode::BranchGt => ">",
+        SplitMode::BranchEq => "==",
+        SplitMode::BranchNeq => "!=",
+        SplitMode::Leaf => "leaf",
+    }
+}
+
+fn print_divergence(row: usize, x: &[f64], onnx: &OnnxRow, native: &Evaluation, classifier: bool) {
+    println!("\nFirst divergence at row {}", row);
+    println!("  features: {:?}", x);
+    if classifier {
+        println!("  onnxruntime:  class {:?}, probabilities {:?}", onnx.label, onnx.scores);
+        println!("  tree JSON:    class {:?}, probabilities {:?}", argmax(&native.scores), native.scores);
+    } else {
+        println!("  onnxruntime:  {:?}", onnx.scores);
+        println!("  tree JSON:    {:?}", native.scores);
+    }
+    println!("  decision path (tree JSON, f64):");
+    for step in native.path.iter() {
+        // onnxruntime compares the f32 feature against the f32 threshold
+        let f32_took_true = if step.x.is_nan() {
+            step.took_true
+        } else {
+            takes_true_branch(step.mode, false, step.x as f32 as f64, step.threshold as f32 as f64)
+        };
+        println!(
+            "    tree {} node {}: x[{}] = {} {} {} -> {}{}",
+            step.tree_id,
+            step.node_id,
+            step.feature,
+            step.x,
+            mode_name(step.mode),
+            step.threshold,
+            if step.took_true { "true" } else { "false" },
+            if f32_took_true != step.took_true { "   <- f32 input takes the other branch" } else { "" }
+        );
+    }
+}
+
+// Returns true when every row agrees.
+pub fn run_check(opts: &CheckOptions) -> Result<bool, String> {
+    let mut onnx = OnnxModel::load(opts.onnx_path)?;
+    let (tree_json, spec_classifier) = load_tree(opts.spec_path)?;
+    let classifier = spec_classifier.unwrap_or(onnx.is_classifier());
+    if classifier != onnx.is_classifier() {
+        return Err(format!(
+            "{} is a tree {} but the ONNX model is a {}",
+            opts.spec_path,
+            if classifier { "classifier" } else { "regressor" },
+            if onnx.is_classifier() { "classifier" } else { "regressor" }
+        ));
+    }
+    let tree = TreeModel::from_json(&tree_json, classifier)?;
+    let width = match (onnx.input_width(), tree.max_feature()) {
+        (Some(d), Some(f)) if f >= d => {
+            return Err(format!("Tree splits on feature {} but the ONNX input has {}", f, d));
+        }
+        (Some(d), _) => d,
+        (None, Some(f)) => f + 1,
+        (None, None) => return Err("Cannot tell the input width from the model or the tree".into()),
+    };
+    let rows = load_dataset(opts.dataset_path, width)?.rows;
+
+    let mut label_matches = 0usize;
+    let mut divergent = 0usize;
+    let mut max_diff = 0.0f64;
+    let mut first: Option<(usize, OnnxRow, Evaluation)> = None;
+    for (batch_no, batch) in rows.chunks(BATCH_ROWS).enumerate() {
+        let onnx_rows = onnx.run(batch)?;
+        for (i, (x, o)) in batch.iter().zip(onnx_rows).enumerate() {
+            let row = batch_no * BATCH_ROWS + i;
+            let native = tree.evaluate(x).map_err(|e| format!("Row {}: {}", row, e))?;
+            let label_ok = !classifier || o.label == argmax(&native.scores);
+            let diff = o.scores.iter().zip(native.scores.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            max_diff = max_diff.max(diff);
+            let scores_ok = o.scores.is_empty() || (o.scores.len() == native.scores.len() && diff <= opts.tolerance);
+            if classifier && label_ok {
+                label_matches += 1;
+            }
+            if !(label_ok && scores_ok) {
+                divergent += 1;
+                if first.is_none() {
+                    first = Some((row, o, native));
+                }
+            }
+        }
+    }
+
+    println!("Rows checked:        {}", rows.len());
+    if classifier {
+        println!("Labels agree:        {}/{}", label_matches, rows.len());
+    }
+    println!("Max |difference|:    {:.3e} (tolerance {:.1e})", max_diff, opts.tolerance);
+    println!("Divergent rows:      {}", divergent);
+    if let Some((row, o, native)) = first {
+        print_divergence(row, &rows[row], &o, &native, classifier);
+        return Ok(false);
+    }
+    println!("\nonnxruntime and the tree JSON agree on every row.");
+    Ok(true)
+}
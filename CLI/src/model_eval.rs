@@ -0,0 +1,98 @@
+// `verse model eval`: run a whole dataset through an ONNX model with
+// onnxruntime and print the metric report `verse verify` prints for a proof,
+// as a non-ZK baseline. Input and output names come from the model; rows go
+// through in batches.
+
+use crate::dataset::load_dataset;
+use crate::onnx_runtime::OnnxModel;
+use std::time::Instant;
+use verse_common::report;
+
+pub struct EvalOptions<'a> {
+    pub onnx_path: &'a str,
+    pub dataset_path: &'a str,
+    pub batch_size: usize,
+}
+
+// Index of a label among the model's classes. Numeric labels also match
+// integer class keys ("2.0" -> "2"); without class keys an integer label is
+// taken as the index itself.
+fn class_index(label: &str, classes: &[String]) -> Option<usize> {
+    if let Some(i) = classes.iter().position(|c| c == label) {
+        return Some(i);
+    }
+    let v: f64 = label.parse().ok()?;
+    if v.fract() != 0.0 || v < 0.0 {
+        return None;
+    }
+    if classes.is_empty() {
+        return Some(v as usize);
+    }
+    let key = (v as i64).to_string();
+    classes.iter().position(|c| *c == key)
+}
+
+pub fn run_eval(opts: &EvalOptions) -> Result<(), String> {
+    let mut model = OnnxModel::load(opts.onnx_path)?;
+    println!("ONNX model {}", opts.onnx_path);
+    model.describe();
+    let width = model
+        .input_width()
+        .ok_or("The model's input width is not fixed; export it with a known feature count")?;
+    let dataset = load_dataset(opts.dataset_path, width)?;
+
+    let start = Instant::now();
+    let mut results = Vec::with_capacity(dataset.rows.len());
+    let batch_size = opts.batch_size.max(1);
+    for batch in dataset.rows.chunks(batch_size) {
+        results.extend(model.run(batch)?);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "\nEvaluated {} rows in {} batch(es) of up to {} ({:?})",
+        dataset.rows.len(),
+        dataset.rows.len().div_ceil(batch_size),
+        batch_size,
+        elapsed
+    );
+
+    let Some(labels) = dataset.labels.as_ref() else {
+        println!("\nDataset has no label column; predictions only.");
+        for (i, r) in results.iter().enumerate().take(5) {
+            println!("{}: label={:?}, scores={:?}", i, r.label, r.scores);
+        }
+        return Ok(());
+    };
+
+    if model.is_classifier() {
+        let mut predictions = Vec::with_capacity(results.len());
+        for (i, (r, label)) in results.into_iter().zip(labels.iter()).enumerate() {
+            let expected = class_index(label, model.classes())
+                .ok_or_else(|| format!("Row {}: label {} is not one of the model's classes {:?}", i, label, model.classes()))?;
+            // without a probability output, score the predicted label as 1
+            let scores = if r.scores.is_empty() {
+                let mut one_hot = vec![0.0; r.label.unwrap_or(0).max(expected) + 1];
+                if let Some(l) = r.label {
+                    one_hot[l] = 1.0;
+                }
+                one_hot
+            } else {
+                r.scores
+            };
+            predictions.push((scores, expected as u32));
+        }
+        report::print_classification_report(&predictions, model.classes());
+    } else {
+        let mut outputs = Vec::with_capacity(results.len());
+        for (i, (r, label)) in results.into_iter().zip(labels.iter()).enumerate() {
+            let target: f64 = label.parse().map_err(|_| format!("Row {}: target {} is not a number", i, label))?;
+            outputs.push((r.scores, target));
+        }
+        let pairs: Vec<(f64, f64)> = outputs
+            .iter()
+            .map(|(pred, t)| (pred.first().copied().unwrap_or(f64::NAN), *t))
+            .collect();
+        report::print_regression_report(&outputs, &report::regression_metrics(&pairs), false);
+    }
+    Ok(())
+}
@@ -0,0 +1,1044 @@
+// `verse model import --onnx`: turn an ONNX export into the model spec read by
+// Zk-host, without the Python scripts in model-owner/.
+//
+// Supported graphs:
+//   - TreeEnsembleClassifier / TreeEnsembleRegressor -> model 8 / 9 plus a tree
+//     ensemble document (same layout as model-owner/tree2json.py)
+//   - LinearRegressor -> model 2, LinearClassifier -> model 4 (binary) or 6
+//   - Gemm / MatMul + Add with Relu, Sigmoid, Tanh and an optional Softmax -> model 7;
+//     a binary classifier's sigmoid output and [1 - p, p] become a two-class
+//     softmax head
+//
+// Label post-processing that skl2onnx appends after the model's output
+// (ZipMap, ArgMax, Cast, ...) is ignored and listed in the report; any other op
+// the importer did not consume is reported as unsupported and nothing is
+// written.
+
+use prost::Message;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+// Subset of onnx.proto. Field tags follow the upstream schema; everything not
+// declared here is skipped when decoding.
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ModelProto {
+        #[prost(string, tag = "2")]
+        pub producer_name: String,
+        #[prost(message, optional, tag = "7")]
+        pub graph: Option<GraphProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GraphProto {
+        #[prost(message, repeated, tag = "1")]
+        pub node: Vec<NodeProto>,
+        #[prost(message, repeated, tag = "5")]
+        pub initializer: Vec<TensorProto>,
+        #[prost(message, repeated, tag = "11")]
+        pub input: Vec<ValueInfoProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct NodeProto {
+        #[prost(string, repeated, tag = "1")]
+        pub input: Vec<String>,
+        #[prost(string, repeated, tag = "2")]
+        pub output: Vec<String>,
+        #[prost(string, tag = "3")]
+        pub name: String,
+        #[prost(string, tag = "4")]
+        pub op_type: String,
+        #[prost(message, repeated, tag = "5")]
+        pub attribute: Vec<AttributeProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AttributeProto {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(float, tag = "2")]
+        pub f: f32,
+        #[prost(int64, tag = "3")]
+        pub i: i64,
+        #[prost(bytes = "vec", tag = "4")]
+        pub s: Vec<u8>,
+        #[prost(message, optional, tag = "5")]
+        pub t: Option<TensorProto>,
+        #[prost(float, repeated, tag = "7")]
+        pub floats: Vec<f32>,
+        #[prost(int64, repeated, tag = "8")]
+        pub ints: Vec<i64>,
+        #[prost(bytes = "vec", repeated, tag = "9")]
+        pub strings: Vec<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TensorProto {
+        #[prost(int64, repeated, tag = "1")]
+        pub dims: Vec<i64>,
+        #[prost(int32, tag = "2")]
+        pub data_type: i32,
+        #[prost(float, repeated, tag = "4")]
+        pub float_data: Vec<f32>,
+        #[prost(int64, repeated, tag = "7")]
+        pub int64_data: Vec<i64>,
+        #[prost(string, tag = "8")]
+        pub name: String,
+        #[prost(bytes = "vec", tag = "9")]
+        pub raw_data: Vec<u8>,
+        #[prost(double, repeated, tag = "10")]
+        pub double_data: Vec<f64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ValueInfoProto {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(message, optional, tag = "2")]
+        pub r#type: Option<TypeProto>,
+    }
+
+    // `value` is a oneof upstream; only the tensor arm is read.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TypeProto {
+        #[prost(message, optional, tag = "1")]
+        pub tensor_type: Option<TensorTypeProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TensorTypeProto {
+        #[prost(int32, tag = "1")]
+        pub elem_type: i32,
+        #[prost(message, optional, tag = "2")]
+        pub shape: Option<TensorShapeProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TensorShapeProto {
+        #[prost(message, repeated, tag = "1")]
+        pub dim: Vec<Dimension>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Dimension {
+        #[prost(int64, tag = "1")]
+        pub dim_value: i64,
+        #[prost(string, tag = "2")]
+        pub dim_param: String,
+    }
+}
+
+use proto::{AttributeProto, GraphProto, NodeProto, TensorProto};
+
+// TensorProto.DataType values we can read
+const DT_FLOAT: i32 = 1;
+const DT_INT64: i32 = 7;
+const DT_DOUBLE: i32 = 11;
+
+// Ops skl2onnx adds after the model proper to build labels and probability maps;
+// they are only skipped when they read the model's output. Arithmetic such as
+// Sub or Normalizer changes the outputs, so it is never skipped here and is
+// reported as unsupported instead.
+const POSTPROCESS_OPS: &[&str] = &[
+    "ZipMap", "ArgMax", "ArrayFeatureExtractor", "Cast", "Identity", "Reshape", "Flatten", "Concat",
+];
+
+pub struct ImportResult {
+    pub spec: Value,
+    // Ensemble document for tree models; written next to the spec
+    pub tree: Option<Value>,
+    pub report: ImportReport,
+}
+
+#[derive(Default)]
+pub struct ImportReport {
+    pub producer: String,
+    pub kind: String,
+    pub op_counts: BTreeMap<String, usize>,
+    pub ignored: Vec<String>,
+    pub unsupported: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn print(&self) {
+        println!("ONNX import report");
+        if !self.producer.is_empty() {
+            println!("  producer:    {}", self.producer);
+        }
+        println!("  model kind:  {}", if self.kind.is_empty() { "-" } else { &self.kind });
+        let ops: Vec<String> = self.op_counts.iter().map(|(op, n)| format!("{} x{}", op, n)).collect();
+        println!("  graph ops:   {}", ops.join(", "));
+        if !self.ignored.is_empty() {
+            println!("  ignored:     {}", self.ignored.join(", "));
+        }
+        for note in self.notes.iter() {
+            println!("  note:        {}", note);
+        }
+        if !self.unsupported.is_empty() {
+            println!("  UNSUPPORTED: {}", self.unsupported.join(", "));
+        }
+    }
+}
+
+pub fn import_onnx(path: &str) -> Result<ImportResult, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read ONNX file ({}): {}", path, e))?;
+    let model = proto::ModelProto::decode(bytes.as_slice())
+        .map_err(|e| format!("Failed to decode ONNX model ({}): {}", path, e))?;
+    let graph = model.graph.ok_or("ONNX model has no graph")?;
+    import_graph(&graph, &model.producer_name)
+}
+
+fn import_graph(graph: &GraphProto, producer: &str) -> Result<ImportResult, String> {
+    let mut report = ImportReport { producer: producer.to_string(), ..Default::default() };
+    for node in graph.node.iter() {
+        *report.op_counts.entry(node.op_type.clone()).or_insert(0) += 1;
+    }
+    let input_width = graph_input_width(graph);
+
+    // positions of the nodes the chosen importer consumed
+    let mut claimed = Vec::new();
+    let position = |ops: &[&str]| graph.node.iter().position(|n| ops.contains(&n.op_type.as_str()));
+    let result = if let Some(i) = position(&["TreeEnsembleClassifier", "TreeEnsembleRegressor"]) {
+        claimed.push(i);
+        import_tree_ensemble(&graph.node[i], input_width, &mut report)?
+    } else if let Some(i) = position(&["LinearRegressor", "LinearClassifier"]) {
+        claimed.push(i);
+        import_linear(&graph.node[i], input_width, &mut report)?
+    } else {
+        import_dense(graph, input_width, &mut claimed, &mut report)?
+    };
+    report_unclaimed(graph, &claimed, &mut report);
+
+    // String class labels name the classes in index order; integer labels are
+    // already the indices
+    let (mut spec, tree) = result;
+    if let Some(i) = position(&["TreeEnsembleClassifier", "LinearClassifier"]) {
+        let labels = attr_strings(&graph.node[i], "classlabels_strings");
+        if !labels.is_empty() && matches!(spec["model_type"].as_u64(), Some(6 | 8)) {
+            spec["class_labels"] = json!(labels);
+        }
+    }
+
+    Ok(ImportResult { spec, tree, report })
+}
+
+// Everything the importer did not consume must be label post-processing that
+// reads the model's output (the outputs of the last consumed node) or
+// something computed from it; anything else is unsupported.
+fn report_unclaimed(graph: &GraphProto, claimed: &[usize], report: &mut ImportReport) {
+    let mut downstream: BTreeSet<&str> = match claimed.iter().max() {
+        Some(&last) => graph.node[last].output.iter().map(String::as_str).collect(),
+        None => BTreeSet::new(),
+    };
+    for (i, node) in graph.node.iter().enumerate() {
+        if claimed.contains(&i) {
+            continue;
+        }
+        let after_output = node.input.iter().any(|t| downstream.contains(t.as_str()));
+        if after_output {
+            downstream.extend(node.output.iter().map(String::as_str));
+        }
+        let list = if after_output && POSTPROCESS_OPS.contains(&node.op_type.as_str()) {
+            &mut report.ignored
+        } else {
+            &mut report.unsupported
+        };
+        if !list.contains(&node.op_type) {
+            list.push(node.op_type.clone());
+        }
+    }
+}
+
+// Feature count from the first real graph input, shaped [N, D] or [D].
+fn graph_input_width(graph: &GraphProto) -> Option<usize> {
+    let init_names: Vec<&str> = graph.initializer.iter().map(|t| t.name.as_str()).collect();
+    let input = graph.input.iter().find(|i| !init_names.contains(&i.name.as_str()))?;
+    let shape = input.r#type.as_ref()?.tensor_type.as_ref()?.shape.as_ref()?;
+    let last = shape.dim.last()?;
+    if last.dim_value > 0 { Some(last.dim_value as usize) } else { None }
+}
+
+fn attr<'a>(node: &'a NodeProto, name: &str) -> Option<&'a AttributeProto> {
+    node.attribute.iter().find(|a| a.name == name)
+}
+
+fn attr_ints(node: &NodeProto, name: &str) -> Vec<i64> {
+    attr(node, name).map(|a| a.ints.clone()).unwrap_or_default()
+}
+
+fn attr_int(node: &NodeProto, name: &str, default: i64) -> i64 {
+    attr(node, name).map(|a| a.i).unwrap_or(default)
+}
+
+fn attr_float(node: &NodeProto, name: &str, default: f32) -> f32 {
+    attr(node, name).map(|a| a.f).unwrap_or(default)
+}
+
+fn attr_string(node: &NodeProto, name: &str, default: &str) -> String {
+    attr(node, name)
+        .map(|a| String::from_utf8_lossy(&a.s).into_owned())
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn attr_strings(node: &NodeProto, name: &str) -> Vec<String> {
+    attr(node, name)
+        .map(|a| a.strings.iter().map(|s| String::from_utf8_lossy(s).into_owned()).collect())
+        .unwrap_or_default()
+}
+
+// Float attribute, falling back to the `<name>_as_tensor` form newer opsets use.
+fn attr_floats(node: &NodeProto, name: &str) -> Result<Vec<f64>, String> {
+    if let Some(a) = attr(node, name) {
+        return Ok(a.floats.iter().map(|&v| v as f64).collect());
+    }
+    match attr(node, &format!("{}_as_tensor", name)).and_then(|a| a.t.as_ref()) {
+        Some(t) => tensor_values(t),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn tensor_values(t: &TensorProto) -> Result<Vec<f64>, String> {
+    let element_size = match t.data_type {
+        DT_FLOAT => 4,
+        DT_DOUBLE | DT_INT64 => 8,
+        other => return Err(format!("Tensor '{}' has unsupported data type {}", t.name, other)),
+    };
+    // as_chunks would silently drop a partial trailing element
+    if !t.raw_data.len().is_multiple_of(element_size) {
+        return Err(format!(
+            "Tensor '{}' has {} bytes of raw_data, not a multiple of its {}-byte elements",
+            t.name,
+            t.raw_data.len(),
+            element_size
+        ));
+    }
+    Ok(match t.data_type {
+        DT_FLOAT if !t.float_data.is_empty() => t.float_data.iter().map(|&v| v as f64).collect(),
+        DT_FLOAT => t.raw_data.as_chunks::<4>().0.iter().map(|c| f32::from_le_bytes(*c) as f64).collect(),
+        DT_DOUBLE if !t.double_data.is_empty() => t.double_data.clone(),
+        DT_DOUBLE => t.raw_data.as_chunks::<8>().0.iter().map(|c| f64::from_le_bytes(*c)).collect(),
+        DT_INT64 if !t.int64_data.is_empty() => t.int64_data.iter().map(|&v| v as f64).collect(),
+        _ => t.raw_data.as_chunks::<8>().0.iter().map(|c| i64::from_le_bytes(*c) as f64).collect(),
+    })
+}
+
+fn resolve_width(input_width: Option<usize>, implied: usize, report: &mut ImportReport) -> Result<usize, String> {
+    match input_width {
+        Some(d) if d != implied => Err(format!(
+            "Graph input has {} features but the model parameters imply {}",
+            d, implied
+        )),
+        Some(d) => Ok(d),
+        None => {
+            report.notes.push(format!("input width not declared in the graph; using {} from the parameters", implied));
+            Ok(implied)
+        }
+    }
+}
+
+fn import_tree_ensemble(
+    node: &NodeProto,
+    input_width: Option<usize>,
+    report: &mut ImportReport,
+) -> Result<(Value, Option<Value>), String> {
+    let is_classifier = node.op_type == "TreeEnsembleClassifier";
+    let node_ids = attr_ints(node, "nodes_nodeids");
+    let tree_ids = attr_ints(node, "nodes_treeids");
+    let features = attr_ints(node, "nodes_featureids");
+    let thresholds = attr_floats(node, "nodes_values")?;
+    let modes = attr_strings(node, "nodes_modes");
+    let true_ids = attr_ints(node, "nodes_truenodeids");
+    let false_ids = attr_ints(node, "nodes_falsenodeids");
+    let mut missing = attr_ints(node, "nodes_missing_value_tracks_true");
+    if missing.is_empty() {
+        missing = vec![0; node_ids.len()];
+    }
+    let n = node_ids.len();
+    for (name, len) in [
+        ("nodes_treeids", tree_ids.len()),
+        ("nodes_featureids", features.len()),
+        ("nodes_values", thresholds.len()),
+        ("nodes_modes", modes.len()),
+        ("nodes_truenodeids", true_ids.len()),
+        ("nodes_falsenodeids", false_ids.len()),
+        ("nodes_missing_value_tracks_true", missing.len()),
+    ] {
+        if len != n {
+            return Err(format!("{}: {} has {} entries, expected {}", node.op_type, name, len, n));
+        }
+    }
+
+    let prefix = if is_classifier { "class" } else { "target" };
+    let leaf_tree_ids = attr_ints(node, &format!("{}_treeids", prefix));
+    let leaf_node_ids = attr_ints(node, &format!("{}_nodeids", prefix));
+    let mut leaf_out_ids = attr_ints(node, &format!("{}_ids", prefix));
+    let leaf_weights = attr_floats(node, &format!("{}_weights", prefix))?;
+
+    let (mut n_outputs, aggregate) = if is_classifier {
+        let int_labels = attr_ints(node, "classlabels_int64s");
+        let k = if int_labels.is_empty() { attr_strings(node, "classlabels_strings").len() } else { int_labels.len() };
+        (k, "SUM".to_string())
+    } else {
+        (attr_int(node, "n_targets", 1) as usize, attr_string(node, "aggregate_function", "SUM"))
+    };
+    // A binary classifier may carry weights for a single class, like
+    // onnxruntime's binary case: class 1 for random forests, class 0 for
+    // GradientBoosting, XGBoost and LightGBM exports
+    let out_ids: BTreeSet<i64> = leaf_out_ids.iter().copied().collect();
+    if is_classifier && n_outputs == 2 && out_ids.len() == 1 {
+        n_outputs = 1;
+        leaf_out_ids = vec![0; leaf_out_ids.len()];
+        report.notes.push("binary classifier with one score per leaf; guest expands it to two like onnxruntime".into());
+    }
+    if n_outputs == 0 {
+        return Err(format!("{} declares no classes or targets", node.op_type));
+    }
+
+    let mut leaf_values: BTreeMap<(i64, i64), Vec<f64>> = BTreeMap::new();
+    for (((t, nid), k), w) in leaf_tree_ids.iter().zip(leaf_node_ids.iter()).zip(leaf_out_ids.iter()).zip(leaf_weights.iter()) {
+        let row = leaf_values.entry((*t, *nid)).or_insert_with(|| vec![0.0; n_outputs]);
+        let k = *k as usize;
+        if k >= n_outputs {
+            return Err(format!("Leaf weight refers to output {} of {}", k, n_outputs));
+        }
+        row[k] += w;
+    }
+
+    let mut nodes = Vec::with_capacity(n);
+    let mut max_feature = 0usize;
+    for i in 0..n {
+        let (tid, nid) = (tree_ids[i], node_ids[i]);
+        if modes[i] == "LEAF" {
+            let value = leaf_values.get(&(tid, nid)).cloned().unwrap_or_else(|| vec![0.0; n_outputs]);
+            nodes.push(json!({
+                "tree_id": tid, "id": nid, "feature": null, "threshold": null, "mode": "LEAF",
+                "missing_tracks_true": false, "left": null, "right": null, "value": [value],
+            }));
+        } else {
+            max_feature = max_feature.max(features[i] as usize);
+            nodes.push(json!({
+                "tree_id": tid, "id": nid, "feature": features[i], "threshold": thresholds[i],
+                "mode": modes[i], "missing_tracks_true": missing[i] != 0,
+                "left": true_ids[i], "right": false_ids[i], "value": [],
+            }));
+        }
+    }
+
+    let post_transform = attr_string(node, "post_transform", "NONE");
+    if !matches!(post_transform.as_str(), "NONE" | "SOFTMAX" | "LOGISTIC") {
+        report.unsupported.push(format!("post_transform {}", post_transform));
+    }
+    let num_trees = tree_ids.iter().collect::<BTreeSet<_>>().len();
+    let num_features = match input_width {
+        Some(d) if d <= max_feature => {
+            return Err(format!("Tree splits on feature {} but the graph input has {}", max_feature, d));
+        }
+        Some(d) => d,
+        None => {
+            report.notes.push(format!("input width not declared in the graph; using {}", max_feature + 1));
+            max_feature + 1
+        }
+    };
+
+    let model_type = if is_classifier { 8 } else { 9 };
+    report.kind = format!(
+        "{} (model {}): {} tree(s), {} nodes, {} output(s), aggregate {}, post_transform {}",
+        node.op_type, model_type, num_trees, n, n_outputs, aggregate, post_transform
+    );
+    let tree = json!({
+        "aggregate": aggregate,
+        "post_transform": post_transform,
+        "base_values": attr_floats(node, "base_values")?,
+        "nodes": nodes,
+    });
+    // tree_path is filled in by the caller once it knows where the document goes
+    let spec = json!({ "model_type": model_type, "num_features": num_features });
+    Ok((spec, Some(tree)))
+}
+
+fn import_linear(
+    node: &NodeProto,
+    input_width: Option<usize>,
+    report: &mut ImportReport,
+) -> Result<(Value, Option<Value>), String> {
+    let coefficients: Vec<f32> = attr_floats(node, "coefficients")?.iter().map(|&v| v as f32).collect();
+    let intercepts: Vec<f32> = attr_floats(node, "intercepts")?.iter().map(|&v| v as f32).collect();
+    let post_transform = attr_string(node, "post_transform", "NONE");
+
+    if node.op_type == "LinearRegressor" {
+        let targets = attr_int(node, "targets", 1) as usize;
+        if targets != 1 {
+            report.unsupported.push(format!("LinearRegressor with {} targets", targets));
+        }
+        if post_transform != "NONE" {
+            report.unsupported.push(format!("LinearRegressor post_transform {}", post_transform));
+        }
+        let d = resolve_width(input_width, coefficients.len(), report)?;
+        report.kind = format!("LinearRegressor (model 2): {} features", d);
+        let bias = intercepts.first().copied().unwrap_or(0.0);
+        return Ok((json!({ "model_type": 2, "num_features": d, "weights": coefficients, "bias": bias }), None));
+    }
+
+    // LinearClassifier: coefficients is K x D row-major, one row per
+    // intercept. Intercepts are optional; without them K follows the class
+    // labels, except that a binary model may store only the positive row.
+    let (k, intercepts) = if intercepts.is_empty() {
+        let int_labels = attr_ints(node, "classlabels_int64s");
+        let labels = if int_labels.is_empty() { attr_strings(node, "classlabels_strings").len() } else { int_labels.len() };
+        let k = match input_width {
+            Some(d) if d > 0 && coefficients.len() == d => 1,
+            _ => labels.max(1),
+        };
+        (k, vec![0.0; k])
+    } else {
+        (intercepts.len(), intercepts)
+    };
+    if !coefficients.len().is_multiple_of(k) {
+        return Err(format!("LinearClassifier has {} coefficients for {} classes", coefficients.len(), k));
+    }
+    let d = resolve_width(input_width, coefficients.len() / k, report)?;
+    let binary_pair = k == 2
+        && coefficients[..d].iter().zip(coefficients[d..].iter()).all(|(a, b)| (a + b).abs() < 1e-6)
+        && (intercepts[0] + intercepts[1]).abs() < 1e-6;
+
+    if k == 1 || binary_pair {
+        // One score z for the positive class (skl2onnx sometimes stores [-z, z])
+        let row = if k == 1 { 0 } else { 1 };
+        let weights = coefficients[row * d..(row + 1) * d].to_vec();
+        if post_transform != "LOGISTIC" {
+            report.notes.push(format!("binary post_transform {} proven as logistic probability", post_transform));
+        }
+        report.kind = format!("LinearClassifier, binary (model 4): {} features", d);
+        return Ok((json!({ "model_type": 4, "num_features": d, "weights": weights, "bias": intercepts[row] }), None));
+    }
+
+    match post_transform.as_str() {
+        "SOFTMAX" | "NONE" => {}
+        "LOGISTIC" => report.notes.push(
+            "one-vs-rest LOGISTIC scores proven as softmax; labels match, probabilities differ".into(),
+        ),
+        other => report.unsupported.push(format!("LinearClassifier post_transform {}", other)),
+    }
+    report.kind = format!("LinearClassifier (model 6): {} classes x {} features", k, d);
+    Ok((
+        json!({ "model_type": 6, "num_features": d, "num_classes": k, "weights": coefficients, "biases": intercepts }),
+        None,
+    ))
+}
+
+// Dense stack: walks the nodes in order, folding Gemm / MatMul + Add into
+// layers and attaching the activation that follows each one. Nodes it folds,
+// or reports itself, go into `claimed`.
+fn import_dense(
+    graph: &GraphProto,
+    input_width: Option<usize>,
+    claimed: &mut Vec<usize>,
+    report: &mut ImportReport,
+) -> Result<(Value, Option<Value>), String> {
+    let inits: BTreeMap<&str, &TensorProto> = graph.initializer.iter().map(|t| (t.name.as_str(), t)).collect();
+    let matrix = |name: &str| -> Result<(Vec<f64>, usize, usize), String> {
+        let t = inits.get(name).ok_or_else(|| format!("Weight '{}' is not an initializer", name))?;
+        if t.dims.len() != 2 {
+            return Err(format!("Weight '{}' is not a matrix", name));
+        }
+        Ok((tensor_values(t)?, t.dims[0] as usize, t.dims[1] as usize))
+    };
+    let weight = |node: &NodeProto| -> Result<(Vec<f64>, usize, usize), String> {
+        let name = node
+            .input
+            .get(1)
+            .ok_or_else(|| format!("{} '{}' has no weight input", node.op_type, node.name))?;
+        matrix(name)
+    };
+
+    // (weights out x in, biases, activation)
+    let mut layers: Vec<(Vec<f64>, Vec<f64>, &str)> = Vec::new();
+    let mut head = "linear";
+    // A binary MLPClassifier ends in one sigmoid unit p, then Sub (1 - p) and
+    // Concat [1 - p, p]: the names p goes by (Identity copies too), and 1 - p
+    let mut sigmoid_out: Vec<&str> = Vec::new();
+    let mut complement: Option<&str> = None;
+    let mut binary_head = false;
+    let is_one = |name: &str| -> Result<bool, String> {
+        Ok(match inits.get(name) {
+            Some(t) => tensor_values(t)?.iter().all(|&v| v == 1.0),
+            None => false,
+        })
+    };
+    for (i, node) in graph.node.iter().enumerate() {
+        let op = node.op_type.as_str();
+        if matches!(op, "Gemm" | "MatMul" | "Add" | "Relu" | "Sigmoid" | "Tanh" | "Softmax") {
+            claimed.push(i);
+        }
+        match op {
+            "Gemm" => {
+                if attr_int(node, "transA", 0) != 0 {
+                    report.unsupported.push("Gemm with transA".into());
+                    continue;
+                }
+                let (w, rows, cols) = weight(node)?;
+                let alpha = attr_float(node, "alpha", 1.0) as f64;
+                let beta = attr_float(node, "beta", 1.0) as f64;
+                // our layout is out x in; Gemm's B is in x out unless transB
+                let (w, out_dim) = if attr_int(node, "transB", 0) != 0 {
+                    (w, rows)
+                } else {
+                    (transpose(&w, rows, cols), cols)
+                };
+                sigmoid_out.clear();
+                let w: Vec<f64> = w.iter().map(|v| v * alpha).collect();
+                let b = match node.input.get(2).and_then(|n| inits.get(n.as_str())) {
+                    Some(t) => tensor_values(t)?.iter().map(|v| v * beta).collect(),
+                    None => vec![0.0; out_dim],
+                };
+                layers.push((w, b, "identity"));
+            }
+            "MatMul" => {
+                let (w, rows, cols) = weight(node)?;
+                sigmoid_out.clear();
+                layers.push((transpose(&w, rows, cols), vec![0.0; cols], "identity"));
+            }
+            "Add" => {
+                let b = node.input.get(1).and_then(|n| inits.get(n.as_str()));
+                match (layers.last_mut(), b) {
+                    (Some(layer), Some(t)) => {
+                        let b = tensor_values(t)?;
+                        if b.len() != layer.1.len() {
+                            return Err(format!("Add '{}' does not match the layer width", node.name));
+                        }
+                        for (acc, v) in layer.1.iter_mut().zip(b.iter()) {
+                            *acc += v;
+                        }
+                    }
+                    _ => report.unsupported.push(format!("Add '{}' that is not a bias", node.name)),
+                }
+            }
+            "Relu" | "Sigmoid" | "Tanh" => match layers.last_mut() {
+                Some(layer) => {
+                    layer.2 = match op {
+                        "Relu" => "relu",
+                        "Sigmoid" => "sigmoid",
+                        _ => "tanh",
+                    };
+                    if op == "Sigmoid" && layer.1.len() == 1 {
+                        sigmoid_out = node.output.iter().map(String::as_str).collect();
+                    }
+                }
+                None => report.unsupported.push(format!("{} before any dense layer", node.op_type)),
+            },
+            "Softmax" => head = "softmax",
+            "Identity" if node.input.iter().any(|t| sigmoid_out.contains(&t.as_str())) => {
+                claimed.push(i);
+                sigmoid_out.extend(node.output.iter().map(String::as_str));
+            }
+            "Sub" if node.input.len() == 2 && sigmoid_out.contains(&node.input[1].as_str()) && is_one(&node.input[0])? => {
+                claimed.push(i);
+                complement = node.output.first().map(String::as_str);
+            }
+            "Concat"
+                if node.input.len() == 2
+                    && complement == Some(node.input[0].as_str())
+                    && sigmoid_out.contains(&node.input[1].as_str()) =>
+            {
+                claimed.push(i);
+                binary_head = true;
+            }
+            _ => {}
+        }
+    }
+    if layers.is_empty() {
+        report.kind = "unrecognised graph".into();
+        return Err("No tree ensemble, linear model or dense layers found in the graph".into());
+    }
+
+    if binary_head {
+        // softmax([-z/2, z/2]) = [1 - sigmoid(z), sigmoid(z)], so the pair is
+        // proven as a two-class softmax head and scored as a classifier
+        let layer = layers.last_mut().expect("the sigmoid follows a layer");
+        layer.0 = layer.0.iter().map(|v| -v / 2.0).chain(layer.0.iter().map(|v| v / 2.0)).collect();
+        layer.1 = vec![-layer.1[0] / 2.0, layer.1[0] / 2.0];
+        layer.2 = "identity";
+        head = "softmax";
+        report.notes.push("binary sigmoid output [1 - p, p] imported as a two-class softmax head".into());
+    }
+
+    let first_in = layers[0].0.len() / layers[0].1.len().max(1);
+    let d = resolve_width(input_width, first_in, report)?;
+    let shapes: Vec<String> = layers.iter().map(|(_, b, act)| format!("{} ({})", b.len(), act)).collect();
+    report.kind = format!("dense network (model 7): {} -> {}, head {}", d, shapes.join(" -> "), head);
+    let layers: Vec<Value> = layers
+        .into_iter()
+        .map(|(w, b, act)| json!({ "weights": w, "biases": b, "activation": act }))
+        .collect();
+    Ok((json!({ "model_type": 7, "num_features": d, "head": head, "layers": layers }), None))
+}
+
+fn transpose(w: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    let mut out = vec![0.0; w.len()];
+    for r in 0..rows {
+        for c in 0..cols {
+            out[c * rows + r] = w[r * cols + c];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(name: &str, v: &[i64]) -> AttributeProto {
+        AttributeProto { name: name.into(), ints: v.to_vec(), ..Default::default() }
+    }
+
+    fn floats(name: &str, v: &[f32]) -> AttributeProto {
+        AttributeProto { name: name.into(), floats: v.to_vec(), ..Default::default() }
+    }
+
+    fn strings(name: &str, v: &[&str]) -> AttributeProto {
+        AttributeProto { name: name.into(), strings: v.iter().map(|s| s.as_bytes().to_vec()).collect(), ..Default::default() }
+    }
+
+    fn int(name: &str, v: i64) -> AttributeProto {
+        AttributeProto { name: name.into(), i: v, ..Default::default() }
+    }
+
+    fn float(name: &str, v: f32) -> AttributeProto {
+        AttributeProto { name: name.into(), f: v, ..Default::default() }
+    }
+
+    fn string(name: &str, v: &str) -> AttributeProto {
+        AttributeProto { name: name.into(), s: v.as_bytes().to_vec(), ..Default::default() }
+    }
+
+    fn node(op_type: &str, input: &[&str], output: &[&str], attribute: Vec<AttributeProto>) -> NodeProto {
+        NodeProto {
+            op_type: op_type.into(),
+            name: output[0].into(),
+            input: input.iter().map(|s| s.to_string()).collect(),
+            output: output.iter().map(|s| s.to_string()).collect(),
+            attribute,
+        }
+    }
+
+    fn tensor(name: &str, dims: &[i64], values: &[f32]) -> TensorProto {
+        let float_data = values.to_vec();
+        TensorProto { name: name.into(), dims: dims.to_vec(), data_type: DT_FLOAT, float_data, ..Default::default() }
+    }
+
+    fn graph(node: Vec<NodeProto>, initializer: Vec<TensorProto>) -> GraphProto {
+        GraphProto { node, initializer, ..Default::default() }
+    }
+
+    fn linear(op_type: &str, attribute: Vec<AttributeProto>, input_width: Option<usize>) -> (Value, ImportReport) {
+        let mut report = ImportReport::default();
+        let node = NodeProto { op_type: op_type.into(), attribute, ..Default::default() };
+        let (spec, tree) = import_linear(&node, input_width, &mut report).unwrap();
+        assert!(tree.is_none());
+        (spec, report)
+    }
+
+    fn dense(nodes: Vec<NodeProto>, initializer: Vec<TensorProto>) -> (Value, Vec<usize>, ImportReport) {
+        let mut report = ImportReport::default();
+        let mut claimed = Vec::new();
+        let (spec, _) = import_dense(&graph(nodes, initializer), None, &mut claimed, &mut report).unwrap();
+        (spec, claimed, report)
+    }
+
+    // Two stumps on features 0 and 1, every leaf weight on `class_id`, the way
+    // binary boosters are exported.
+    fn binary_booster(class_id: i64) -> NodeProto {
+        NodeProto {
+            op_type: "TreeEnsembleClassifier".into(),
+            attribute: vec![
+                ints("nodes_treeids", &[0, 0, 0, 1, 1, 1]),
+                ints("nodes_nodeids", &[0, 1, 2, 0, 1, 2]),
+                ints("nodes_featureids", &[0, 0, 0, 1, 0, 0]),
+                floats("nodes_values", &[0.5, 0.0, 0.0, 1.5, 0.0, 0.0]),
+                strings("nodes_modes", &["BRANCH_LEQ", "LEAF", "LEAF", "BRANCH_LEQ", "LEAF", "LEAF"]),
+                ints("nodes_truenodeids", &[1, 0, 0, 1, 0, 0]),
+                ints("nodes_falsenodeids", &[2, 0, 0, 2, 0, 0]),
+                ints("class_treeids", &[0, 0, 1, 1]),
+                ints("class_nodeids", &[1, 2, 1, 2]),
+                ints("class_ids", &[class_id; 4]),
+                floats("class_weights", &[-0.5, 0.75, 0.25, -1.0]),
+                floats("base_values", &[0.1]),
+                ints("classlabels_int64s", &[0, 1]),
+                string("post_transform", "LOGISTIC"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decodes_raw_tensor_data() {
+        let raw = |data_type: i32, raw_data: Vec<u8>| TensorProto { data_type, raw_data, ..Default::default() };
+        let floats: Vec<u8> = [1.5f32, -2.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(tensor_values(&raw(DT_FLOAT, floats)).unwrap(), [1.5, -2.0]);
+        let doubles: Vec<u8> = [0.25f64, 3.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(tensor_values(&raw(DT_DOUBLE, doubles)).unwrap(), [0.25, 3.0]);
+        let ints: Vec<u8> = [7i64, -3].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(tensor_values(&raw(DT_INT64, ints)).unwrap(), [7.0, -3.0]);
+        let err = tensor_values(&TensorProto { name: "w".into(), data_type: 6, ..Default::default() }).unwrap_err();
+        assert!(err.contains("unsupported data type 6"));
+    }
+
+    #[test]
+    fn rejects_raw_data_with_a_partial_element() {
+        let raw = |data_type: i32, len: usize| TensorProto {
+            name: "w".into(),
+            data_type,
+            raw_data: vec![0; len],
+            ..Default::default()
+        };
+        for (data_type, len, size) in [(DT_FLOAT, 6, 4), (DT_DOUBLE, 12, 8), (DT_INT64, 9, 8)] {
+            let err = tensor_values(&raw(data_type, len)).unwrap_err();
+            assert!(err.contains(&format!("{} bytes of raw_data, not a multiple of its {}-byte", len, size)), "{}", err);
+        }
+        assert_eq!(tensor_values(&raw(DT_FLOAT, 8)).unwrap(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn imports_linear_regressors() {
+        let attribute = vec![floats("coefficients", &[0.5, -2.0]), floats("intercepts", &[3.0])];
+        let (spec, _) = linear("LinearRegressor", attribute, None);
+        assert_eq!(spec, json!({ "model_type": 2, "num_features": 2, "weights": [0.5, -2.0], "bias": 3.0 }));
+        let (spec, _) = linear("LinearRegressor", vec![floats("coefficients", &[0.5, -2.0])], Some(2));
+        assert_eq!(spec["bias"], 0.0);
+    }
+
+    #[test]
+    fn collapses_binary_linear_classifier_pairs() {
+        // skl2onnx stores a binary logistic regression as rows [-w, w], [-b, b]
+        let (spec, report) = linear(
+            "LinearClassifier",
+            vec![
+                floats("coefficients", &[-0.5, 1.5, 0.5, -1.5]),
+                floats("intercepts", &[0.25, -0.25]),
+                ints("classlabels_int64s", &[0, 1]),
+                string("post_transform", "LOGISTIC"),
+            ],
+            Some(2),
+        );
+        assert_eq!(spec, json!({ "model_type": 4, "num_features": 2, "weights": [0.5, -1.5], "bias": -0.25 }));
+        assert!(report.notes.is_empty());
+
+        // two rows that are not negations of each other stay a two-class model
+        let (spec, _) = linear(
+            "LinearClassifier",
+            vec![floats("coefficients", &[-0.5, 1.5, 0.5, -1.0]), floats("intercepts", &[0.25, -0.25])],
+            Some(2),
+        );
+        assert_eq!(spec["model_type"], 6);
+        assert_eq!(spec["num_classes"], 2);
+    }
+
+    #[test]
+    fn imports_linear_classifiers_without_intercepts() {
+        // one row for a declared width: the positive class of a binary model
+        let (spec, report) = linear("LinearClassifier", vec![floats("coefficients", &[1.0, 2.0, 3.0])], Some(3));
+        assert_eq!(spec, json!({ "model_type": 4, "num_features": 3, "weights": [1.0, 2.0, 3.0], "bias": 0.0 }));
+        assert!(report.notes[0].contains("binary post_transform NONE proven as logistic"));
+
+        // otherwise one row per class label, with zero biases
+        let (spec, _) = linear(
+            "LinearClassifier",
+            vec![floats("coefficients", &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), strings("classlabels_strings", &["a", "b", "c"])],
+            None,
+        );
+        assert_eq!(spec["model_type"], 6);
+        assert_eq!(spec["num_features"], 2);
+        assert_eq!(spec["biases"], json!([0.0, 0.0, 0.0]));
+
+        let err = import_linear(
+            &NodeProto {
+                op_type: "LinearClassifier".into(),
+                attribute: vec![floats("coefficients", &[1.0, 2.0, 3.0, 4.0, 5.0]), ints("classlabels_int64s", &[0, 1])],
+                ..Default::default()
+            },
+            None,
+            &mut ImportReport::default(),
+        )
+        .unwrap_err();
+        assert!(err.contains("5 coefficients for 2 classes"), "{}", err);
+    }
+
+    #[test]
+    fn folds_gemm_with_trans_b_alpha_and_beta() {
+        // y = 2 * x W^T + 0.5 * c, W stored out x in because transB = 1
+        let (spec, claimed, report) = dense(
+            vec![
+                node("Gemm", &["x", "W", "c"], &["h"], vec![int("transB", 1), float("alpha", 2.0), float("beta", 0.5)]),
+                node("Relu", &["h"], &["r"], vec![]),
+            ],
+            vec![tensor("W", &[2, 3], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), tensor("c", &[2], &[4.0, -2.0])],
+        );
+        assert_eq!(spec["num_features"], 3);
+        assert_eq!(spec["head"], "linear");
+        assert_eq!(
+            spec["layers"],
+            json!([{ "weights": [2.0, 4.0, 6.0, 8.0, 10.0, 12.0], "biases": [2.0, -1.0], "activation": "relu" }])
+        );
+        assert_eq!(claimed, [0, 1]);
+        assert!(report.unsupported.is_empty());
+
+        // without transB, B is in x out and is transposed into our layout
+        let (spec, _, _) = dense(
+            vec![node("Gemm", &["x", "W"], &["h"], vec![])],
+            vec![tensor("W", &[3, 2], &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0])],
+        );
+        assert_eq!(spec["layers"][0]["weights"], json!([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        assert_eq!(spec["layers"][0]["biases"], json!([0.0, 0.0]));
+    }
+
+    #[test]
+    fn folds_matmul_and_add_into_layers() {
+        let (spec, claimed, report) = dense(
+            vec![
+                node("MatMul", &["x", "W1"], &["m1"], vec![]),
+                node("Add", &["m1", "b1"], &["a1"], vec![]),
+                node("Tanh", &["a1"], &["t1"], vec![]),
+                node("MatMul", &["t1", "W2"], &["m2"], vec![]),
+                node("Add", &["m2", "b2"], &["a2"], vec![]),
+                node("Softmax", &["a2"], &["p"], vec![]),
+            ],
+            vec![
+                tensor("W1", &[2, 2], &[1.0, 2.0, 3.0, 4.0]),
+                tensor("b1", &[2], &[0.5, -0.5]),
+                tensor("W2", &[2, 3], &[1.0, 0.0, -1.0, 0.0, 1.0, 1.0]),
+                tensor("b2", &[3], &[0.0, 1.0, 2.0]),
+            ],
+        );
+        assert_eq!(spec["num_features"], 2);
+        assert_eq!(spec["head"], "softmax");
+        assert_eq!(
+            spec["layers"],
+            json!([
+                { "weights": [1.0, 3.0, 2.0, 4.0], "biases": [0.5, -0.5], "activation": "tanh" },
+                { "weights": [1.0, 0.0, 0.0, 1.0, -1.0, 1.0], "biases": [0.0, 1.0, 2.0], "activation": "identity" }
+            ])
+        );
+        assert_eq!(claimed, [0, 1, 2, 3, 4, 5]);
+        assert!(report.unsupported.is_empty());
+
+        let mismatched = graph(
+            vec![node("MatMul", &["x", "W1"], &["m1"], vec![]), node("Add", &["m1", "b2"], &["a1"], vec![])],
+            vec![tensor("W1", &[2, 2], &[1.0, 2.0, 3.0, 4.0]), tensor("b2", &[3], &[0.0, 1.0, 2.0])],
+        );
+        let err = import_dense(&mismatched, None, &mut Vec::new(), &mut ImportReport::default()).unwrap_err();
+        assert!(err.contains("Add 'a1' does not match the layer width"), "{}", err);
+    }
+
+    #[test]
+    fn imports_binary_sigmoid_outputs_as_a_softmax_head() {
+        // skl2onnx's binary MLPClassifier: one sigmoid unit, then [1 - p, p]
+        let nodes = |one: f32| {
+            graph(
+                vec![
+                    node("Gemm", &["x", "W1", "b1"], &["h"], vec![]),
+                    node("Relu", &["h"], &["r"], vec![]),
+                    node("Gemm", &["r", "W2", "b2"], &["z"], vec![]),
+                    node("Sigmoid", &["z"], &["p"], vec![]),
+                    node("Identity", &["p"], &["p2"], vec![]),
+                    node("Sub", &["one", "p2"], &["q"], vec![]),
+                    node("Concat", &["q", "p2"], &["probabilities"], vec![int("axis", 1)]),
+                    node("ArgMax", &["probabilities"], &["label"], vec![]),
+                ],
+                vec![
+                    tensor("W1", &[2, 2], &[1.0, 0.0, 0.0, 1.0]),
+                    tensor("b1", &[2], &[0.0, 0.0]),
+                    tensor("W2", &[2, 1], &[1.5, -0.5]),
+                    tensor("b2", &[1], &[0.25]),
+                    tensor("one", &[1], &[one]),
+                ],
+            )
+        };
+        let result = import_graph(&nodes(1.0), "skl2onnx").unwrap();
+        let spec = &result.spec;
+        assert_eq!(spec["head"], "softmax");
+        assert_eq!(
+            spec["layers"][1],
+            json!({ "weights": [-0.75, 0.25, 0.75, -0.25], "biases": [-0.125, 0.125], "activation": "identity" })
+        );
+        assert_eq!(result.report.ignored, ["ArgMax"]);
+        assert!(result.report.unsupported.is_empty(), "{:?}", result.report.unsupported);
+        assert!(result.report.notes.iter().any(|n| n.contains("two-class softmax head")));
+
+        // softmax of the two rows is [1 - sigmoid(z), sigmoid(z)]
+        let z: f64 = 0.8;
+        let (a, b) = (-z / 2.0, z / 2.0);
+        let p1 = b.exp() / (a.exp() + b.exp());
+        assert!((p1 - 1.0 / (1.0 + (-z).exp())).abs() < 1e-12);
+
+        // anything but 1 - p is arithmetic the guest does not do
+        let result = import_graph(&nodes(2.0), "skl2onnx").unwrap();
+        assert_eq!(result.spec["head"], "linear");
+        assert_eq!(result.report.unsupported, ["Sub"]);
+    }
+
+    #[test]
+    fn reports_ops_the_importer_did_not_consume() {
+        // a tree with a stray Gemm beside it and a Concat feeding it: neither is
+        // label post-processing of the tree's output
+        let mut tree = binary_booster(1);
+        tree.input = vec!["xc".into()];
+        tree.output = vec!["label".into(), "probabilities".into()];
+        let nodes = vec![
+            node("Concat", &["x", "x"], &["xc"], vec![]),
+            tree,
+            node("Gemm", &["x", "W"], &["g"], vec![]),
+            node("Cast", &["label"], &["label_out"], vec![]),
+            node("ZipMap", &["probabilities"], &["output_probability"], vec![]),
+        ];
+        let result = import_graph(&graph(nodes, vec![tensor("W", &[2, 2], &[1.0; 4])]), "test").unwrap();
+        assert_eq!(result.report.ignored, ["Cast", "ZipMap"]);
+        assert_eq!(result.report.unsupported, ["Concat", "Gemm"]);
+        assert_eq!(result.report.op_counts["TreeEnsembleClassifier"], 1);
+
+        // Sub after a dense network's output is arithmetic, not post-processing
+        let nodes = vec![
+            node("Gemm", &["x", "W"], &["h"], vec![]),
+            node("Sigmoid", &["h"], &["p"], vec![]),
+            node("Sub", &["one", "p"], &["q"], vec![]),
+            node("Reshape", &["q", "shape"], &["out"], vec![]),
+        ];
+        let result = import_graph(&graph(nodes, vec![tensor("W", &[2, 1], &[1.0, 2.0])]), "test").unwrap();
+        assert_eq!(result.report.ignored, ["Reshape"]);
+        assert_eq!(result.report.unsupported, ["Sub"]);
+    }
+
+    #[test]
+    fn collapses_single_class_binary_boosters() {
+        for class_id in [0, 1] {
+            let mut report = ImportReport::default();
+            let (spec, tree) = import_tree_ensemble(&binary_booster(class_id), Some(2), &mut report).unwrap();
+            let tree = tree.unwrap();
+            assert_eq!(spec["model_type"], 8);
+            // one score per leaf, as wide as the single base value
+            assert_eq!(tree["base_values"], json!([0.1f32 as f64]));
+            let leaves: Vec<Value> = tree["nodes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|n| n["mode"] == "LEAF")
+                .map(|n| n["value"][0].clone())
+                .collect();
+            assert_eq!(leaves, [json!([-0.5]), json!([0.75]), json!([0.25]), json!([-1.0])], "class {}", class_id);
+            assert!(report.unsupported.is_empty());
+        }
+    }
+
+    #[test]
+    fn keeps_both_columns_when_two_classes_are_used() {
+        let mut node = binary_booster(0);
+        node.attribute.retain(|a| a.name != "class_ids" && a.name != "base_values");
+        node.attribute.push(ints("class_ids", &[0, 1, 0, 1]));
+        let mut report = ImportReport::default();
+        let (_, tree) = import_tree_ensemble(&node, Some(2), &mut report).unwrap();
+        let leaf = tree.unwrap()["nodes"][1]["value"][0].clone();
+        assert_eq!(leaf, json!([-0.5, 0.0]));
+    }
+}
@@ -0,0 +1,213 @@
+// onnxruntime evaluation for `verse model check` and `verse model eval`: loads
+// an ONNX model with `ort` and runs batches of feature rows through it. Input and output names are read from the
+// session rather than hard-coded, so any skl2onnx export works:
+//
+//   label          int64 or string tensor [N]
+//   probabilities  sequence of maps (ZipMap) or float tensor [N, K]
+//   regression     float tensor [N, T] (no label output)
+//
+// Probabilities are returned in class order (map keys sorted), and labels as
+// the index of the class in that order, to line up with the guest's argmax.
+
+use ort::session::Session;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::tensor::TensorElementType;
+use ort::value::{DynValue, MapValueType, Tensor, ValueType};
+
+pub struct OnnxModel {
+    session: Session,
+    input_name: String,
+    input_width: Option<usize>,
+    label_output: Option<String>,
+    score_output: Option<String>,
+    // class keys from the probability map, once a batch has run
+    classes: Vec<String>,
+}
+
+pub struct OnnxRow {
+    // class index, for classifiers
+    pub label: Option<usize>,
+    // class probabilities, or regression targets
+    pub scores: Vec<f64>,
+}
+
+fn err(context: &str) -> impl Fn(ort::Error) -> String + '_ {
+    move |e| format!("{}: {}", context, e)
+}
+
+impl OnnxModel {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let session = Session::builder()
+            .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+            .and_then(|b| b.commit_from_file(path))
+            .map_err(|e| format!("Failed to load ONNX model ({}): {}", path, e))?;
+
+        let input = session.inputs.first().ok_or("ONNX model has no inputs")?;
+        if session.inputs.len() > 1 {
+            return Err(format!("ONNX model has {} inputs; expected one feature tensor", session.inputs.len()));
+        }
+        let input_name = input.name.clone();
+        let input_width = input
+            .input_type
+            .tensor_shape()
+            .and_then(|shape| shape.last().copied())
+            .filter(|&d| d > 0)
+            .map(|d| d as usize);
+
+        let mut label_output = None;
+        let mut score_output = None;
+        for output in session.outputs.iter() {
+            match &output.output_type {
+                ValueType::Tensor { ty: TensorElementType::Int64 | TensorElementType::String, .. } => {
+                    label_output.get_or_insert(output.name.clone());
+                }
+                ValueType::Tensor { ty: TensorElementType::Float32 | TensorElementType::Float64, .. }
+                | ValueType::Sequence(_) => {
+                    score_output.get_or_insert(output.name.clone());
+                }
+                _ => {}
+            }
+        }
+        if label_output.is_none() && score_output.is_none() {
+            return Err("ONNX model has no label, probability or float output".into());
+        }
+        Ok(OnnxModel { session, input_name, input_width, label_output, score_output, classes: Vec::new() })
+    }
+
+    // Prints the input and output names and types read from the model.
+    pub fn describe(&self) {
+        for input in self.session.inputs.iter() {
+            println!("  input   {:<20} {}", input.name, input.input_type);
+        }
+        for output in self.session.outputs.iter() {
+            let role = if Some(&output.name) == self.label_output.as_ref() {
+                " (label)"
+            } else if Some(&output.name) == self.score_output.as_ref() {
+                if self.is_classifier() { " (probabilities)" } else { " (prediction)" }
+            } else {
+                ""
+            };
+            println!("  output  {:<20} {}{}", output.name, output.output_type, role);
+        }
+    }
+
+    pub fn input_width(&self) -> Option<usize> {
+        self.input_width
+    }
+
+    // A model with a label output is scored as a classifier.
+    pub fn is_classifier(&self) -> bool {
+        self.label_output.is_some()
+    }
+
+    // Class keys in score order (sorted), when the model returns a
+    // probability map; empty before the first run or for tensor outputs.
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn run(&mut self, rows: &[Vec<f64>]) -> Result<Vec<OnnxRow>, String> {
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        let data: Vec<f32> = rows.iter().flat_map(|r| r.iter().map(|&v| v as f32)).collect();
+        let input = Tensor::from_array(([rows.len(), width], data.into_boxed_slice())).map_err(err("Invalid input"))?;
+        let mut outputs = self
+            .session
+            .run(ort::inputs![self.input_name.as_str() => input])
+            .map_err(err("onnxruntime failed"))?;
+        let label_value = self.label_output.as_deref().and_then(|name| outputs.remove(name));
+        let score_value = self.score_output.as_deref().and_then(|name| outputs.remove(name));
+        drop(outputs);
+
+        let (classes, scores) = match score_value {
+            Some(value) => self.extract_scores(&value, rows.len())?,
+            None => (Vec::new(), vec![Vec::new(); rows.len()]),
+        };
+        let labels = match label_value {
+            Some(value) => extract_labels(&value, &classes, rows.len())?,
+            None => vec![None; rows.len()],
+        };
+        if !classes.is_empty() {
+            self.classes = classes;
+        }
+        Ok(labels.into_iter().zip(scores).map(|(label, scores)| OnnxRow { label, scores }).collect())
+    }
+
+    // Returns the class keys (sorted, empty for tensors) and one score row per sample.
+    fn extract_scores(&self, value: &DynValue, n: usize) -> Result<(Vec<String>, Vec<Vec<f64>>), String> {
+        if let ValueType::Sequence(inner) = value.dtype() {
+            let allocator = self.session.allocator();
+            let mut classes: Vec<String> = Vec::new();
+            let mut rows = Vec::with_capacity(n);
+            let maps: Vec<Vec<(String, f64)>> = match inner.as_ref() {
+                ValueType::Map { key: TensorElementType::Int64, .. } => {
+                    let seq = value
+                        .try_extract_sequence::<MapValueType<i64, f32>>(allocator)
+                        .map_err(err("Invalid probability output"))?;
+                    let mut maps = Vec::with_capacity(seq.len());
+                    for m in seq.iter() {
+                        let mut kv = m.try_extract_key_values::<i64, f32>().map_err(err("Invalid probability map"))?;
+                        kv.sort_by_key(|(k, _)| *k);
+                        maps.push(kv.into_iter().map(|(k, p)| (k.to_string(), p as f64)).collect());
+                    }
+                    maps
+                }
+                ValueType::Map { key: TensorElementType::String, .. } => {
+                    let seq = value
+                        .try_extract_sequence::<MapValueType<String, f32>>(allocator)
+                        .map_err(err("Invalid probability output"))?;
+                    let mut maps = Vec::with_capacity(seq.len());
+                    for m in seq.iter() {
+                        let mut kv = m.try_extract_key_values::<String, f32>().map_err(err("Invalid probability map"))?;
+                        kv.sort_by(|a, b| a.0.cmp(&b.0));
+                        maps.push(kv.into_iter().map(|(k, p)| (k, p as f64)).collect());
+                    }
+                    maps
+                }
+                other => return Err(format!("Unsupported probability output type {:?}", other)),
+            };
+            for kv in maps {
+                if classes.is_empty() {
+                    classes = kv.iter().map(|(k, _)| k.clone()).collect();
+                }
+                rows.push(kv.into_iter().map(|(_, p)| p).collect());
+            }
+            return Ok((classes, rows));
+        }
+
+        let (shape, flat): (Vec<i64>, Vec<f64>) = match value.dtype().tensor_type() {
+            Some(TensorElementType::Float32) => {
+                let (shape, data) = value.try_extract_tensor::<f32>().map_err(err("Invalid float output"))?;
+                (shape.to_vec(), data.iter().map(|&v| v as f64).collect())
+            }
+            Some(TensorElementType::Float64) => {
+                let (shape, data) = value.try_extract_tensor::<f64>().map_err(err("Invalid float output"))?;
+                (shape.to_vec(), data.to_vec())
+            }
+            other => return Err(format!("Unsupported score output type {:?}", other)),
+        };
+        let width = if shape.len() > 1 { shape[1..].iter().product::<i64>() as usize } else { 1 };
+        if flat.len() != n * width {
+            return Err(format!("Score output has shape {:?} for {} rows", shape, n));
+        }
+        Ok((Vec::new(), flat.chunks(width.max(1)).map(|c| c.to_vec()).collect()))
+    }
+}
+
+fn extract_labels(value: &DynValue, classes: &[String], n: usize) -> Result<Vec<Option<usize>>, String> {
+    let keys: Vec<String> = match value.dtype().tensor_type() {
+        Some(TensorElementType::Int64) => {
+            let (_, data) = value.try_extract_tensor::<i64>().map_err(err("Invalid label output"))?;
+            // without a probability map, integer labels are taken as class indexes
+            if classes.is_empty() {
+                return Ok(data.iter().map(|&l| usize::try_from(l).ok()).collect());
+            }
+            data.iter().map(|l| l.to_string()).collect()
+        }
+        Some(TensorElementType::String) => value.try_extract_strings().map_err(err("Invalid label output"))?.1,
+        other => return Err(format!("Unsupported label output type {:?}", other)),
+    };
+    if keys.len() != n {
+        return Err(format!("Label output has {} entries for {} rows", keys.len(), n));
+    }
+    Ok(keys.iter().map(|k| classes.iter().position(|c| c == k)).collect())
+}
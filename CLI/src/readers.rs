@@ -0,0 +1,466 @@
+// Dataset readers. Every format is read into the same RawTable, optional
+// column names plus text cells, which ingest.rs (`verse request`) and
+// dataset.rs (`verse model check` / `eval`) validate and convert from there.
+// The format follows the file extension unless given explicitly:
+//
+//   csv               delimited text; the first record is a header when none
+//                     of its cells is a number
+//   ndjson, jsonl     one JSON value per line: an object of named columns, a
+//                     { "features": [...], "label": ... } object, or an array
+//   parquet           flat columns of numbers, booleans or strings; nulls are
+//                     missing cells
+//   npy               a 2-D numeric array, one row per sample (label last)
+//   npz               X (n x d) and y (n) arrays, or a single 2-D array
+//
+// Missing values come through as empty cells (NaN, null, None).
+
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+pub struct RawTable {
+    // column names, when the format carries them
+    pub names: Option<Vec<String>>,
+    pub records: Vec<Vec<String>>,
+}
+
+pub trait DatasetReader {
+    fn read(&self, path: &str) -> Result<RawTable, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Ndjson,
+    Parquet,
+    Npy,
+    Npz,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" | "tsv" | "txt" => Ok(Format::Csv),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            "parquet" | "pq" => Ok(Format::Parquet),
+            "npy" => Ok(Format::Npy),
+            "npz" => Ok(Format::Npz),
+            other => Err(format!(
+                "Unknown dataset format '{}' (expected csv, ndjson, parquet, npy or npz)",
+                other
+            )),
+        }
+    }
+}
+
+impl Format {
+    // The --format flag when given, else the file extension; CSV by default.
+    pub fn detect(path: &str, flag: Option<&str>) -> Result<Format, String> {
+        if let Some(flag) = flag {
+            return flag.parse();
+        }
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some(ext) => Ok(ext.parse().unwrap_or(Format::Csv)),
+            None => Ok(Format::Csv),
+        }
+    }
+
+    // `header` only applies to CSV: whether the first row names the columns,
+    // or None to guess.
+    pub fn reader(self, delimiter: u8, header: Option<bool>) -> Box<dyn DatasetReader> {
+        match self {
+            Format::Csv => Box::new(CsvReader { delimiter, header }),
+            Format::Ndjson => Box::new(NdjsonReader),
+            Format::Parquet => Box::new(ParquetReader),
+            Format::Npy => Box::new(NpyReader),
+            Format::Npz => Box::new(NpzReader),
+        }
+    }
+}
+
+// Text of a number cell; NaN is a missing (empty) cell.
+fn number_cell(v: f64) -> String {
+    if v.is_nan() { String::new() } else { v.to_string() }
+}
+
+// ------------------ CSV ------------------
+
+pub struct CsvReader {
+    pub delimiter: u8,
+    // None guesses: the first row is a header when none of its cells is a
+    // number, which misreads an all-categorical first data row
+    pub header: Option<bool>,
+}
+
+impl DatasetReader for CsvReader {
+    fn read(&self, path: &str) -> Result<RawTable, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| format!("Failed to read CSV file ({}): {}", path, e))?;
+        let mut records: Vec<Vec<String>> = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("{} record {}: {}", path, i + 1, e))?;
+            if record.iter().all(str::is_empty) {
+                continue;
+            }
+            records.push(record.iter().map(str::to_string).collect());
+        }
+        let has_header = self
+            .header
+            .unwrap_or_else(|| records.first().is_some_and(|r| r.iter().all(|c| c.parse::<f64>().is_err())));
+        let names = if has_header && !records.is_empty() { Some(records.remove(0)) } else { None };
+        Ok(RawTable { names, records })
+    }
+}
+
+// ------------------ NDJSON ------------------
+
+pub struct NdjsonReader;
+
+fn json_cell(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl DatasetReader for NdjsonReader {
+    fn read(&self, path: &str) -> Result<RawTable, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut names: Option<Vec<String>> = None;
+        let mut records = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let bad = |what: &str| format!("{} line {}: {}", path, line_no + 1, what);
+            let doc: Value = serde_json::from_str(line).map_err(|e| bad(&format!("invalid JSON: {}", e)))?;
+            let record: Vec<String> = match &doc {
+                Value::Array(values) => values.iter().map(json_cell).collect(),
+                Value::Object(obj) if obj.contains_key("features") => {
+                    let values = obj.get("features").and_then(Value::as_array).ok_or_else(|| bad("\"features\" is not an array"))?;
+                    let mut record: Vec<String> = values.iter().map(json_cell).collect();
+                    // a null or absent label leaves the row unlabelled
+                    if let Some(label) = obj.get("label").filter(|l| !l.is_null()) {
+                        record.push(json_cell(label));
+                    }
+                    record
+                }
+                Value::Object(obj) => {
+                    let keys: Vec<String> = obj.keys().cloned().collect();
+                    match names.as_ref() {
+                        None => names = Some(keys),
+                        Some(n) if *n != keys => return Err(bad("columns differ from the first line")),
+                        Some(_) => {}
+                    }
+                    obj.values().map(json_cell).collect()
+                }
+                _ => return Err(bad("expected an object or an array")),
+            };
+            records.push(record);
+        }
+        Ok(RawTable { names, records })
+    }
+}
+
+// ------------------ Parquet ------------------
+
+pub struct ParquetReader;
+
+impl DatasetReader for ParquetReader {
+    fn read(&self, path: &str) -> Result<RawTable, String> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let reader = SerializedFileReader::new(file).map_err(|e| format!("Invalid Parquet file {}: {}", path, e))?;
+        let names: Vec<String> = reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .root_schema()
+            .get_fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect();
+        let rows = reader.get_row_iter(None).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut records = Vec::new();
+        for (i, row) in rows.enumerate() {
+            let row = row.map_err(|e| format!("{} row {}: {}", path, i + 1, e))?;
+            let mut record = Vec::with_capacity(names.len());
+            for (name, field) in row.get_column_iter() {
+                let cell = match field {
+                    Field::Null => String::new(),
+                    Field::Bool(b) => b.to_string(),
+                    Field::Byte(v) => v.to_string(),
+                    Field::Short(v) => v.to_string(),
+                    Field::Int(v) => v.to_string(),
+                    Field::Long(v) => v.to_string(),
+                    Field::UByte(v) => v.to_string(),
+                    Field::UShort(v) => v.to_string(),
+                    Field::UInt(v) => v.to_string(),
+                    Field::ULong(v) => v.to_string(),
+                    Field::Float(v) => number_cell(*v as f64),
+                    Field::Double(v) => number_cell(*v),
+                    Field::Str(s) => s.clone(),
+                    other => return Err(format!("{}: column '{}' has unsupported type ({})", path, name, other)),
+                };
+                record.push(cell);
+            }
+            records.push(record);
+        }
+        Ok(RawTable { names: Some(names), records })
+    }
+}
+
+// ------------------ NumPy ------------------
+
+// A parsed .npy array: shape and values in row-major order. Integer and
+// boolean arrays keep integral text; floats use f64.
+struct NpyArray {
+    shape: Vec<usize>,
+    cells: Vec<String>,
+}
+
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let at = header.find(&format!("'{}'", key))?;
+    let rest = &header[at + key.len() + 2..];
+    Some(rest[rest.find(':')? + 1..].trim_start())
+}
+
+fn parse_npy(bytes: &[u8], what: &str) -> Result<NpyArray, String> {
+    let bad = |msg: &str| format!("{}: {}", what, msg);
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err(bad("not a .npy array"));
+    }
+    let (header_len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+        v => return Err(bad(&format!("unsupported .npy version {}", v))),
+    };
+    let header = std::str::from_utf8(bytes.get(start..start + header_len).ok_or_else(|| bad("truncated header"))?)
+        .map_err(|_| bad("header is not text"))?;
+    let data = &bytes[start + header_len..];
+
+    let descr = header_value(header, "descr")
+        .and_then(|v| v.strip_prefix('\''))
+        .and_then(|v| v.split('\'').next())
+        .ok_or_else(|| bad("header has no descr"))?;
+    let fortran = header_value(header, "fortran_order").is_some_and(|v| v.starts_with("True"));
+    let shape: Vec<usize> = header_value(header, "shape")
+        .and_then(|v| v.strip_prefix('('))
+        .and_then(|v| v.split(')').next())
+        .ok_or_else(|| bad("header has no shape"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|_| bad("invalid shape")))
+        .collect::<Result<_, _>>()?;
+
+    let (order, kind, size) = {
+        let mut chars = descr.chars();
+        let order = chars.next().ok_or_else(|| bad("empty descr"))?;
+        let kind = chars.next().ok_or_else(|| bad("empty descr"))?;
+        let size: usize = chars.as_str().parse().map_err(|_| bad(&format!("unsupported dtype {}", descr)))?;
+        (order, kind, size)
+    };
+    if !matches!((kind, size), ('f', 4 | 8) | ('i' | 'u', 1 | 2 | 4 | 8) | ('b', 1)) {
+        return Err(bad(&format!("unsupported dtype {}", descr)));
+    }
+    let big = order == '>';
+    // the shape is untrusted, so guard the size arithmetic
+    let count = shape
+        .iter()
+        .try_fold(1usize, |acc, &n| acc.checked_mul(n))
+        .ok_or_else(|| bad("shape is too large"))?;
+    if count.checked_mul(size).is_none_or(|n| data.len() < n) {
+        return Err(bad("data is shorter than the shape"));
+    }
+    let word = |i: usize| -> [u8; 8] {
+        let mut w = [0u8; 8];
+        let chunk = &data[i * size..(i + 1) * size];
+        if big {
+            // right-align for from_be_bytes
+            w[8 - size..].copy_from_slice(chunk);
+        } else {
+            w[..size].copy_from_slice(chunk);
+        }
+        w
+    };
+    let read = |w: [u8; 8]| if big { u64::from_be_bytes(w) } else { u64::from_le_bytes(w) };
+    let mut cells = Vec::with_capacity(count);
+    for i in 0..count {
+        let bits = read(word(i));
+        let cell = match (kind, size) {
+            ('f', 8) => number_cell(f64::from_bits(bits)),
+            ('f', 4) => number_cell(f32::from_bits(bits as u32) as f64),
+            ('i', 1 | 2 | 4 | 8) => {
+                let shift = 64 - 8 * size as u32;
+                (((bits << shift) as i64) >> shift).to_string()
+            }
+            ('u', 1 | 2 | 4 | 8) => bits.to_string(),
+            ('b', 1) => (bits != 0).to_string(),
+            _ => return Err(bad(&format!("unsupported dtype {}", descr))),
+        };
+        cells.push(cell);
+    }
+    if fortran && shape.len() == 2 {
+        let (n, d) = (shape[0], shape[1]);
+        cells = (0..n * d).map(|k| cells[(k % d) * n + k / d].clone()).collect();
+    }
+    Ok(NpyArray { shape, cells })
+}
+
+impl NpyArray {
+    // 2-D arrays give one record per row; 1-D arrays one cell per record.
+    fn records(&self, what: &str) -> Result<Vec<Vec<String>>, String> {
+        match self.shape.as_slice() {
+            [_] => Ok(self.cells.iter().map(|c| vec![c.clone()]).collect()),
+            [_, d] if *d > 0 => Ok(self.cells.chunks(*d).map(|r| r.to_vec()).collect()),
+            shape => Err(format!("{}: expected a 1-D or 2-D array, got shape {:?}", what, shape)),
+        }
+    }
+}
+
+pub struct NpyReader;
+
+impl DatasetReader for NpyReader {
+    fn read(&self, path: &str) -> Result<RawTable, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let array = parse_npy(&bytes, path)?;
+        if array.shape.len() != 2 {
+            return Err(format!("{}: expected a 2-D array (samples x columns), got shape {:?}", path, array.shape));
+        }
+        Ok(RawTable { names: None, records: array.records(path)? })
+    }
+}
+
+pub struct NpzReader;
+
+// Array names taken as the features and the labels, compared case-insensitively.
+const NPZ_FEATURES: [&str; 3] = ["x", "features", "data"];
+const NPZ_LABELS: [&str; 4] = ["y", "labels", "label", "target"];
+
+impl DatasetReader for NpzReader {
+    fn read(&self, path: &str) -> Result<RawTable, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid .npz file {}: {}", path, e))?;
+        let mut arrays: Vec<(String, NpyArray)> = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| format!("{}: {}", path, e))?;
+            let name = entry.name().trim_end_matches(".npy").to_string();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).map_err(|e| format!("{}: {}: {}", path, name, e))?;
+            let array = parse_npy(&bytes, &format!("{}:{}", path, name))?;
+            arrays.push((name, array));
+        }
+        let find = |keys: &[&str]| arrays.iter().find(|(n, _)| keys.contains(&n.to_ascii_lowercase().as_str()));
+        let (features, labels) = match (find(&NPZ_FEATURES), find(&NPZ_LABELS)) {
+            (Some(x), y) => (x, y),
+            (None, None) if arrays.len() == 1 => (&arrays[0], None),
+            _ => {
+                let names: Vec<&str> = arrays.iter().map(|(n, _)| n.as_str()).collect();
+                return Err(format!("{}: expected arrays X and y, found {:?}", path, names));
+            }
+        };
+        if features.1.shape.len() != 2 {
+            return Err(format!("{}: {} must be 2-D, got shape {:?}", path, features.0, features.1.shape));
+        }
+        let mut records = features.1.records(path)?;
+        if let Some((name, y)) = labels {
+            let column = matches!(y.shape.as_slice(), [_] | [_, 1]);
+            if y.cells.len() != records.len() || !column {
+                return Err(format!("{}: {} must hold one label per row of {}", path, name, features.0));
+            }
+            for (record, label) in records.iter_mut().zip(y.cells.iter()) {
+                record.push(label.clone());
+            }
+        }
+        Ok(RawTable { names: None, records })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A version 1 .npy file with the given header fields and raw data.
+    fn npy(descr: &str, shape: &str, fortran: bool, data: &[u8]) -> Vec<u8> {
+        let header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n",
+            descr,
+            if fortran { "True" } else { "False" },
+            shape
+        );
+        let mut out = b"\x93NUMPY\x01\x00".to_vec();
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn parse_err(bytes: &[u8]) -> String {
+        match parse_npy(bytes, "x.npy") {
+            Ok(array) => panic!("parsed as shape {:?}", array.shape),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn reads_supported_dtypes() {
+        let data: Vec<u8> = [1.5f64, -2.0, f64::NAN, 4.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let array = parse_npy(&npy("<f8", "(2, 2)", false, &data), "x.npy").unwrap();
+        assert_eq!(array.shape, [2, 2]);
+        assert_eq!(array.cells, ["1.5", "-2", "", "4"]);
+
+        let data: Vec<u8> = [-7i32, 300].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(parse_npy(&npy("<i4", "(2,)", false, &data), "x.npy").unwrap().cells, ["-7", "300"]);
+
+        let data: Vec<u8> = [0.25f32, 8.0].iter().flat_map(|v| v.to_be_bytes()).collect();
+        assert_eq!(parse_npy(&npy(">f4", "(2,)", false, &data), "x.npy").unwrap().cells, ["0.25", "8"]);
+
+        assert_eq!(parse_npy(&npy("|u1", "(2,)", false, &[255, 0]), "x.npy").unwrap().cells, ["255", "0"]);
+        assert_eq!(parse_npy(&npy("|b1", "(2,)", false, &[1, 0]), "x.npy").unwrap().cells, ["true", "false"]);
+    }
+
+    #[test]
+    fn transposes_fortran_order() {
+        // columns [1, 2, 3] and [4, 5, 6]
+        let data: Vec<u8> = [1i64, 2, 3, 4, 5, 6].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let array = parse_npy(&npy("<i8", "(3, 2)", true, &data), "x.npy").unwrap();
+        assert_eq!(array.records("x.npy").unwrap(), [["1", "4"], ["2", "5"], ["3", "6"]]);
+    }
+
+    #[test]
+    fn rejects_unsupported_dtypes() {
+        for descr in ["<U10", "<c16", "<f16", "<f2", "<i16", "|b2", "|O", "<M8[ns]"] {
+            let err = parse_err(&npy(descr, "(1,)", false, &[0; 64]));
+            assert!(err.contains(&format!("unsupported dtype {}", descr)), "{}: {}", descr, err);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(parse_err(b"not numpy at all").contains("not a .npy array"));
+        let mut truncated = npy("<f8", "(1,)", false, &[]);
+        truncated.truncate(20);
+        assert!(parse_err(&truncated).contains("truncated header"));
+        assert!(parse_err(&npy("<f8", "(1, x)", false, &[])).contains("invalid shape"));
+        assert!(parse_err(&npy("<f8", "(3,)", false, &[0; 16])).contains("data is shorter than the shape"));
+        // the element count overflows usize
+        let huge = format!("({}, {})", usize::MAX, 2);
+        assert!(parse_err(&npy("<f8", &huge, false, &[0; 16])).contains("shape is too large"));
+        // the count fits but count * size does not
+        let huge = format!("({},)", usize::MAX / 2);
+        assert!(parse_err(&npy("<f8", &huge, false, &[0; 16])).contains("data is shorter than the shape"));
+    }
+}
@@ -0,0 +1,42 @@
+// Reports for `verse verify`, from the verified journal; the printing itself
+// lives in verse_common::report so it matches what Zk-host prints after proving.
+
+use risc0_zkvm::serde::{from_slice, to_vec};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use verse_common::report::{
+    print_classification_report, print_regression_report, print_routing_report, ClassificationJournal,
+    RegressionJournal, RoutingReport,
+};
+
+// Decodes `bytes` as T only if T accounts for every word, so one journal
+// layout is never mistaken for another.
+fn decode_exact<T: DeserializeOwned + Serialize>(bytes: &[u8]) -> Option<T> {
+    let value: T = from_slice(bytes).ok()?;
+    let words = to_vec(&value).ok()?;
+    (words.len() * 4 == bytes.len()).then_some(value)
+}
+
+// Prints the report for a guest journal, naming classes with `labels`.
+// Returns false if the journal matches none of the known layouts.
+pub fn print_journal_report(bytes: &[u8], labels: &[String]) -> bool {
+    if let Some((predictions, routing)) = decode_exact::<(ClassificationJournal, RoutingReport)>(bytes) {
+        print_classification_report(&predictions, labels);
+        print_routing_report(&routing, predictions.len());
+    } else if let Some(((outputs, metrics), routing)) = decode_exact::<(RegressionJournal, RoutingReport)>(bytes) {
+        print_regression_report(&outputs, &metrics, true);
+        print_routing_report(&routing, outputs.len());
+    } else if let Some(predictions) = decode_exact::<ClassificationJournal>(bytes) {
+        print_classification_report(&predictions, labels);
+    } else if let Some((outputs, metrics)) = decode_exact::<RegressionJournal>(bytes) {
+        print_regression_report(&outputs, &metrics, true);
+    } else if let Some(output) = decode_exact::<Vec<(f32, f32)>>(bytes) {
+        println!("\n=== Results (first 5) ===");
+        for (i, (p, t)) in output.iter().enumerate().take(5) {
+            println!("{}: pred={:.6}, true={:.6}", i, p, t);
+        }
+    } else {
+        return false;
+    }
+    true
+}
@@ -0,0 +1,426 @@
+// `vectorFormat` schema language. A model publishes the layout of its input
+// vector and label as a schema; `verse model new` rejects malformed schemas
+// and `verse request --dataset` checks every row against the published one
+// before the dataset is embedded in the guest.
+//
+//   schema  := field { ";" field } [ ";" ]
+//   field   := [ "label" ] name ":" type [ range ]
+//   name    := identifier | "quoted text"
+//   type    := "float" | "int" | "bool" | "cat" "(" level { "|" level } ")"
+//   level   := identifier | "quoted text"
+//   range   := "[" [ number ] "," [ number ] "]"      (float and int only)
+//
+// Fields are listed in vector order. Exactly one field is the label, and it
+// comes last, matching the dataset layout (features, then label). Example:
+//
+//   age: int [0, 120]; bmi: float [10,]; smoker: bool;
+//   region: cat(north|south|"east coast"); label diagnosis: cat(benign|malignant)
+//
+// Dataset cells are checked against their field and converted to the numbers
+// the guest reads: bool cells (true/false/1/0) become 1 / 0, categorical cells
+// (a level name, or its index) become the level's index.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Float,
+    Int,
+    Bool,
+    Categorical(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: FieldType,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub features: Vec<Field>,
+    pub label: Field,
+}
+
+// Errors reported per dataset, at most this many.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldType::Float => write!(f, "float"),
+            FieldType::Int => write!(f, "int"),
+            FieldType::Bool => write!(f, "bool"),
+            FieldType::Categorical(levels) => write!(f, "cat({})", levels.join("|")),
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_ws(&mut self) {
+        while let Some(c) = self.text[self.pos..].chars().next() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, what: &str) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}' {}", c, what)))
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{} at position {}", msg, self.pos + 1)
+    }
+
+    // identifier or "quoted text"
+    fn word(&mut self, what: &str) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                let rest = &self.text[self.pos..];
+                let end = rest.find('"').ok_or_else(|| self.error("unterminated quoted name"))?;
+                let word = rest[..end].to_string();
+                self.pos += end + 1;
+                if word.is_empty() {
+                    return Err(self.error(&format!("empty {}", what)));
+                }
+                Ok(word)
+            }
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                let rest = &self.text[self.pos..];
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+                    .unwrap_or(rest.len());
+                self.pos += end;
+                Ok(rest[..end].to_string())
+            }
+            _ => Err(self.error(&format!("expected {}", what))),
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<f64>, String> {
+        self.skip_ws();
+        let rest = &self.text[self.pos..];
+        let end = rest.find(|c: char| matches!(c, ',' | ']') || c.is_whitespace()).unwrap_or(rest.len());
+        if end == 0 {
+            return Ok(None);
+        }
+        let v: f64 = rest[..end]
+            .parse()
+            .map_err(|_| self.error(&format!("invalid number '{}'", &rest[..end])))?;
+        if !v.is_finite() {
+            return Err(self.error("range bounds must be finite"));
+        }
+        self.pos += end;
+        Ok(Some(v))
+    }
+
+    fn field(&mut self) -> Result<(Field, bool), String> {
+        let mut name = self.word("a field name")?;
+        let mut is_label = false;
+        if name == "label" && self.peek() != Some(':') {
+            is_label = true;
+            name = self.word("a label name")?;
+        }
+        self.expect(':', &format!("after field '{}'", name))?;
+        let ty = match self.word("a type")?.as_str() {
+            "float" => FieldType::Float,
+            "int" => FieldType::Int,
+            "bool" => FieldType::Bool,
+            "cat" => {
+                self.expect('(', "to open the category levels")?;
+                let mut levels = vec![self.word("a category level")?];
+                while self.eat('|') {
+                    levels.push(self.word("a category level")?);
+                }
+                self.expect(')', "to close the category levels")?;
+                if levels.len() < 2 {
+                    return Err(format!("field '{}': a categorical type needs at least 2 levels", name));
+                }
+                for (i, level) in levels.iter().enumerate() {
+                    if levels[..i].contains(level) {
+                        return Err(format!("field '{}': level '{}' is repeated", name, level));
+                    }
+                }
+                FieldType::Categorical(levels)
+            }
+            other => {
+                return Err(format!(
+                    "field '{}': unknown type '{}' (expected float, int, bool or cat(...))",
+                    name, other
+                ));
+            }
+        };
+        let (mut min, mut max) = (None, None);
+        if self.eat('[') {
+            if !matches!(ty, FieldType::Float | FieldType::Int) {
+                return Err(format!("field '{}': only float and int fields take a range", name));
+            }
+            min = self.number()?;
+            self.expect(',', "between the range bounds")?;
+            max = self.number()?;
+            self.expect(']', "to close the range")?;
+            if let (Some(lo), Some(hi)) = (min, max)
+                && lo > hi
+            {
+                return Err(format!("field '{}': range [{}, {}] is empty", name, lo, hi));
+            }
+        }
+        Ok((Field { name, ty, min, max }, is_label))
+    }
+}
+
+// Whether a vectorFormat predates the schema language: free text such as
+// "len=3; x[0]=age, x[1]=bmi". Every schema field has a ':', so text without
+// one cannot be a schema; anything else is parsed and its errors reported.
+pub fn is_legacy(text: &str) -> bool {
+    text.trim_start().starts_with("len=") || !text.contains(':')
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> Result<Schema, String> {
+        let mut p = Parser { text, pos: 0 };
+        let mut features = Vec::new();
+        let mut label = None;
+        while p.peek().is_some() {
+            let (field, is_label) = p.field()?;
+            if label.is_some() {
+                return Err(format!("field '{}' comes after the label; the label must be the last field", field.name));
+            }
+            if features.iter().any(|f: &Field| f.name == field.name) {
+                return Err(format!("field '{}' is defined twice", field.name));
+            }
+            if is_label {
+                label = Some(field);
+            } else {
+                features.push(field);
+            }
+            if !p.eat(';') && p.peek().is_some() {
+                return Err(p.error("expected ';' between fields"));
+            }
+        }
+        let label = label.ok_or("schema has no label field (mark the last field with 'label')")?;
+        if features.is_empty() {
+            return Err("schema has no feature fields".into());
+        }
+        if features.iter().any(|f| f.name == label.name) {
+            return Err(format!("label '{}' has the same name as a feature", label.name));
+        }
+        Ok(Schema { features, label })
+    }
+
+    pub fn feature_names(&self) -> Vec<String> {
+        self.features.iter().map(|f| f.name.clone()).collect()
+    }
+
+    // Class names for a categorical label, in index order.
+    pub fn class_labels(&self) -> Vec<String> {
+        match &self.label.ty {
+            FieldType::Categorical(levels) => levels.clone(),
+            FieldType::Bool => vec!["false".into(), "true".into()],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn print(&self) {
+        for (i, f) in self.features.iter().chain(std::iter::once(&self.label)).enumerate() {
+            let range = match (f.min, f.max) {
+                (None, None) => String::new(),
+                (lo, hi) => format!(
+                    " [{}, {}]",
+                    lo.map(|v| v.to_string()).unwrap_or_default(),
+                    hi.map(|v| v.to_string()).unwrap_or_default()
+                ),
+            };
+            let role = if i == self.features.len() { "  (label)" } else { "" };
+            println!("  {:>3}  {:<20} {}{}{}", i, f.name, f.ty, range, role);
+        }
+    }
+
+    // Checks a dataset (features then label per row) and converts it to the
+    // numbers embedded in the guest. Header names, when the file has them,
+    // must name the fields in schema order. Reports up to MAX_REPORTED_ERRORS
+    // problems; lines are counted from the first data row.
+    pub fn validate(&self, header: Option<&[String]>, rows: &[Vec<String>]) -> Result<Vec<Vec<f32>>, String> {
+        let fields: Vec<&Field> = self.features.iter().chain(std::iter::once(&self.label)).collect();
+        if let Some(header) = header {
+            let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+            let given: Vec<&str> = header.iter().map(|h| h.trim()).collect();
+            if given != names {
+                return Err(format!("Columns {:?} do not match the model's schema {:?}", given, names));
+            }
+        }
+
+        let mut errors = Vec::new();
+        let mut out = Vec::with_capacity(rows.len());
+        for (r, row) in rows.iter().enumerate() {
+            if row.len() != fields.len() {
+                errors.push(format!("line {}: {} columns, schema has {}", r + 1, row.len(), fields.len()));
+            } else {
+                let mut values = Vec::with_capacity(row.len());
+                for (cell, field) in row.iter().zip(fields.iter()) {
+                    match field.check(cell.trim()) {
+                        Ok(v) => values.push(v as f32),
+                        Err(e) => errors.push(format!("line {}, {}: {}", r + 1, field.name, e)),
+                    }
+                }
+                out.push(values);
+            }
+            if errors.len() >= MAX_REPORTED_ERRORS {
+                break;
+            }
+        }
+        if errors.is_empty() {
+            Ok(out)
+        } else {
+            Err(format!("Dataset does not match the model's schema:\n  {}", errors.join("\n  ")))
+        }
+    }
+}
+
+impl Field {
+    // Value of one cell, or why it is rejected.
+    fn check(&self, cell: &str) -> Result<f64, String> {
+        if cell.is_empty() {
+            return Err("missing value".into());
+        }
+        let v = match &self.ty {
+            FieldType::Float => match cell.parse::<f64>() {
+                Ok(v) if v.is_finite() => v,
+                _ => return Err(format!("'{}' is not a number", cell)),
+            },
+            FieldType::Int => match cell.parse::<f64>() {
+                Ok(v) if v.is_finite() && v.fract() == 0.0 => v,
+                _ => return Err(format!("'{}' is not an integer", cell)),
+            },
+            FieldType::Bool => match cell.to_ascii_lowercase().as_str() {
+                "true" | "1" => 1.0,
+                "false" | "0" => 0.0,
+                _ => return Err(format!("'{}' is not a boolean (true/false/1/0)", cell)),
+            },
+            FieldType::Categorical(levels) => match levels.iter().position(|l| l == cell) {
+                Some(i) => i as f64,
+                None => match cell.parse::<usize>() {
+                    Ok(i) if i < levels.len() => i as f64,
+                    _ => return Err(format!("'{}' is not one of {}", cell, levels.join("|"))),
+                },
+            },
+        };
+        if self.min.is_some_and(|lo| v < lo) || self.max.is_some_and(|hi| v > hi) {
+            return Err(format!(
+                "{} is outside [{}, {}]",
+                cell,
+                self.min.map(|v| v.to_string()).unwrap_or_default(),
+                self.max.map(|v| v.to_string()).unwrap_or_default()
+            ));
+        }
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(text: &str) -> String {
+        Schema::parse(text).expect_err(text)
+    }
+
+    #[test]
+    fn parses_the_example_schema() {
+        let schema = Schema::parse(
+            "age: int [0, 120]; bmi: float [10,]; smoker: bool;\
+             region: cat(north|south|\"east coast\"); label diagnosis: cat(benign|malignant);",
+        )
+        .unwrap();
+        assert_eq!(schema.feature_names(), ["age", "bmi", "smoker", "region"]);
+        assert_eq!(schema.features[1].min, Some(10.0));
+        assert_eq!(schema.features[1].max, None);
+        assert_eq!(
+            schema.features[3].ty,
+            FieldType::Categorical(vec!["north".into(), "south".into(), "east coast".into()])
+        );
+        assert_eq!(schema.class_labels(), ["benign", "malignant"]);
+    }
+
+    #[test]
+    fn rejects_malformed_schemas() {
+        let cases = [
+            ("a: float; b: int", "no label field"),
+            ("label y: bool", "no feature fields"),
+            ("a: float; label y: bool; b: int", "comes after the label"),
+            ("a: float; a: int; label y: bool", "defined twice"),
+            ("a: double; label y: bool", "unknown type 'double'"),
+            ("a: cat(x); label y: bool", "at least 2 levels"),
+            ("a: cat(x|x); label y: bool", "level 'x' is repeated"),
+            ("a: bool [0, 1]; label y: bool", "only float and int fields take a range"),
+            ("a: float [5, 1]; label y: bool", "range [5, 1] is empty"),
+            ("a: float [x, 1]; label y: bool", "invalid number 'x'"),
+            ("a: float [inf,]; label y: bool", "must be finite"),
+            ("a: float [0 1]; label y: bool", "expected ','"),
+            ("a float; label y: bool", "expected ':'"),
+            ("a: float label y: bool", "expected ';' between fields"),
+            ("\"a: float; label y: bool", "unterminated quoted name"),
+            ("a: cat(x|y; label y: bool", "expected ')'"),
+        ];
+        for (text, expected) in cases {
+            let err = parse_err(text);
+            assert!(err.contains(expected), "{:?}: got {:?}, expected {:?}", text, err, expected);
+        }
+    }
+
+    #[test]
+    fn tells_legacy_text_from_schemas() {
+        assert!(is_legacy("len=3; x[0]=age, x[1]=bmi"));
+        assert!(is_legacy("three floats then a label"));
+        assert!(!is_legacy("a: float; label y: bool"));
+        // a schema typo is reported, not skipped as legacy text
+        assert!(!is_legacy("a: flaot; label y: bool"));
+    }
+
+    #[test]
+    fn validates_and_converts_rows() {
+        let schema = Schema::parse("n: int [0, 10]; b: bool; label c: cat(lo|hi)").unwrap();
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let values = schema.validate(None, &[row(&["3", "true", "hi"]), row(&["0", "0", "0"])]).unwrap();
+        assert_eq!(values, [vec![3.0, 1.0, 1.0], vec![0.0, 0.0, 0.0]]);
+
+        let err = schema
+            .validate(None, &[row(&["3.5", "yes", "mid"]), row(&["11", "1"])])
+            .unwrap_err();
+        assert!(err.contains("line 1, n: '3.5' is not an integer"), "{}", err);
+        assert!(err.contains("line 1, b: 'yes' is not a boolean"), "{}", err);
+        assert!(err.contains("line 1, c: 'mid' is not one of lo|hi"), "{}", err);
+        assert!(err.contains("line 2: 2 columns, schema has 3"), "{}", err);
+
+        let header = row(&["n", "c", "b"]);
+        assert!(schema.validate(Some(&header), &[]).unwrap_err().contains("do not match"));
+    }
+}
@@ -0,0 +1,199 @@
+// Native tree evaluator for `verse model check`. Reads the same tree JSON the
+// ZK guest reads (a list of nodes, or an ensemble document from
+// `verse model import` / model-owner/tree2json.py), flattens it as the host
+// does for the guest and walks it with verse_common::flat_tree::FlatTrees,
+// which does the guest's own walk and scoring, recording the path taken
+// through each tree so a divergence from onnxruntime can be traced to a split.
+
+use verse_common::flat_tree::{self, FlatTrees, LeafValues};
+use verse_common::tree::{SplitMode, TreeEnsemble};
+
+// One split on the way to a leaf.
+#[derive(Debug, Clone)]
+pub struct PathStep {
+    pub tree_id: usize,
+    pub node_id: usize,
+    pub feature: usize,
+    pub x: f64,
+    pub threshold: f64,
+    pub mode: SplitMode,
+    pub took_true: bool,
+}
+
+pub struct Evaluation {
+    pub scores: Vec<f64>,
+    pub path: Vec<PathStep>,
+}
+
+pub struct TreeModel {
+    ensemble: TreeEnsemble,
+    // the flattened words the guest would receive
+    words: Vec<u32>,
+    is_classifier: bool,
+}
+
+impl TreeModel {
+    pub fn from_json(tree_json: &str, is_classifier: bool) -> Result<Self, String> {
+        let ensemble = TreeEnsemble::from_json(tree_json)?;
+        let leaf_values = if is_classifier { LeafValues::ClassScores } else { LeafValues::Targets };
+        let words = flat_tree::flatten_tree_json(tree_json, leaf_values)?;
+        Ok(TreeModel { ensemble, words, is_classifier })
+    }
+
+    pub fn max_feature(&self) -> Option<usize> {
+        self.ensemble.nodes.iter().filter(|n| n.mode != SplitMode::Leaf).filter_map(|n| n.feature).max()
+    }
+
+    pub fn evaluate(&self, x: &[f64]) -> Result<Evaluation, String> {
+        let trees = FlatTrees::parse(&self.words)?;
+        let mut path = Vec::new();
+        let mut leaves = Vec::with_capacity(trees.num_trees);
+        for t in 0..trees.num_trees {
+            let leaf = trees.find_leaf_with(t, x, |split| {
+                path.push(PathStep {
+                    tree_id: t,
+                    // flattened positions are indexes into the JSON node list
+                    node_id: self.ensemble.nodes[split.node].id,
+                    feature: split.feature,
+                    x: split.x,
+                    threshold: split.threshold,
+                    mode: split.mode,
+                    took_true: split.took_true,
+                })
+            })?;
+            leaves.push(trees.leaf_values(leaf));
+        }
+        let mut scores =
+            flat_tree::combine_leaves(leaves.into_iter(), trees.aggregate, &trees.base_values, trees.post_transform)?;
+        // a binary classifier with weights for one class only keeps one score
+        // per leaf; the guest expands it to two like onnxruntime
+        if self.is_classifier && scores.len() == 1 {
+            scores = if trees.signed_binary_scores() {
+                vec![-scores[0], scores[0]]
+            } else {
+                vec![1.0 - scores[0], scores[0]]
+            };
+        }
+        Ok(Evaluation { scores, path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use verse_common::tree::takes_true_branch;
+
+    // Two three-class stumps: tree 0 on x[0] at 0.5, tree 1 (node ids out of
+    // order) on x[1] at 0.
+    fn ensemble(aggregate: &str, post_transform: &str, base_values: Value) -> String {
+        json!({
+            "aggregate": aggregate,
+            "post_transform": post_transform,
+            "base_values": base_values,
+            "nodes": [
+                {"tree_id": 0, "id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2},
+                {"tree_id": 0, "id": 1, "mode": "LEAF", "value": [[1.0, 0.0, 0.0]]},
+                {"tree_id": 0, "id": 2, "mode": "LEAF", "value": [[0.0, 2.0, 0.0]]},
+                {"tree_id": 1, "id": 2, "mode": "LEAF", "value": [[0.0, 0.0, 4.0]]},
+                {"tree_id": 1, "id": 1, "mode": "LEAF", "value": [[3.0, 0.0, 1.0]]},
+                {"tree_id": 1, "id": 0, "feature": 1, "threshold": 0.0, "left": 1, "right": 2,
+                 "mode": "BRANCH_LEQ", "missing_tracks_true": true}
+            ]
+        })
+        .to_string()
+    }
+
+    fn scores(tree_json: &str, is_classifier: bool, x: &[f64]) -> Vec<f64> {
+        TreeModel::from_json(tree_json, is_classifier).unwrap().evaluate(x).unwrap().scores
+    }
+
+    fn assert_close(got: &[f64], expected: &[f64]) {
+        assert_eq!(got.len(), expected.len(), "{:?} vs {:?}", got, expected);
+        for (g, e) in got.iter().zip(expected) {
+            assert!((g - e).abs() < 1e-12, "{:?} vs {:?}", got, expected);
+        }
+    }
+
+    #[test]
+    fn aggregates_across_trees() {
+        // x = [0, 1] reaches [1, 0, 0] and [0, 0, 4]
+        let x = [0.0, 1.0];
+        assert_close(&scores(&ensemble("SUM", "NONE", json!([])), true, &x), &[1.0, 0.0, 4.0]);
+        assert_close(&scores(&ensemble("AVERAGE", "NONE", json!([])), true, &x), &[0.5, 0.0, 2.0]);
+        assert_close(&scores(&ensemble("MIN", "NONE", json!([])), true, &x), &[0.0, 0.0, 0.0]);
+        assert_close(&scores(&ensemble("MAX", "NONE", json!([])), true, &x), &[1.0, 0.0, 4.0]);
+    }
+
+    #[test]
+    fn adds_base_values_before_the_post_transform() {
+        let x = [1.0, -1.0];
+        // [0, 2, 0] + [3, 0, 1], averaged, then shifted
+        let tree_json = ensemble("AVERAGE", "NONE", json!([0.5, -1.0, 0.25]));
+        assert_close(&scores(&tree_json, true, &x), &[2.0, 0.0, 0.75]);
+
+        let z: [f64; 3] = [2.0, 0.0, 0.75];
+        let tree_json = ensemble("AVERAGE", "LOGISTIC", json!([0.5, -1.0, 0.25]));
+        let logistic: Vec<f64> = z.iter().map(|&z| 1.0 / (1.0 + (-z).exp())).collect();
+        assert_close(&scores(&tree_json, true, &x), &logistic);
+
+        let tree_json = ensemble("AVERAGE", "SOFTMAX", json!([0.5, -1.0, 0.25]));
+        let sum: f64 = z.iter().map(|z| z.exp()).sum();
+        let softmax: Vec<f64> = z.iter().map(|z| z.exp() / sum).collect();
+        let got = scores(&tree_json, true, &x);
+        assert_close(&got, &softmax);
+        assert!((got.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let tree_json = ensemble("SUM", "NONE", json!([1.0]));
+        let err = TreeModel::from_json(&tree_json, true).err().unwrap();
+        assert!(err.contains("base_values width differs"), "{}", err);
+    }
+
+    #[test]
+    fn expands_single_binary_scores() {
+        let stump = |low: f64, high: f64, post_transform: &str| {
+            json!({
+                "post_transform": post_transform,
+                "nodes": [
+                    {"id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2},
+                    {"id": 1, "mode": "LEAF", "value": [[low]]},
+                    {"id": 2, "mode": "LEAF", "value": [[high]]}
+                ]
+            })
+            .to_string()
+        };
+        // probabilities: [1 - s, s]
+        assert_close(&scores(&stump(0.25, 0.75, "NONE"), true, &[1.0]), &[0.25, 0.75]);
+        // a negative margin under NONE: [-s, s]
+        assert_close(&scores(&stump(-0.5, 0.75, "NONE"), true, &[1.0]), &[-0.75, 0.75]);
+        assert_close(&scores(&stump(-0.5, 0.75, "NONE"), true, &[0.0]), &[0.5, -0.5]);
+        // margins squashed by LOGISTIC are probabilities again
+        let s = 1.0 / (1.0 + 0.5f64.exp());
+        assert_close(&scores(&stump(-0.5, 0.75, "LOGISTIC"), true, &[0.0]), &[1.0 - s, s]);
+        // regressors keep their single target
+        assert_close(&scores(&stump(-0.5, 0.75, "NONE"), false, &[0.0]), &[-0.5]);
+    }
+
+    #[test]
+    fn matches_flat_trees_and_records_the_path() {
+        let tree_json = ensemble("SUM", "SOFTMAX", json!([0.0, 0.5, 1.0]));
+        let model = TreeModel::from_json(&tree_json, true).unwrap();
+        let words = flat_tree::flatten_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+        let trees = FlatTrees::parse(&words).unwrap();
+        for x0 in [0.0, 0.5, 1.0, f64::NAN] {
+            for x1 in [-1.0, 0.0, 1.0, f64::NAN] {
+                let x = [x0, x1];
+                let evaluation = model.evaluate(&x).unwrap();
+                assert_eq!(evaluation.scores, trees.predict(&x).unwrap(), "x {:?}", x);
+                assert_eq!(evaluation.path.len(), 2);
+                for (t, step) in evaluation.path.iter().enumerate() {
+                    assert_eq!((step.tree_id, step.node_id, step.feature), (t, 0, t));
+                    assert_eq!(step.took_true, takes_true_branch(step.mode, t == 1, x[t], step.threshold));
+                }
+            }
+        }
+        assert_eq!(model.max_feature(), Some(1));
+        let err = model.evaluate(&[0.0]).err().unwrap();
+        assert!(err.contains("splits on feature 1 but the row has 1"), "{}", err);
+    }
+}
@@ -23,7 +23,10 @@ async def create_model(model_data: ModelCreate, current_user: User) -> ModelResp
             userId=current_user,
             vectorFormat=model_data.vectorFormat,
             name=model_data.name,
-            description=model_data.description
+            description=model_data.description,
+            featureNames=model_data.featureNames,
+            classLabels=model_data.classLabels,
+            preprocessing=model_data.preprocessing
         )
         
         # Save the model to the database
@@ -33,6 +36,11 @@ async def create_model(model_data: ModelCreate, current_user: User) -> ModelResp
             id=str(model.id),
             userId=str(current_user.id),
             vectorFormat=model.vectorFormat,
+            name=model.name,
+            description=model.description,
+            featureNames=model.featureNames,
+            classLabels=model.classLabels,
+            preprocessing=model.preprocessing,
             createdAt=model.createdAt,
             updatedAt=model.updatedAt
         )
@@ -54,6 +62,9 @@ async def get_user_models(current_user: User):
                 vectorFormat=model.vectorFormat,
                 name=model.name,
                 description=model.description,
+                featureNames=model.featureNames,
+                classLabels=model.classLabels,
+                preprocessing=model.preprocessing,
                 createdAt=model.createdAt,
                 updatedAt=model.updatedAt
             )
@@ -77,6 +88,9 @@ async def get_all_models_controller():
                 vectorFormat=model.vectorFormat,
                 name=model.name,
                 description=model.description,
+                featureNames=model.featureNames,
+                classLabels=model.classLabels,
+                preprocessing=model.preprocessing,
                 createdAt=model.createdAt,
                 updatedAt=model.updatedAt
             )
@@ -281,6 +295,9 @@ async def get_user_models_with_validations(current_user: User) -> UserModelsWith
                     name = model.name,
                     description = model.description,
                     vectorFormat=model.vectorFormat,
+                    featureNames=model.featureNames,
+                    classLabels=model.classLabels,
+                    preprocessing=model.preprocessing,
                     createdAt=model.createdAt,
                     updatedAt=model.updatedAt,
                     validationRequests=validation_responses
@@ -364,7 +381,7 @@ async def add_proof_to_validation(validation_request_id: str, json_file: UploadF
                         'validation_request_id': str(validation_request_doc.id),
                         'upload_date': datetime.utcnow().isoformat()
                     },
-                    content_type='application/json',
+                    content_type='application/json' if file_extension == '.json' else 'application/octet-stream',
                     r2_manager=r2_manager
                 )
                 
@@ -452,6 +469,9 @@ async def get_verifier_validation_requests_controller(current_user: User) -> Lis
                     vectorFormat=model.vectorFormat,
                     name=model.name,
                     description=model.description,
+                    featureNames=model.featureNames,
+                    classLabels=model.classLabels,
+                    preprocessing=model.preprocessing,
                     createdAt=model.createdAt,
                     updatedAt=model.updatedAt
                 )
@@ -488,6 +508,25 @@ async def get_particular_validation_request(validation_request_id: str) -> Valid
                 detail="Validation request not found"
             )
         
+        # Include the model so verifiers get its feature and class names
+        model = await Model.get(validation_request.modelId.ref.id)
+        model_response = None
+        if model:
+            owner = await User.get(model.userId.ref.id)
+            model_response = ModelResponse(
+                id=str(model.id),
+                userId=str(model.userId.ref.id),
+                vectorFormat=model.vectorFormat,
+                name=model.name,
+                description=model.description,
+                featureNames=model.featureNames,
+                classLabels=model.classLabels,
+                preprocessing=model.preprocessing,
+                ownerPublicKey=owner.publicKey if owner else None,
+                createdAt=model.createdAt,
+                updatedAt=model.updatedAt
+            )
+
         return ValidationRequestResponse(
             id=str(validation_request.id),
             modelId=str(validation_request.modelId.ref.id),
@@ -497,6 +536,7 @@ async def get_particular_validation_request(validation_request_id: str) -> Valid
             proofHash=validation_request.proofHash,
             status=validation_request.status,
             createdAt=validation_request.createdAt,
+            model=model_response
         )
         
     except HTTPException:
@@ -1,5 +1,5 @@
 from models.user import User
-from schemas.user import UserRegistration, UserLogin, UserResponse, Token
+from schemas.user import UserRegistration, UserLogin, UserResponse, Token, PublicKeyUpdate, PublicKeyResponse
 from utils.password import verify_password 
 from utils.auth import create_access_token
 from fastapi import HTTPException, status
@@ -60,4 +60,22 @@ async def login_user(user_data: UserLogin) -> Token:
         access_token=access_token,
         token_type="bearer",
         expires_in=expires_in
-    )
\ No newline at end of file
+    )
+
+
+async def set_public_key(key_data: PublicKeyUpdate, current_user: User) -> PublicKeyResponse:
+    """Publish the key the user's proof envelopes are signed with"""
+    public_key = key_data.publicKey.strip().lower()
+    try:
+        valid = len(bytes.fromhex(public_key)) == 32
+    except ValueError:
+        valid = False
+    if not valid:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="publicKey must be a hex-encoded 32-byte Ed25519 public key"
+        )
+
+    current_user.publicKey = public_key
+    await current_user.save()
+    return PublicKeyResponse(id=str(current_user.id), publicKey=public_key)
@@ -1,5 +1,5 @@
 from beanie import Document, Link
-from typing import Optional
+from typing import Any, Dict, List, Optional
 from datetime import datetime
 from enum import Enum
 from pydantic import Field
@@ -14,6 +14,9 @@ class Model(Document):
     vectorFormat: str
     name: str 
     description: Optional[str] = None
+    featureNames: List[str] = []
+    classLabels: List[str] = []
+    preprocessing: Optional[Dict[str, Any]] = None
     createdAt: datetime = Field(default_factory=datetime.utcnow)
     updatedAt: datetime = Field(default_factory=datetime.utcnow)
     
@@ -6,6 +6,8 @@ from utils.password import hash_password
 class User(Document):
     email: EmailStr = Field(..., unique=True)
     hashed_password: str
+    # Hex Ed25519 key the user's proof envelopes are signed with
+    publicKey: Optional[str] = None
     
     class Settings:
         name = "users"
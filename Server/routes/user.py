@@ -1,6 +1,6 @@
 from fastapi import APIRouter, HTTPException, status, Depends
-from controller.user import get_all_users, register_user, login_user
-from schemas.user import UserRegistration, UserLogin, UserResponse, Token
+from controller.user import get_all_users, register_user, login_user, set_public_key
+from schemas.user import UserRegistration, UserLogin, UserResponse, Token, PublicKeyUpdate, PublicKeyResponse
 from utils.auth import get_current_user
 from models.user import User
 router = APIRouter()
@@ -18,6 +18,12 @@ async def login(user_data: UserLogin):
     return await login_user(user_data)
 
 
+@router.put("/public-key", response_model=PublicKeyResponse)
+async def publish_public_key(key_data: PublicKeyUpdate, current_user: User = Depends(get_current_user)):
+    """Publish the current user's proof signing key"""
+    return await set_public_key(key_data, current_user)
+
+
 @router.get("",response_model=list[UserResponse])
 async def list_users( current_user:User = Depends(get_current_user)):
     """Get all users"""
@@ -1,5 +1,5 @@
 from pydantic import BaseModel
-from typing import Optional, List
+from typing import Any, Dict, Optional, List
 from datetime import datetime
 from enum import Enum
 
@@ -12,6 +12,11 @@ class ModelCreate(BaseModel):
     vectorFormat: str
     name: str 
     description: Optional[str] = None
+    # Ordered input and class names; reports show these instead of indices
+    featureNames: List[str] = []
+    classLabels: List[str] = []
+    # Encoders and scalers from raw dataset columns to the model input
+    preprocessing: Optional[Dict[str, Any]] = None
 
 class ModelResponse(BaseModel):
     id: str
@@ -19,6 +24,11 @@ class ModelResponse(BaseModel):
     name :str 
     description: Optional[str] = None
     vectorFormat: Optional[str] = None
+    featureNames: List[str] = []
+    classLabels: List[str] = []
+    preprocessing: Optional[Dict[str, Any]] = None
+    # Owner's proof signing key, when published; set on validation request lookups
+    ownerPublicKey: Optional[str] = None
     createdAt: datetime
     updatedAt: datetime
 
@@ -54,6 +64,9 @@ class ModelWithValidationsResponse(BaseModel):
     name: str
     description: Optional[str] = None
     vectorFormat: Optional[str] = None
+    featureNames: List[str] = []
+    classLabels: List[str] = []
+    preprocessing: Optional[Dict[str, Any]] = None
     createdAt: datetime
     updatedAt: datetime
     validationRequests: List[ValidationRequestResponse] = []
@@ -13,6 +13,13 @@ class UserResponse(BaseModel):
     id: str
     email: EmailStr
 
+class PublicKeyUpdate(BaseModel):
+    publicKey: str
+
+class PublicKeyResponse(BaseModel):
+    id: str
+    publicKey: str
+
 class Token(BaseModel):
     access_token: str
     token_type: str
@@ -1,4 +1,4 @@
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 use risc0_zkvm::guest::env;
 
 // pub fn run_onnx_inference(model_path: &str, input_data: &[f32]) -> Result<(), Box<dyn std::error::Error>> {
@@ -73,11 +73,6 @@ fn fixed_mul(a: i64, b: i64) -> i64 {
     (prod >> SCALE_BITS) as i64
 }
 
-#[inline(always)]
-fn clamp_fx(x: i64, lo: i64, hi: i64) -> i64 {
-    if x < lo { lo } else if x > hi { hi } else { x }
-}
-
 // ------------------ Fixed-model math primitives ------------------
 fn multiple_regression_fixed_accumulate(features_fx: &[i64], weights_fx: &[i64], b_fx: i64) -> i64 {
     let mut acc: i64 = 0;
@@ -95,23 +90,109 @@ fn polynomial_fixed_horner(x_fx: i64, coeffs_fx: &[i64]) -> i64 {
     acc
 }
 
-// Cubic sigmoid approximation in fixed domain
-fn sigmoid_fixed_approx(z_fx: i64) -> i64 {
-    const A1_F: f32 = 0.1963;
-    const A3_F: f32 = 0.004375;
-    let a1_fx = f32_to_fixed(A1_F);
-    let a3_fx = f32_to_fixed(A3_F);
-    let half_fx = f32_to_fixed(0.5);
+// ------------------ Fixed-point transcendental functions ------------------
+// Error bounds below were measured against f64 references by sweeping every Q16
+// input in the stated range (softmax: 200k random vectors of 2..10 logits in [-20, 20]);
+// the tests at the end of this file repeat the sweeps.
+
+// exp(x) by range reduction x = k*ln2 + r, r in [0, ln2), with a degree-9 Taylor
+// polynomial for exp(r) evaluated in Q32 and shifted back by k.
+// Inputs below -12 return 0, inputs above 20 saturate at exp(20).
+// Max abs error 7.7e-6 for x < 0, max relative error 7.6e-6 for x >= 0 (x in [-12, 20]).
+const EXP_MIN_FX: i64 = -12 * SCALE;
+const EXP_MAX_FX: i64 = 20 * SCALE;
+const LN2_Q32: i64 = 2_977_044_472;
+const EXP_TAYLOR_Q32: [i64; 10] = [
+    4_294_967_296, 4_294_967_296, 2_147_483_648, 715_827_883, 178_956_971,
+    35_791_394, 5_965_232, 852_176, 106_522, 11_836,
+];
+
+fn exp_fixed(x_fx: i64) -> i64 {
+    if x_fx < EXP_MIN_FX { return 0; }
+    let x_fx = if x_fx > EXP_MAX_FX { EXP_MAX_FX } else { x_fx };
+    let x_q32 = x_fx << 16;
+    let k = x_q32.div_euclid(LN2_Q32);
+    let r_q32 = x_q32 - k * LN2_Q32;
+    let mut p_q32: i64 = 0;
+    for &c in EXP_TAYLOR_Q32.iter().rev() {
+        p_q32 = (((p_q32 as i128) * (r_q32 as i128)) >> 32) as i64 + c;
+    }
+    let shift = 16 - k;
+    if shift <= 0 {
+        p_q32 << (-shift)
+    } else {
+        (p_q32 + (1 << (shift - 1))) >> shift
+    }
+}
+
+// sigmoid(z) from a 1/16-step lookup table over [0, 12] with linear interpolation,
+// using sigmoid(-z) = 1 - sigmoid(z); |z| >= 12 returns exactly 0 or 1.
+// Max abs error 5.8e-5 over z in [-20, 20].
+const SIGMOID_LUT_STEP_BITS: i32 = 12; // 1/16 in Q16
+const SIGMOID_LUT: [i64; 193] = [
+    32768, 33792, 34813, 35831, 36843, 37847, 38841, 39824,
+    40793, 41748, 42687, 43608, 44511, 45393, 46254, 47094,
+    47911, 48704, 49474, 50220, 50941, 51638, 52310, 52957,
+    53581, 54179, 54754, 55306, 55834, 56339, 56822, 57284,
+    57724, 58144, 58544, 58925, 59287, 59632, 59959, 60270,
+    60565, 60844, 61109, 61360, 61598, 61823, 62036, 62238,
+    62428, 62608, 62778, 62938, 63090, 63233, 63368, 63495,
+    63615, 63728, 63835, 63935, 64030, 64119, 64203, 64283,
+    64357, 64427, 64494, 64556, 64614, 64669, 64721, 64770,
+    64816, 64859, 64900, 64938, 64974, 65008, 65039, 65069,
+    65097, 65124, 65149, 65172, 65194, 65215, 65234, 65252,
+    65269, 65285, 65300, 65315, 65328, 65341, 65352, 65364,
+    65374, 65384, 65393, 65402, 65410, 65417, 65425, 65431,
+    65438, 65444, 65449, 65454, 65459, 65464, 65468, 65472,
+    65476, 65480, 65483, 65486, 65489, 65492, 65495, 65497,
+    65500, 65502, 65504, 65506, 65508, 65509, 65511, 65513,
+    65514, 65515, 65517, 65518, 65519, 65520, 65521, 65522,
+    65523, 65523, 65524, 65525, 65526, 65526, 65527, 65527,
+    65528, 65528, 65529, 65529, 65530, 65530, 65530, 65531,
+    65531, 65531, 65532, 65532, 65532, 65532, 65533, 65533,
+    65533, 65533, 65533, 65534, 65534, 65534, 65534, 65534,
+    65534, 65534, 65534, 65535, 65535, 65535, 65535, 65535,
+    65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535,
+    65535, 65535, 65535, 65535, 65535, 65536, 65536, 65536,
+    65536,
+];
+
+fn sigmoid_fixed(z_fx: i64) -> i64 {
+    let mag = z_fx.abs();
+    let idx = (mag >> SIGMOID_LUT_STEP_BITS) as usize;
+    let y_fx = if idx + 1 >= SIGMOID_LUT.len() {
+        SCALE
+    } else {
+        let frac = mag & ((1 << SIGMOID_LUT_STEP_BITS) - 1);
+        let lo = SIGMOID_LUT[idx];
+        let hi = SIGMOID_LUT[idx + 1];
+        lo + (((hi - lo) * frac + (1 << (SIGMOID_LUT_STEP_BITS - 1))) >> SIGMOID_LUT_STEP_BITS)
+    };
+    if z_fx < 0 { SCALE - y_fx } else { y_fx }
+}
 
-    let z2 = fixed_mul(z_fx, z_fx);
-    let z3 = fixed_mul(z2, z_fx);
+// tanh(x) = 2*sigmoid(2x) - 1, so it inherits the sigmoid table.
+// Max abs error 1.2e-4 for x in [-20, 20].
+fn tanh_fixed(x_fx: i64) -> i64 {
+    2 * sigmoid_fixed(2 * x_fx) - SCALE
+}
 
-    let term1 = fixed_mul(a1_fx, z_fx);
-    let term3 = fixed_mul(a3_fx, z3);
+// softmax over Q16 logits: subtract the max, exp_fixed each term, normalise with
+// a rounded division. Outputs are Q16 probabilities.
+// Max abs error 3.6e-5 per probability.
+fn softmax_fixed(logits_fx: &[i64]) -> Vec<i64> {
+    let max_fx = logits_fx.iter().copied().max().unwrap_or(0);
+    let exps: Vec<i64> = logits_fx.iter().map(|&z| exp_fixed(z - max_fx)).collect();
+    let sum: i64 = exps.iter().sum();
+    exps.iter().map(|&e| ((e << SCALE_BITS) + sum / 2) / sum).collect()
+}
 
-    let mut y_fx = half_fx + term1 - term3;
-    y_fx = clamp_fx(y_fx, 0, SCALE);
-    y_fx
+fn softmax_regression_fixed(features_fx: &[i64], weights_fx: &[i64], biases_fx: &[i64]) -> Vec<i64> {
+    let d = features_fx.len();
+    let logits_fx: Vec<i64> = biases_fx.iter().enumerate()
+        .map(|(k, &b_fx)| multiple_regression_fixed_accumulate(features_fx, &weights_fx[k * d..(k + 1) * d], b_fx))
+        .collect();
+    softmax_fixed(&logits_fx)
 }
 
 // ------------------ Float models ------------------
@@ -134,15 +215,188 @@ fn logistic_regression_f(xs: &[f32], weights: &[f32], b: f32) -> f32 {
     1.0 / (1.0 + (-z).exp())
 }
 
+fn softmax_f(logits: &[f32]) -> Vec<f32> {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits.iter().map(|&z| (z - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    exps.iter().map(|&e| e / sum).collect()
+}
+
+// Multinomial logistic regression: weights is K x D row-major, one bias per class.
+fn softmax_regression_f(xs: &[f32], weights: &[f32], biases: &[f32]) -> Vec<f32> {
+    let d = xs.len();
+    let logits: Vec<f32> = biases.iter().enumerate()
+        .map(|(k, &b)| multiple_regression_f(xs, &weights[k * d..(k + 1) * d], b))
+        .collect();
+    softmax_f(&logits)
+}
+
+// ------------------ MLP (dense layers) ------------------
+// Activation codes sent by the host for each layer; 0 is identity.
+const ACT_RELU: u32 = 1;
+const ACT_SIGMOID: u32 = 2;
+const ACT_TANH: u32 = 3;
+
+// Head codes: linear returns the last layer as is, softmax normalises it.
+const HEAD_SOFTMAX: u32 = 1;
+
+// weights is out_dim x in_dim row-major, one bias per output.
+struct DenseLayer<T> {
+    activation: u32,
+    weights: Vec<T>,
+    biases: Vec<T>,
+}
+
+fn read_dense_layers(num_features: usize) -> Vec<DenseLayer<f32>> {
+    let num_layers: u32 = env::read();
+    let mut layers = Vec::with_capacity(num_layers as usize);
+    let mut in_dim = num_features;
+    for i in 0..num_layers {
+        let activation: u32 = env::read();
+        let weights: Vec<f32> = env::read();
+        let biases: Vec<f32> = env::read();
+        assert!(activation <= ACT_TANH, "Unknown activation {} in layer {}", activation, i);
+        assert!(
+            !biases.is_empty() && weights.len() == biases.len() * in_dim,
+            "Layer {} weights must be out_dim x in_dim", i
+        );
+        in_dim = biases.len();
+        layers.push(DenseLayer { activation, weights, biases });
+    }
+    assert!(!layers.is_empty(), "MLP needs at least one layer");
+    layers
+}
+
+fn dense_layer_to_fixed(layer: &DenseLayer<f32>) -> DenseLayer<i64> {
+    DenseLayer {
+        activation: layer.activation,
+        weights: layer.weights.iter().map(|&w| f32_to_fixed(w)).collect(),
+        biases: layer.biases.iter().map(|&b| f32_to_fixed(b)).collect(),
+    }
+}
+
+fn activate_f(z: f32, activation: u32) -> f32 {
+    match activation {
+        ACT_RELU => z.max(0.0),
+        ACT_SIGMOID => 1.0 / (1.0 + (-z).exp()),
+        ACT_TANH => z.tanh(),
+        _ => z,
+    }
+}
+
+fn activate_fixed(z_fx: i64, activation: u32) -> i64 {
+    match activation {
+        ACT_RELU => z_fx.max(0),
+        ACT_SIGMOID => sigmoid_fixed(z_fx),
+        ACT_TANH => tanh_fixed(z_fx),
+        _ => z_fx,
+    }
+}
+
+fn dense_f(x: &[f32], layer: &DenseLayer<f32>) -> Vec<f32> {
+    let d = x.len();
+    layer.biases.iter().enumerate()
+        .map(|(j, &b)| activate_f(multiple_regression_f(x, &layer.weights[j * d..(j + 1) * d], b), layer.activation))
+        .collect()
+}
+
+fn dense_fixed(x_fx: &[i64], layer: &DenseLayer<i64>) -> Vec<i64> {
+    let d = x_fx.len();
+    layer.biases.iter().enumerate()
+        .map(|(j, &b_fx)| {
+            let z_fx = multiple_regression_fixed_accumulate(x_fx, &layer.weights[j * d..(j + 1) * d], b_fx);
+            activate_fixed(z_fx, layer.activation)
+        })
+        .collect()
+}
+
+// Forward pass for one sample. layers_fx is only used (and only filled) in
+// fixed mode. cycles[i] accumulates the cycles spent in layer i.
+fn mlp_predict(
+    layers: &[DenseLayer<f32>],
+    layers_fx: &[DenseLayer<i64>],
+    head: u32,
+    use_opt: bool,
+    x: &[f32],
+    cycles: &mut [u64],
+) -> Vec<f64> {
+    if use_opt {
+        let mut h_fx: Vec<i64> = x.iter().map(|&v| f32_to_fixed(v)).collect();
+        for (i, layer) in layers_fx.iter().enumerate() {
+            let start = env::cycle_count();
+            h_fx = dense_fixed(&h_fx, layer);
+            cycles[i] += env::cycle_count() - start;
+        }
+        if head == HEAD_SOFTMAX { h_fx = softmax_fixed(&h_fx); }
+        h_fx.into_iter().map(|v| fixed_to_f32(v) as f64).collect()
+    } else {
+        let mut h: Vec<f32> = x.to_vec();
+        for (i, layer) in layers.iter().enumerate() {
+            let start = env::cycle_count();
+            h = dense_f(&h, layer);
+            cycles[i] += env::cycle_count() - start;
+        }
+        if head == HEAD_SOFTMAX { h = softmax_f(&h); }
+        h.into_iter().map(|v| v as f64).collect()
+    }
+}
+
+fn log_layer_cycles(layers: &[DenseLayer<f32>], cycles: &[u64], samples: usize) {
+    for (i, (layer, total)) in layers.iter().zip(cycles.iter()).enumerate() {
+        let out_dim = layer.biases.len();
+        env::log(&format!(
+            "[guest] layer {} ({} -> {}, activation {}): {} cycles, {} per sample",
+            i, layer.weights.len() / out_dim, out_dim, layer.activation, total, total / samples.max(1) as u64
+        ));
+    }
+}
+
 // ------------------ Decision Tree Structures ------------------
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TreeNode {
+    #[serde(default)]
+    pub tree_id: usize,
     pub id: usize,
     pub feature: Option<usize>,
     pub threshold: Option<f64>,
     pub left: Option<usize>,
     pub right: Option<usize>,
     pub value: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub mode: SplitMode,
+    #[serde(default)]
+    pub missing_tracks_true: bool,
+}
+
+// ONNX split modes. The comparison is `x <mode> threshold`; when it holds the
+// walk takes the true branch (`left`). LEAF marks leaf nodes in ONNX exports.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SplitMode {
+    #[default]
+    BranchLeq,
+    BranchLt,
+    BranchGte,
+    BranchGt,
+    BranchEq,
+    BranchNeq,
+    Leaf,
+}
+
+// Same rule as onnxruntime: a NaN feature goes to the true branch when
+// missing_tracks_true is set, otherwise it falls through the comparison (which
+// is false for NaN except under BRANCH_NEQ).
+fn takes_true_branch(mode: SplitMode, missing_tracks_true: bool, x: f64, thr: f64) -> bool {
+    let cond = match mode {
+        SplitMode::BranchLeq => x <= thr,
+        SplitMode::BranchLt => x < thr,
+        SplitMode::BranchGte => x >= thr,
+        SplitMode::BranchGt => x > thr,
+        SplitMode::BranchEq => x == thr,
+        SplitMode::BranchNeq => x != thr,
+        SplitMode::Leaf => panic!("Leaf node has no split"),
+    };
+    cond || (missing_tracks_true && x.is_nan())
 }
 
 #[derive(Debug)]
@@ -151,25 +405,135 @@ pub struct Sample {
     pub expected: u32,
 }
 
-fn get_dataset_tree() -> Vec<Sample> {
-    vec![
-        Sample { features: vec![5.1, 3.5, 1.4, 0.2], expected: 0 },
-        Sample { features: vec![4.9, 3.0, 1.4, 0.2], expected: 0 },
-        Sample { features: vec![6.0, 2.2, 4.0, 1.0], expected: 1 },
-        Sample { features: vec![5.9, 3.0, 5.1, 1.8], expected: 2 },
-        Sample { features: vec![6.5, 3.0, 5.2, 2.0], expected: 2 },
-    ]
+// Ensemble document (random forest, gradient boosting). Node ids are per tree and
+// every tree's root is id 0. Scores follow ONNX TreeEnsembleClassifier/Regressor:
+// leaf vectors are combined across trees with `aggregate`, `base_values` is added,
+// then `post_transform` is applied.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Average,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PostTransform {
+    #[default]
+    None,
+    Softmax,
+    Logistic,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeEnsemble {
+    #[serde(default)]
+    pub aggregate: Aggregate,
+    #[serde(default)]
+    pub post_transform: PostTransform,
+    #[serde(default)]
+    pub base_values: Vec<f64>,
+    pub nodes: Vec<TreeNode>,
+}
+
+// One id -> position map per tree_id.
+fn build_tree_indexes(nodes: &[TreeNode]) -> Vec<Vec<usize>> {
+    let num_trees = nodes.iter().map(|n| n.tree_id + 1).max().unwrap_or(0);
+    let mut max_ids = vec![0usize; num_trees];
+    for n in nodes.iter() { if n.id > max_ids[n.tree_id] { max_ids[n.tree_id] = n.id; } }
+    let mut maps: Vec<Vec<usize>> = max_ids.iter().map(|&m| vec![usize::MAX; m + 1]).collect();
+    for (idx, n) in nodes.iter().enumerate() { maps[n.tree_id][n.id] = idx; }
+    maps
+}
+
+// A plain node list (single tree) is read as an ensemble of one.
+fn parse_tree_document(tree_json: &str) -> TreeEnsemble {
+    if let Ok(ensemble) = serde_json::from_str::<TreeEnsemble>(tree_json) {
+        return ensemble;
+    }
+    let nodes: Vec<TreeNode> = match serde_json::from_str(tree_json) {
+        Ok(t) => t,
+        Err(_) => panic!("Failed to parse tree JSON in guest"),
+    };
+    TreeEnsemble {
+        aggregate: Aggregate::Sum,
+        post_transform: PostTransform::None,
+        base_values: Vec::new(),
+        nodes,
+    }
+}
+
+// Classifier leaves hold one class-score row in value[0].
+fn leaf_class_scores(node: &TreeNode) -> Vec<f64> {
+    node.value[0].clone()
+}
+
+// Regressor leaves hold one value per target, either as [[t0, t1, ..]] (ONNX
+// export) or [[t0], [t1], ..] (sklearn tree_.value).
+fn leaf_targets(node: &TreeNode) -> Vec<f64> {
+    node.value.concat()
 }
 
-fn build_id_index(nodes: &Vec<TreeNode>) -> Vec<usize> {
-    let mut max_id = 0usize;
-    for n in nodes.iter() { if n.id > max_id { max_id = n.id; } }
-    let mut map = vec![usize::MAX; max_id + 1];
-    for (idx, n) in nodes.iter().enumerate() { map[n.id] = idx; }
-    map
+fn predict_ensemble(
+    ensemble: &TreeEnsemble,
+    indexes: &[Vec<usize>],
+    x: &[f64],
+    leaf_values: fn(&TreeNode) -> Vec<f64>,
+) -> Vec<f64> {
+    let leaves = indexes.iter().map(|id_index| leaf_values(find_leaf(&ensemble.nodes, id_index, x)));
+    combine_leaves(leaves, ensemble.aggregate, &ensemble.base_values, ensemble.post_transform)
+}
+
+// Per-tree leaf vectors -> scores: aggregate across trees, add base_values,
+// then apply post_transform.
+fn combine_leaves(
+    leaves: impl Iterator<Item = Vec<f64>>,
+    aggregate: Aggregate,
+    base_values: &[f64],
+    post_transform: PostTransform,
+) -> Vec<f64> {
+    let mut scores: Vec<f64> = Vec::new();
+    let mut num_trees = 0usize;
+    for leaf in leaves {
+        num_trees += 1;
+        if num_trees == 1 {
+            scores = leaf;
+            continue;
+        }
+        assert!(leaf.len() == scores.len(), "Leaf value width differs between trees");
+        for (s, v) in scores.iter_mut().zip(leaf.iter()) {
+            *s = match aggregate {
+                Aggregate::Sum | Aggregate::Average => *s + v,
+                Aggregate::Min => s.min(*v),
+                Aggregate::Max => s.max(*v),
+            };
+        }
+    }
+    if aggregate == Aggregate::Average && num_trees > 0 {
+        let n = num_trees as f64;
+        for s in scores.iter_mut() { *s /= n; }
+    }
+    if !base_values.is_empty() {
+        assert!(base_values.len() == scores.len(), "base_values width differs from leaf values");
+        for (s, b) in scores.iter_mut().zip(base_values.iter()) { *s += b; }
+    }
+
+    match post_transform {
+        PostTransform::None => scores,
+        PostTransform::Softmax => {
+            let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let exps: Vec<f64> = scores.iter().map(|&z| (z - max).exp()).collect();
+            let sum: f64 = exps.iter().sum();
+            exps.iter().map(|&e| e / sum).collect()
+        }
+        PostTransform::Logistic => scores.iter().map(|&z| 1.0 / (1.0 + (-z).exp())).collect(),
+    }
 }
 
-fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec<f64> {
+fn find_leaf<'a>(nodes: &'a [TreeNode], id_index: &[usize], x: &[f64]) -> &'a TreeNode {
     let mut current_id: usize = 0;
     loop {
         if current_id >= id_index.len() { panic!("Unknown node id"); }
@@ -177,15 +541,15 @@ fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec
         if idx == usize::MAX { panic!("Unmapped node id"); }
         let node = &nodes[idx];
 
-        if node.feature.is_none() {
-            return node.value[0].clone();
+        if node.feature.is_none() || node.mode == SplitMode::Leaf {
+            return node;
         }
 
         let feat = node.feature.unwrap();
         let thr = node.threshold.unwrap();
         let xf = x[feat];
 
-        if xf <= thr {
+        if takes_true_branch(node.mode, node.missing_tracks_true, xf, thr) {
             current_id = node.left.expect("Missing left child");
         } else {
             current_id = node.right.expect("Missing right child");
@@ -193,7 +557,359 @@ fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec
     }
 }
 
-// ------------------ Original dataset ------------------
+// ------------------ Flattened tree input ------------------
+// By default the host (verse-common/src/flat_tree.rs) resolves node ids and leaf
+// values ahead of time and sends the trees as u32 words, which are walked in
+// place; see that file for the layout. With VERSE_TREE_FORMAT=json the host
+// sends the tree JSON instead and it is parsed here as before.
+//
+// In optimized mode the host sends the quantized variant: each node also holds
+// thr_q = floor(threshold * 2^scale_bits) and features are floored the same
+// way, so the walk compares i32s. Integer and f64 comparisons can only disagree
+// when x_q == thr_q; those ties are checked against the f64 threshold and the
+// samples whose routing changed are committed with the predictions.
+const TREE_FORMAT_FLAT: u32 = 1;
+const TREE_FORMAT_QUANTIZED: u32 = 2;
+const FLAT_MAGIC: u32 = 0x5654_4631; // "VTF1"
+const FLAT_HEADER_WORDS: usize = 7;
+const FLAT_NODE_WORDS: usize = 6;
+const FLAT_LEAF_FEATURE: u32 = u32::MAX;
+const QUANT_MAGIC: u32 = 0x5654_5131; // "VTQ1"
+const QUANT_HEADER_WORDS: usize = 8;
+const QUANT_NODE_WORDS: usize = 7;
+
+pub struct FlatTrees {
+    words: Vec<u32>,
+    aggregate: Aggregate,
+    post_transform: PostTransform,
+    base_values: Vec<f64>,
+    num_trees: usize,
+    leaf_width: usize,
+    scale_bits: Option<u32>,
+    roots_at: usize,
+    node_len: usize,
+    nodes_at: usize,
+    leaves_at: usize,
+}
+
+// Samples (dataset indexes) whose quantized routing differs from f64 routing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoutingReport {
+    pub scale_bits: u32,
+    pub changed_samples: Vec<u32>,
+}
+
+fn word_f64(words: &[u32], at: usize) -> f64 {
+    f64::from_bits(words[at] as u64 | (words[at + 1] as u64) << 32)
+}
+
+fn split_mode_from_code(code: u32) -> SplitMode {
+    match code {
+        0 => SplitMode::BranchLeq,
+        1 => SplitMode::BranchLt,
+        2 => SplitMode::BranchGte,
+        3 => SplitMode::BranchGt,
+        4 => SplitMode::BranchEq,
+        5 => SplitMode::BranchNeq,
+        6 => SplitMode::Leaf,
+        other => panic!("Unknown split mode code {}", other),
+    }
+}
+
+fn read_flat_trees() -> FlatTrees {
+    let len: u32 = env::read();
+    let mut words = vec![0u32; len as usize];
+    env::read_slice(&mut words);
+    assert!(words.len() >= FLAT_HEADER_WORDS, "Not a flattened tree");
+    let quantized = match words[0] {
+        FLAT_MAGIC => false,
+        QUANT_MAGIC => true,
+        _ => panic!("Not a flattened tree"),
+    };
+    let (header_len, node_len) = if quantized {
+        (QUANT_HEADER_WORDS, QUANT_NODE_WORDS)
+    } else {
+        (FLAT_HEADER_WORDS, FLAT_NODE_WORDS)
+    };
+    assert!(words.len() >= header_len, "Flattened tree is truncated");
+    let scale_bits = if quantized { Some(words[7]) } else { None };
+
+    let aggregate = match words[1] {
+        0 => Aggregate::Sum,
+        1 => Aggregate::Average,
+        2 => Aggregate::Min,
+        3 => Aggregate::Max,
+        other => panic!("Unknown aggregate code {}", other),
+    };
+    let post_transform = match words[2] {
+        0 => PostTransform::None,
+        1 => PostTransform::Softmax,
+        2 => PostTransform::Logistic,
+        other => panic!("Unknown post_transform code {}", other),
+    };
+    let num_trees = words[3] as usize;
+    let num_nodes = words[4] as usize;
+    let leaf_width = words[5] as usize;
+    let num_base_values = words[6] as usize;
+
+    let roots_at = header_len;
+    let base_at = roots_at + num_trees;
+    let nodes_at = base_at + 2 * num_base_values;
+    let leaves_at = nodes_at + num_nodes * node_len;
+    assert!(leaves_at <= words.len(), "Flattened tree is truncated");
+    let base_values = (0..num_base_values).map(|i| word_f64(&words, base_at + 2 * i)).collect();
+
+    FlatTrees {
+        words,
+        aggregate,
+        post_transform,
+        base_values,
+        num_trees,
+        leaf_width,
+        scale_bits,
+        roots_at,
+        node_len,
+        nodes_at,
+        leaves_at,
+    }
+}
+
+// Integer form of takes_true_branch for a present feature.
+fn takes_true_branch_q(mode: SplitMode, x_q: i32, thr_q: i32) -> bool {
+    match mode {
+        SplitMode::BranchLeq => x_q <= thr_q,
+        SplitMode::BranchLt => x_q < thr_q,
+        SplitMode::BranchGte => x_q >= thr_q,
+        SplitMode::BranchGt => x_q > thr_q,
+        SplitMode::BranchEq => x_q == thr_q,
+        SplitMode::BranchNeq => x_q != thr_q,
+        SplitMode::Leaf => panic!("Leaf node has no split"),
+    }
+}
+
+// floor(x * 2^scale_bits), saturating; None for a missing (NaN) feature.
+fn quantize_features(x: &[f64], scale_bits: u32) -> Vec<Option<i32>> {
+    let scale = (1u64 << scale_bits) as f64;
+    x.iter()
+        .map(|&v| if v.is_nan() { None } else { Some((v * scale).floor() as i32) })
+        .collect()
+}
+
+impl FlatTrees {
+    // Leaf number reached by tree `t`.
+    fn find_leaf(&self, t: usize, x: &[f64]) -> usize {
+        let w = &self.words;
+        let mut pos = w[self.roots_at + t] as usize;
+        loop {
+            let node = self.nodes_at + pos * self.node_len;
+            let feature = w[node];
+            if feature == FLAT_LEAF_FEATURE {
+                return w[node + 4] as usize;
+            }
+            let flags = w[node + 1];
+            let mode = split_mode_from_code(flags & 0xff);
+            let thr = word_f64(w, node + 2);
+            pos = if takes_true_branch(mode, flags >> 8 != 0, x[feature as usize], thr) {
+                w[node + 4] as usize
+            } else {
+                w[node + 5] as usize
+            };
+        }
+    }
+
+    // Leaf number reached by tree `t` comparing quantized features, and whether
+    // any split on the way went differently than the f64 comparison would.
+    fn find_leaf_quantized(&self, t: usize, x: &[f64], x_q: &[Option<i32>]) -> (usize, bool) {
+        let w = &self.words;
+        let mut pos = w[self.roots_at + t] as usize;
+        let mut changed = false;
+        loop {
+            let node = self.nodes_at + pos * self.node_len;
+            let feature = w[node];
+            if feature == FLAT_LEAF_FEATURE {
+                return (w[node + 4] as usize, changed);
+            }
+            let flags = w[node + 1];
+            let mode = split_mode_from_code(flags & 0xff);
+            let missing_tracks_true = flags >> 8 != 0;
+            let go_true = match x_q[feature as usize] {
+                None => missing_tracks_true || mode == SplitMode::BranchNeq,
+                Some(q) => {
+                    let thr_q = w[node + 6] as i32;
+                    let go_true = takes_true_branch_q(mode, q, thr_q);
+                    if q == thr_q {
+                        let thr = word_f64(w, node + 2);
+                        changed |= go_true != takes_true_branch(mode, missing_tracks_true, x[feature as usize], thr);
+                    }
+                    go_true
+                }
+            };
+            pos = if go_true { w[node + 4] as usize } else { w[node + 5] as usize };
+        }
+    }
+
+    fn leaf_values(&self, leaf: usize) -> Vec<f64> {
+        let at = self.leaves_at + 2 * leaf * self.leaf_width;
+        (0..self.leaf_width).map(|i| word_f64(&self.words, at + 2 * i)).collect()
+    }
+
+    fn predict(&self, x: &[f64]) -> (Vec<f64>, bool) {
+        let Some(scale_bits) = self.scale_bits else {
+            let leaves = (0..self.num_trees).map(|t| self.leaf_values(self.find_leaf(t, x)));
+            return (combine_leaves(leaves, self.aggregate, &self.base_values, self.post_transform), false);
+        };
+        let x_q = quantize_features(x, scale_bits);
+        let mut changed = false;
+        let leaves = (0..self.num_trees).map(|t| {
+            let (leaf, leaf_changed) = self.find_leaf_quantized(t, x, &x_q);
+            changed |= leaf_changed;
+            self.leaf_values(leaf)
+        });
+        let scores = combine_leaves(leaves, self.aggregate, &self.base_values, self.post_transform);
+        (scores, changed)
+    }
+}
+
+// Tree input in either format. JSON leaves are read with `leaf_values`; the
+// flattened format already holds the values the model kind reads.
+pub enum TreeInput {
+    Json(TreeEnsemble, Vec<Vec<usize>>, fn(&TreeNode) -> Vec<f64>),
+    Flat(FlatTrees),
+}
+
+fn read_tree_input(leaf_values: fn(&TreeNode) -> Vec<f64>) -> TreeInput {
+    let _tree_path: String = env::read();
+    let tree_format: u32 = env::read();
+    if tree_format == TREE_FORMAT_FLAT || tree_format == TREE_FORMAT_QUANTIZED {
+        let trees = read_flat_trees();
+        assert!(trees.num_trees > 0, "Tree model has no trees");
+        return TreeInput::Flat(trees);
+    }
+    let tree_json: String = env::read();
+    let ensemble = parse_tree_document(&tree_json);
+    let indexes = build_tree_indexes(&ensemble.nodes);
+    assert!(!indexes.is_empty(), "Tree model has no trees");
+    TreeInput::Json(ensemble, indexes, leaf_values)
+}
+
+impl TreeInput {
+    // Scores, and whether quantized routing differed from f64 routing (only
+    // ever set for the quantized format).
+    fn predict(&self, x: &[f64]) -> (Vec<f64>, bool) {
+        match self {
+            TreeInput::Json(ensemble, indexes, leaf_values) => {
+                (predict_ensemble(ensemble, indexes, x, *leaf_values), false)
+            }
+            TreeInput::Flat(trees) => trees.predict(x),
+        }
+    }
+
+    // onnxruntime expands a single binary score s to [1 - s, s], except under
+    // post_transform NONE when some leaf weight is negative: then [-s, s].
+    fn signed_binary_scores(&self) -> bool {
+        match self {
+            TreeInput::Json(ensemble, _, leaf_values) => {
+                ensemble.post_transform == PostTransform::None
+                    && ensemble
+                        .nodes
+                        .iter()
+                        .filter(|n| n.feature.is_none() || n.mode == SplitMode::Leaf)
+                        .any(|n| leaf_values(n).iter().any(|&v| v < 0.0))
+            }
+            TreeInput::Flat(trees) => {
+                trees.post_transform == PostTransform::None
+                    && (trees.leaves_at..trees.words.len()).step_by(2).any(|at| word_f64(&trees.words, at) < 0.0)
+            }
+        }
+    }
+
+    // Commits the journal, followed by the routing report for quantized trees.
+    fn commit<T: Serialize>(&self, journal: &T, changed_samples: Vec<u32>) {
+        match self {
+            TreeInput::Flat(FlatTrees { scale_bits: Some(scale_bits), .. }) => {
+                env::commit(&(journal, RoutingReport { scale_bits: *scale_bits, changed_samples }));
+            }
+            _ => env::commit(journal),
+        }
+    }
+}
+
+// ------------------ Regression metrics ------------------
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegressionMetrics {
+    pub mse: f64,
+    pub mae: f64,
+    pub r2: f64,
+}
+
+// (predicted, true) pairs. r2 is 0 when the targets have no variance.
+fn regression_metrics(pairs: &[(f64, f64)]) -> RegressionMetrics {
+    let n = pairs.len().max(1) as f64;
+    let mean = pairs.iter().map(|(_, t)| t).sum::<f64>() / n;
+    let mut sse = 0.0;
+    let mut sae = 0.0;
+    let mut sst = 0.0;
+    for &(p, t) in pairs.iter() {
+        sse += (p - t) * (p - t);
+        sae += (p - t).abs();
+        sst += (t - mean) * (t - mean);
+    }
+    RegressionMetrics {
+        mse: sse / n,
+        mae: sae / n,
+        r2: if sst > 0.0 { 1.0 - sse / sst } else { 0.0 },
+    }
+}
+
+// ------------------ Dataset width ------------------
+// The host sends num_features from the model spec; every row has to match it.
+fn check_width(width: usize, num_features: usize) {
+    assert!(
+        width == num_features,
+        "Dataset row has {} features but the model expects {}",
+        width, num_features
+    );
+}
+
+// ------------------ Feature scaling ------------------
+// Standard / min-max scaling from the model's preprocessing spec, one
+// (offset, factor) per feature: x' = (x - offset) * factor. The host sends an
+// empty list when the model has no scaling; categorical encoding already
+// happened when the verifier's dataset was embedded.
+fn read_scaling(num_features: usize) -> Vec<(f32, f32)> {
+    let scaling: Vec<(f32, f32)> = env::read();
+    assert!(
+        scaling.is_empty() || scaling.len() == num_features,
+        "Scaling has {} entries but the model expects {} features",
+        scaling.len(), num_features
+    );
+    scaling
+}
+
+fn scale_samples(mut dataset: Vec<Sample>, scaling: &[(f32, f32)]) -> Vec<Sample> {
+    for sample in dataset.iter_mut() {
+        for (x, &(offset, factor)) in sample.features.iter_mut().zip(scaling.iter()) {
+            *x = (*x - offset as f64) * factor as f64;
+        }
+    }
+    dataset
+}
+
+fn scale_rows(mut dataset: Vec<(Vec<f32>, f32)>, scaling: &[(f32, f32)]) -> Vec<(Vec<f32>, f32)> {
+    for (features, _) in dataset.iter_mut() {
+        for (x, &(offset, factor)) in features.iter_mut().zip(scaling.iter()) {
+            *x = (*x - offset) * factor;
+        }
+    }
+    dataset
+}
+
+// ------------------ Embedded datasets ------------------
+// `verse request` rewrites both functions below with the verifier's dataset:
+// a classification dataset goes into get_dataset_tree() with class indexes,
+// a regression dataset into get_dataset() with targets, and the other one is
+// left empty. Each model reads its own kind through classification_dataset()
+// or regression_dataset(), which fall back to the other function.
 fn get_dataset() -> Vec<(Vec<f32>, f32)> {
     vec![
         (vec![2.0, 2.0, 3.0], 14.0),
@@ -203,42 +919,233 @@ fn get_dataset() -> Vec<(Vec<f32>, f32)> {
     ]
 }
 
+fn get_dataset_tree() -> Vec<Sample> {
+    vec![
+        Sample { features: vec![5.1, 3.5, 1.4, 0.2], expected: 0 },
+        Sample { features: vec![4.9, 3.0, 1.4, 0.2], expected: 0 },
+        Sample { features: vec![6.0, 2.2, 4.0, 1.0], expected: 1 },
+        Sample { features: vec![5.9, 3.0, 5.1, 1.8], expected: 2 },
+        Sample { features: vec![6.5, 3.0, 5.2, 2.0], expected: 2 },
+    ]
+}
+
+// Samples with class indexes, for tree and classifier models.
+fn classification_dataset() -> Vec<Sample> {
+    let samples = get_dataset_tree();
+    if !samples.is_empty() {
+        return samples;
+    }
+    get_dataset()
+        .into_iter()
+        .map(|(features, y)| {
+            assert!(
+                y >= 0.0 && y.fract() == 0.0,
+                "Label {} is not a class index; embed the dataset with `verse request --task classification`",
+                y
+            );
+            Sample { features: features.iter().map(|&x| x as f64).collect(), expected: y as u32 }
+        })
+        .collect()
+}
+
+// Rows with numeric targets, for regression models (and logistic regression,
+// which scores 0/1 targets).
+fn regression_dataset() -> Vec<(Vec<f32>, f32)> {
+    let rows = get_dataset();
+    if !rows.is_empty() {
+        return rows;
+    }
+    get_dataset_tree()
+        .into_iter()
+        .map(|s| (s.features.iter().map(|&x| x as f32).collect(), s.expected as f32))
+        .collect()
+}
+
 
 // ------------------ Guest Entry ------------------
+#[cfg(not(test))]
 risc0_zkvm::guest::entry!(main);
 
 fn main() {
     let use_opt_flag: u32 = env::read(); // 0 = float, 1 = fixed
-    let model_type: u32 = env::read();   // 1..5
+    let model_type: u32 = env::read();   // 1..9
+    let num_features: u32 = env::read();
+    let d = num_features as usize;
+    let scaling = read_scaling(d);
 
-    // Decision tree (case 5) needs tree JSON instead of weights/bias
+    // Decision tree (case 5) needs a tree instead of weights/bias
     if model_type == 5 {
-        let _tree_path: String = env::read();
-        let tree_json: String = env::read();
-        let tree: Vec<TreeNode> = match serde_json::from_str(&tree_json) {
-            Ok(t) => t,
-            Err(_) => panic!("Failed to parse tree JSON in guest"),
-        };
-        let id_index = build_id_index(&tree);
-        let dataset = get_dataset_tree();
+        let tree = read_tree_input(leaf_class_scores);
+        let dataset = scale_samples(classification_dataset(), &scaling);
+        for sample in dataset.iter() {
+            check_width(sample.features.len(), d);
+        }
 
         let mut predictions = Vec::new();
+        let mut changed_samples = Vec::new();
+        for (i, sample) in dataset.iter().enumerate() {
+            let (pred, changed) = tree.predict(&sample.features);
+            if changed { changed_samples.push(i as u32); }
+            predictions.push((pred, sample.expected));
+        }
+
+        tree.commit(&predictions, changed_samples);
+        return;
+    }
+
+    // Tree ensemble (case 8): same inputs as case 5, usually an ensemble document
+    if model_type == 8 {
+        let ensemble = read_tree_input(leaf_class_scores);
+        let dataset = scale_samples(classification_dataset(), &scaling);
         for sample in dataset.iter() {
-            let pred = traverse_tree(&tree, &id_index, &sample.features);
+            check_width(sample.features.len(), d);
+        }
+
+        // a binary classifier with weights for one class only keeps one score
+        // per leaf; expand it to two like onnxruntime so it can be scored as
+        // two classes
+        let signed = ensemble.signed_binary_scores();
+        let mut predictions = Vec::new();
+        let mut changed_samples = Vec::new();
+        for (i, sample) in dataset.iter().enumerate() {
+            let (mut pred, changed) = ensemble.predict(&sample.features);
+            if changed { changed_samples.push(i as u32); }
+            if pred.len() == 1 {
+                pred = if signed { vec![-pred[0], pred[0]] } else { vec![1.0 - pred[0], pred[0]] };
+            }
             predictions.push((pred, sample.expected));
         }
 
+        ensemble.commit(&predictions, changed_samples);
+        return;
+    }
+
+    // Tree regressor (case 9): a single tree or an ensemble document whose leaves
+    // hold target values. Commits the per-sample outputs and metrics on target 0,
+    // the column the dataset labels.
+    if model_type == 9 {
+        let ensemble = read_tree_input(leaf_targets);
+        let dataset = scale_rows(regression_dataset(), &scaling);
+        assert!(!dataset.is_empty(), "Dataset loaded is empty");
+
+        let mut outputs: Vec<(Vec<f64>, f64)> = Vec::with_capacity(dataset.len());
+        let mut changed_samples = Vec::new();
+        for (i, (features, y_true)) in dataset.iter().enumerate() {
+            check_width(features.len(), d);
+            let x: Vec<f64> = features.iter().map(|&v| v as f64).collect();
+            let (pred, changed) = ensemble.predict(&x);
+            if changed { changed_samples.push(i as u32); }
+            assert!(!pred.is_empty(), "Tree regressor leaf has no values");
+            outputs.push((pred, *y_true as f64));
+        }
+        let pairs: Vec<(f64, f64)> = outputs.iter().map(|(p, t)| (p[0], *t)).collect();
+        let metrics = regression_metrics(&pairs);
+
+        ensemble.commit(&(outputs, metrics), changed_samples);
+        return;
+    }
+
+    // Softmax regression (case 6) needs a K x D weight matrix and K biases
+    if model_type == 6 {
+        let num_classes: u32 = env::read();
+        let weights: Vec<f32> = env::read();
+        let biases: Vec<f32> = env::read();
+        let k = num_classes as usize;
+        assert!(k >= 2, "Softmax regression needs at least 2 classes");
+        assert!(biases.len() == k, "Softmax regression expects one bias per class");
+        assert!(weights.len() == k * d, "Softmax regression weights must be num_classes x num_features");
+
+        let dataset = scale_samples(classification_dataset(), &scaling);
+        assert!(!dataset.is_empty(), "Dataset loaded is empty");
+        for sample in dataset.iter() {
+            check_width(sample.features.len(), d);
+        }
+
+        let mut predictions: Vec<(Vec<f64>, u32)> = Vec::with_capacity(dataset.len());
+        if use_opt_flag != 0 {
+            let weights_fx: Vec<i64> = weights.iter().map(|&w| f32_to_fixed(w)).collect();
+            let biases_fx: Vec<i64> = biases.iter().map(|&b| f32_to_fixed(b)).collect();
+            for sample in dataset.iter() {
+                let features_fx: Vec<i64> = sample.features.iter().map(|&x| f32_to_fixed(x as f32)).collect();
+                let probs_fx = softmax_regression_fixed(&features_fx, &weights_fx, &biases_fx);
+                let probs: Vec<f64> = probs_fx.into_iter().map(|p| fixed_to_f32(p) as f64).collect();
+                predictions.push((probs, sample.expected));
+            }
+        } else {
+            for sample in dataset.iter() {
+                let features: Vec<f32> = sample.features.iter().map(|&x| x as f32).collect();
+                let probs = softmax_regression_f(&features, &weights, &biases);
+                predictions.push((probs.into_iter().map(|p| p as f64).collect(), sample.expected));
+            }
+        }
+
         env::commit(&predictions);
         return;
     }
 
+    // MLP (case 7): dense layers, then a linear or softmax head. A softmax head
+    // or more than one output is scored as a classifier, a single linear output
+    // as a regressor.
+    if model_type == 7 {
+        let use_opt = use_opt_flag != 0;
+        let layers = read_dense_layers(d);
+        let head: u32 = env::read();
+        let layers_fx: Vec<DenseLayer<i64>> = if use_opt {
+            layers.iter().map(dense_layer_to_fixed).collect()
+        } else {
+            Vec::new()
+        };
+        let out_dim = layers[layers.len() - 1].biases.len();
+        let mut cycles = vec![0u64; layers.len()];
+
+        if head == HEAD_SOFTMAX || out_dim > 1 {
+            let dataset = scale_samples(classification_dataset(), &scaling);
+            assert!(!dataset.is_empty(), "Dataset loaded is empty");
+            let mut predictions: Vec<(Vec<f64>, u32)> = Vec::with_capacity(dataset.len());
+            for sample in dataset.iter() {
+                check_width(sample.features.len(), d);
+                let x: Vec<f32> = sample.features.iter().map(|&v| v as f32).collect();
+                let scores = mlp_predict(&layers, &layers_fx, head, use_opt, &x, &mut cycles);
+                predictions.push((scores, sample.expected));
+            }
+            log_layer_cycles(&layers, &cycles, dataset.len());
+            env::commit(&predictions);
+        } else {
+            let dataset = scale_rows(regression_dataset(), &scaling);
+            assert!(!dataset.is_empty(), "Dataset loaded is empty");
+            let mut out: Vec<(f32, f32)> = Vec::with_capacity(dataset.len());
+            for (features, y_true) in dataset.iter() {
+                check_width(features.len(), d);
+                let y = mlp_predict(&layers, &layers_fx, head, use_opt, features, &mut cycles);
+                out.push((y[0] as f32, *y_true));
+            }
+            log_layer_cycles(&layers, &cycles, dataset.len());
+            env::commit(&out);
+        }
+        return;
+    }
+
     // Other models (1–4)
     let weights: Vec<f32> = env::read();
     let b: f32 = env::read();
 
+    // Simple linear regression reads only features[0] and weights[0], so
+    // anything wider would silently ignore the rest.
+    if model_type == 1 {
+        assert!(d == 1 && weights.len() == 1,
+            "Model 1 expects 1 feature and 1 weight, got {} and {}", d, weights.len());
+    }
+    // Polynomial regression likewise reads only features[0].
+    if model_type == 3 {
+        assert!(d == 1, "Model 3 expects 1 feature, got {}", d);
+    }
+
     let use_opt = use_opt_flag != 0;
-    let dataset = get_dataset();
+    let dataset = scale_rows(regression_dataset(), &scaling);
     assert!(!dataset.is_empty(), "Dataset loaded is empty");
+    for (features, _) in dataset.iter() {
+        check_width(features.len(), d);
+    }
 
     if use_opt {
         let weights_fx: Vec<i64> = weights.iter().map(|&w| f32_to_fixed(w)).collect();
@@ -252,8 +1159,8 @@ fn main() {
                 2 => multiple_regression_fixed_accumulate(&features_fx, &weights_fx, b_fx),
                 3 => polynomial_fixed_horner(features_fx[0], &weights_fx),
                 4 => {
-                    let z_fx = multiple_regression_fixed_accumulate(&features_fx, &weights_fx, b_fx) - b_fx;
-                    sigmoid_fixed_approx(z_fx)
+                    let z_fx = multiple_regression_fixed_accumulate(&features_fx, &weights_fx, b_fx);
+                    sigmoid_fixed(z_fx)
                 }
                 _ => panic!("Unknown model type {}", model_type),
             };
@@ -283,6 +1190,93 @@ fn main() {
     }
 }
 
+// Host-side checks of the documented error bounds: `cargo test` in this
+// directory builds the guest natively (no zkVM entry point under test).
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(x_fx: i64) -> f64 {
+        x_fx as f64 / SCALE as f64
+    }
+
+    #[test]
+    fn exp_fixed_stays_within_its_bounds() {
+        let (mut max_abs, mut max_rel) = (0.0f64, 0.0f64);
+        for x_fx in EXP_MIN_FX..=EXP_MAX_FX {
+            let want = to_f64(x_fx).exp();
+            let err = to_f64(exp_fixed(x_fx)) - want;
+            if x_fx < 0 {
+                max_abs = max_abs.max(err.abs());
+            } else {
+                max_rel = max_rel.max((err / want).abs());
+            }
+        }
+        assert!(max_abs <= 7.7e-6, "max abs error {:e} for x < 0", max_abs);
+        assert!(max_rel <= 7.6e-6, "max relative error {:e} for x >= 0", max_rel);
+        assert_eq!(exp_fixed(EXP_MIN_FX - 1), 0);
+        assert_eq!(exp_fixed(EXP_MAX_FX + SCALE), exp_fixed(EXP_MAX_FX));
+    }
+
+    #[test]
+    fn sigmoid_and_tanh_fixed_stay_within_their_bounds() {
+        let (mut sigmoid_err, mut tanh_err) = (0.0f64, 0.0f64);
+        for z_fx in -20 * SCALE..=20 * SCALE {
+            let z = to_f64(z_fx);
+            sigmoid_err = sigmoid_err.max((to_f64(sigmoid_fixed(z_fx)) - 1.0 / (1.0 + (-z).exp())).abs());
+            tanh_err = tanh_err.max((to_f64(tanh_fixed(z_fx)) - z.tanh()).abs());
+        }
+        assert!(sigmoid_err <= 5.8e-5, "sigmoid max abs error {:e}", sigmoid_err);
+        assert!(tanh_err <= 1.2e-4, "tanh max abs error {:e}", tanh_err);
+        assert_eq!(sigmoid_fixed(12 * SCALE), SCALE);
+        assert_eq!(sigmoid_fixed(-12 * SCALE), 0);
+    }
+
+    #[test]
+    fn softmax_fixed_stays_within_its_bound() {
+        // xorshift64, so the sweep is the same on every run
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut max_err = 0.0f64;
+        for _ in 0..200_000 {
+            let n = 2 + (next() % 9) as usize;
+            let logits_fx: Vec<i64> = (0..n).map(|_| (next() % (40 * SCALE as u64 + 1)) as i64 - 20 * SCALE).collect();
+            let max = logits_fx.iter().map(|&z| to_f64(z)).fold(f64::NEG_INFINITY, f64::max);
+            let exps: Vec<f64> = logits_fx.iter().map(|&z| (to_f64(z) - max).exp()).collect();
+            let sum: f64 = exps.iter().sum();
+            for (p_fx, e) in softmax_fixed(&logits_fx).iter().zip(exps.iter()) {
+                max_err = max_err.max((to_f64(*p_fx) - e / sum).abs());
+            }
+        }
+        assert!(max_err <= 3.6e-5, "softmax max abs error {:e}", max_err);
+    }
+
+    #[test]
+    fn binary_scores_expand_like_onnxruntime() {
+        let input = |post_transform: &str, left: f64| {
+            let doc = format!(
+                r#"{{ "post_transform": "{}", "nodes": [
+                    {{ "id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2, "value": [] }},
+                    {{ "id": 1, "feature": null, "threshold": null, "left": null, "right": null, "value": [[{}]] }},
+                    {{ "id": 2, "feature": null, "threshold": null, "left": null, "right": null, "value": [[0.75]] }}
+                ] }}"#,
+                post_transform, left
+            );
+            let ensemble = parse_tree_document(&doc);
+            let indexes = build_tree_indexes(&ensemble.nodes);
+            TreeInput::Json(ensemble, indexes, leaf_class_scores)
+        };
+        assert!(input("NONE", -0.5).signed_binary_scores());
+        assert!(!input("NONE", 0.25).signed_binary_scores());
+        assert!(!input("LOGISTIC", -0.5).signed_binary_scores());
+    }
+}
+
 
 
 
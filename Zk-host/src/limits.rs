@@ -0,0 +1,134 @@
+// Per-run budgets for untrusted guests, set by `verse prove` (from its flags
+// or the limits in the CLI's config.json) through the environment:
+//
+//   VERSE_MAX_CYCLES         executor session limit, in cycles
+//   VERSE_TIMEOUT_SECS       wall-clock limit for executing and proving
+//   VERSE_MAX_JOURNAL_BYTES  largest journal accepted from the guest
+//
+// Unset or 0 means no limit. A tripped limit stops the run with a LimitError
+// and exit code LIMIT_EXIT_CODE, before any receipt is written.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const LIMIT_EXIT_CODE: i32 = 3;
+
+// risc0 3.x has no typed error for a tripped session limit: the executor
+// bails with this message (risc0-circuit-rv32im, execute/executor.rs).
+const SESSION_LIMIT_MESSAGE: &str = "Session limit exceeded";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub max_cycles: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub max_journal_bytes: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum LimitError {
+    Cycles { limit: u64 },
+    Timeout { limit: Duration },
+    JournalBytes { limit: usize, actual: usize },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitError::Cycles { limit } => write!(f, "cycle limit exceeded: the guest ran past {} cycles", limit),
+            LimitError::Timeout { limit } => write!(f, "timeout: the run took longer than {:?}", limit),
+            LimitError::JournalBytes { limit, actual } => {
+                write!(f, "journal limit exceeded: {} bytes committed, the limit is {}", actual, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl LimitError {
+    // Reports the tripped limit and ends the process.
+    pub fn exit(&self) -> ! {
+        eprintln!("[host] Limit tripped, {}", self);
+        std::process::exit(LIMIT_EXIT_CODE);
+    }
+}
+
+fn env_limit(name: &str) -> Result<Option<u64>, String> {
+    match std::env::var(name) {
+        Ok(v) => match v.trim().parse::<u64>() {
+            Ok(0) => Ok(None),
+            Ok(n) => Ok(Some(n)),
+            Err(_) => Err(format!("{} must be a whole number, got '{}'", name, v)),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+impl Limits {
+    pub fn from_env() -> Result<Limits, String> {
+        Ok(Limits {
+            max_cycles: env_limit("VERSE_MAX_CYCLES")?,
+            timeout: env_limit("VERSE_TIMEOUT_SECS")?.map(Duration::from_secs),
+            max_journal_bytes: env_limit("VERSE_MAX_JOURNAL_BYTES")?.map(|n| n as usize),
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let show = |v: Option<String>| v.unwrap_or_else(|| "none".to_string());
+        format!(
+            "max cycles {}, timeout {}, max journal {}",
+            show(self.max_cycles.map(|n| n.to_string())),
+            show(self.timeout.map(|t| format!("{:?}", t))),
+            show(self.max_journal_bytes.map(|n| format!("{} bytes", n)))
+        )
+    }
+
+    // Ends the process with LimitError::Timeout once the timeout has passed
+    // since `start`. The executor and prover cannot be interrupted, so the
+    // watchdog exits instead of cancelling them. Stop it once they return,
+    // before writing any output.
+    pub fn start_watchdog(&self, start: Instant) -> Watchdog {
+        let watchdog = Watchdog { stopped: Arc::new(Mutex::new(false)) };
+        if let Some(limit) = self.timeout {
+            let stopped = Arc::clone(&watchdog.stopped);
+            std::thread::spawn(move || {
+                std::thread::sleep(limit.saturating_sub(start.elapsed()));
+                // exit while holding the lock, so stop() cannot return and
+                // let the caller start writing
+                let stopped = stopped.lock().unwrap_or_else(|e| e.into_inner());
+                if !*stopped {
+                    LimitError::Timeout { limit }.exit();
+                }
+            });
+        }
+        watchdog
+    }
+
+    // The limit behind an executor or prover error, when the session limit
+    // is what stopped it.
+    pub fn classify(&self, err: &anyhow::Error) -> Option<LimitError> {
+        let limit = self.max_cycles?;
+        err.chain()
+            .any(|cause| cause.to_string().starts_with(SESSION_LIMIT_MESSAGE))
+            .then_some(LimitError::Cycles { limit })
+    }
+
+    pub fn check_journal(&self, journal: &[u8]) -> Result<(), LimitError> {
+        match self.max_journal_bytes {
+            Some(limit) if journal.len() > limit => Err(LimitError::JournalBytes { limit, actual: journal.len() }),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct Watchdog {
+    stopped: Arc<Mutex<bool>>,
+}
+
+impl Watchdog {
+    // Disarms the watchdog; once this returns it can no longer end the process.
+    pub fn stop(&self) {
+        *self.stopped.lock().unwrap_or_else(|e| e.into_inner()) = true;
+    }
+}
@@ -234,12 +234,253 @@
 
 
 
-use risc0_zkvm::{default_prover, ExecutorEnv};
+mod limits;
+mod preprocess;
+mod receipt;
+mod spec;
+
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv};
 use risc0_zkvm::serde::from_slice;
-use serde_json;
+use limits::Limits;
+use verse_common::{flat_tree, report};
+use receipt::ReceiptKind;
+use spec::ModelSpec;
 use std::fs;
 use std::io;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+fn parse_f32_list(line: &str, what: &str) -> Vec<f32> {
+    line.trim()
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().parse::<f32>().unwrap_or_else(|_| panic!("Invalid {}", what)))
+        .collect()
+}
+
+fn read_trimmed_line() -> String {
+    let mut buffer = String::new();
+    io::stdin().read_line(&mut buffer).unwrap();
+    buffer.trim().to_string()
+}
+
+// Interactive fallback when no spec file is given. Feature width is implied by
+// the weights where the model kind allows it, otherwise asked for.
+fn prompt_model_spec() -> ModelSpec {
+    println!("Select model (1=linear, 2=multiple, 3=poly, 4=logistic, 5=decision tree, 6=softmax regression, 8=tree ensemble, 9=tree regressor):");
+    let model_type: u32 = read_trimmed_line().parse().expect("Enter 1..9");
+    if model_type == 7 {
+        panic!("MLP (model 7) parameters must be given in a model spec file");
+    }
+    let mut spec = ModelSpec { model_type, ..Default::default() };
+
+    match model_type {
+        5 | 8 | 9 => {
+            println!("Enter path to Decision Tree JSON file (default: tree.json):");
+            let tree_path = read_trimmed_line();
+            if !tree_path.is_empty() {
+                spec.tree_path = Some(tree_path);
+            }
+        }
+        6 => {
+            println!("Enter number of classes:");
+            spec.num_classes = read_trimmed_line().parse().expect("Invalid number of classes");
+            if spec.num_classes < 2 { panic!("Softmax regression expects >=2 classes"); }
+
+            println!("Enter weights (comma-separated, row-major num_classes x num_features):");
+            spec.weights = parse_f32_list(&read_trimmed_line(), "weight");
+            if spec.weights.is_empty() || !spec.weights.len().is_multiple_of(spec.num_classes as usize) {
+                panic!("Softmax regression expects num_classes x num_features weights");
+            }
+            spec.num_features = spec.weights.len() / spec.num_classes as usize;
+
+            println!("Enter biases (comma-separated, one per class):");
+            spec.biases = parse_f32_list(&read_trimmed_line(), "bias");
+        }
+        _ => {
+            println!("Enter weights (comma-separated):");
+            spec.weights = parse_f32_list(&read_trimmed_line(), "weight");
+            if model_type == 2 || model_type == 4 {
+                spec.num_features = spec.weights.len();
+            } else if model_type == 1 || model_type == 3 {
+                spec.num_features = 1;
+            }
+
+            println!("Enter bias (b) (use dot, e.g. 2.0):");
+            spec.bias = read_trimmed_line().parse().expect("Invalid bias");
+        }
+    }
+
+    if spec.num_features == 0 {
+        println!("Enter number of input features:");
+        spec.num_features = read_trimmed_line().parse().expect("Invalid number of features");
+    }
+    spec
+}
+
+// How a tree model reaches the guest. The guest reads the format code after
+// tree_path; see verse-common/src/flat_tree.rs for the flattened and quantized layouts.
+enum TreeInput {
+    Json(String),
+    Flat(Vec<u32>),
+    Quantized(Vec<u32>),
+}
+
+impl TreeInput {
+    fn format_code(&self) -> u32 {
+        match self {
+            TreeInput::Json(_) => 0,
+            TreeInput::Flat(_) => 1,
+            TreeInput::Quantized(_) => 2,
+        }
+    }
+}
+
+fn build_env<'a>(
+    use_opt_flag: u32,
+    spec: &ModelSpec,
+    tree_path: &str,
+    tree: Option<&TreeInput>,
+    limits: &Limits,
+) -> ExecutorEnv<'a> {
+    let model_type = spec.model_type;
+    let mut builder = ExecutorEnv::builder();
+    builder.session_limit(limits.max_cycles);
+    builder.write(&use_opt_flag).unwrap();
+    builder.write(&model_type).unwrap();
+    builder.write(&(spec.num_features as u32)).unwrap();
+    // per-feature (offset, factor) the guest applies to its dataset; empty when unscaled
+    let scaling: Vec<(f32, f32)> = spec
+        .preprocessing
+        .as_ref()
+        .filter(|p| p.has_scaling())
+        .map(preprocess::affine)
+        .unwrap_or_default();
+    builder.write(&scaling).unwrap();
+
+    if let Some(tree) = tree {
+        builder.write(&tree_path).unwrap();
+        builder.write(&tree.format_code()).unwrap();
+        match tree {
+            TreeInput::Json(json) => {
+                builder.write(json).unwrap();
+            }
+            TreeInput::Flat(words) | TreeInput::Quantized(words) => {
+                builder.write(&(words.len() as u32)).unwrap();
+                builder.write_slice(words);
+            }
+        }
+    } else if model_type == 6 {
+        builder.write(&spec.num_classes).unwrap();
+        builder.write(&spec.weights).unwrap();
+        builder.write(&spec.biases).unwrap();
+    } else if model_type == 7 {
+        builder.write(&(spec.layers.len() as u32)).unwrap();
+        for layer in spec.layers.iter() {
+            builder.write(&layer.activation.code()).unwrap();
+            builder.write(&layer.weights).unwrap();
+            builder.write(&layer.biases).unwrap();
+        }
+        builder.write(&spec.head.code()).unwrap();
+    } else {
+        builder.write(&spec.weights).unwrap();
+        builder.write(&spec.bias).unwrap();
+    }
+
+    builder.build().unwrap()
+}
+
+// Decodes the guest's journal for the spec's model kind and prints the
+// report (classification, tree regression, or the first outputs).
+fn print_outputs(spec: &ModelSpec, journal: &[u8], quantized: bool) {
+    let model_type = spec.model_type;
+    if spec.is_classifier() {
+        println!("[host] Decoding journal to predictions...");
+        let (predictions, routing): (Vec<(Vec<f64>, u32)>, Option<report::RoutingReport>) = if quantized {
+            let (p, r) = from_slice(journal).expect("Failed decode journal");
+            (p, Some(r))
+        } else {
+            (from_slice(journal).expect("Failed decode journal"), None)
+        };
+        println!("[host] Decoded {} predictions", predictions.len());
+        let num_scores = predictions.first().map(|(p, _)| p.len()).unwrap_or(0);
+        if !spec.class_labels.is_empty() && num_scores != spec.class_labels.len() {
+            println!(
+                "[host] Warning: spec has {} class_labels but the model scores {} classes",
+                spec.class_labels.len(),
+                num_scores
+            );
+        }
+        report::print_classification_report(&predictions, &spec.class_labels);
+        if let Some(routing) = routing {
+            report::print_routing_report(&routing, predictions.len());
+        }
+    } else if model_type == 9 {
+        println!("[host] Decoding journal to regression outputs...");
+        let ((outputs, metrics), routing): (report::RegressionJournal, Option<report::RoutingReport>) = if quantized {
+            let (o, r) = from_slice(journal).expect("Failed decode journal");
+            (o, Some(r))
+        } else {
+            (from_slice(journal).expect("Failed decode journal"), None)
+        };
+        println!("[host] Decoded {} predictions", outputs.len());
+        report::print_regression_report(&outputs, &metrics, true);
+        if let Some(routing) = routing {
+            report::print_routing_report(&routing, outputs.len());
+        }
+    } else {
+        let output: Vec<(f32, f32)> = from_slice(journal).expect("Failed decode journal");
+        println!("\n=== Results (first 5) ===");
+        for (i, (p, t)) in output.iter().enumerate().take(5) {
+            println!("{}: pred={:.6}, true={:.6}", i, p, t);
+        }
+    }
+}
+
+// Proving throughput assumed by the execute-only estimate, in cycles per
+// second; VERSE_PROVE_RATE overrides it with a figure measured on this machine.
+const DEFAULT_PROVE_RATE: f64 = 25_000.0;
+
+// Dry run (VERSE_EXECUTE_ONLY=1, set by `verse prove --execute-only`): runs the
+// guest in the executor to check it accepts the spec and to size the proof.
+// No receipt is produced.
+fn execute_only(env: ExecutorEnv, guest_elf: &[u8], spec: &ModelSpec, quantized: bool, limits: &Limits) {
+    println!("[host] Executing guest without proving...");
+    let start = Instant::now();
+    let watchdog = limits.start_watchdog(start);
+    let session = default_executor().execute(env, guest_elf).unwrap_or_else(|e| match limits.classify(&e) {
+        Some(tripped) => tripped.exit(),
+        None => panic!("Execution failed: {:#}", e),
+    });
+    watchdog.stop();
+    let elapsed = start.elapsed();
+    if let Err(tripped) = limits.check_journal(&session.journal.bytes) {
+        tripped.exit();
+    }
+
+    print_outputs(spec, &session.journal.bytes, quantized);
+
+    // each segment is proven padded to 2^po2 cycles
+    let total_cycles: u64 = session.segments.iter().map(|s| 1u64 << s.po2).sum();
+    let user_cycles: u64 = session.segments.iter().map(|s| s.cycles as u64).sum();
+    let rate = std::env::var("VERSE_PROVE_RATE")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|r| *r > 0.0)
+        .unwrap_or(DEFAULT_PROVE_RATE);
+
+    println!("\n=== Execution (no proof) ===");
+    println!("Exit code: {:?}", session.exit_code);
+    println!("Execution time: {:?}", elapsed);
+    println!("Total cycles: {}", total_cycles);
+    println!("User cycles: {}", user_cycles);
+    println!("Segments: {}", session.segments.len());
+    println!("Journal size: {} bytes", session.journal.bytes.len());
+    println!(
+        "Estimated proving time: {:.0?} at {} cycles/s (set VERSE_PROVE_RATE to calibrate)",
+        Duration::from_secs_f64(total_cycles as f64 / rate),
+        rate
+    );
+}
 
 fn main() {
     println!("Enter path to guest ELF file:");
@@ -253,47 +494,31 @@ fn main() {
     io::stdin().read_line(&mut buffer).unwrap();
     let use_opt_flag: u32 = buffer.trim().parse().expect("Enter 0 or 1");
 
-    println!("Select model (1=linear, 2=multiple, 3=poly, 4=logistic, 5=decision tree):");
+    println!("Enter path to model spec JSON (leave empty to enter parameters interactively):");
     buffer.clear();
     io::stdin().read_line(&mut buffer).unwrap();
-    let model_type: u32 = buffer.trim().parse().expect("Enter 1..5");
+    let spec_path = buffer.trim().to_string();
+    let mut spec = if spec_path.is_empty() {
+        prompt_model_spec()
+    } else {
+        println!("[host] Using model spec: {}", spec_path);
+        ModelSpec::load(&spec_path).unwrap_or_else(|e| panic!("{}", e))
+    };
+    spec.validate().unwrap_or_else(|e| panic!("{}", e));
+    let model_type = spec.model_type;
+    if !spec.feature_names.is_empty() {
+        println!("[host] Features: {}", spec.feature_names.join(", "));
+    }
+    if let Some(p) = spec.preprocessing.as_ref().filter(|p| p.has_scaling()) {
+        let scaled: Vec<&str> = p.columns.iter().filter(|c| c.scale.is_some()).map(|c| c.name.as_str()).collect();
+        println!("[host] Guest scales: {}", scaled.join(", "));
+    }
 
-    // Variables to be passed to guest
-    let mut weights: Vec<f32> = Vec::new();
-    let mut b: f32 = 0.0;
     let mut tree_path = String::new();
     let mut tree_json = String::new();
-
-    if model_type != 5 {
-        println!("Enter weights (comma-separated):");
-        buffer.clear();
-        io::stdin().read_line(&mut buffer).unwrap();
-        weights = buffer
-            .trim()
-            .split(',')
-            .filter(|s| !s.trim().is_empty())
-            .map(|s| s.trim().parse::<f32>().expect("Invalid weight"))
-            .collect();
-
-        match model_type {
-            1 => if weights.len() != 1 { panic!("Model 1 expects 1 weight"); },
-            2 | 4 => if weights.len() != 3 { panic!("Model 2/4 expect 3 weights"); },
-            3 => if weights.is_empty() { panic!("Polynomial expects >=1 coeff"); },
-            _ => panic!("Unknown model"),
-        }
-
-        println!("Enter bias (b) (use dot, e.g. 2.0):");
-        buffer.clear();
-        io::stdin().read_line(&mut buffer).unwrap();
-        b = buffer.trim().parse().expect("Invalid bias");
-    } else {
-        println!("Enter path to Decision Tree JSON file (default: tree.json):");
-        buffer.clear();
-        io::stdin().read_line(&mut buffer).unwrap();
-        tree_path = buffer.trim().to_string();
-        if tree_path.is_empty() {
-            tree_path = "tree.json".to_string();
-        }
+    let mut tree_input = None;
+    if spec.uses_tree() {
+        tree_path = spec.tree_path.take().unwrap_or_else(|| "tree.json".to_string());
 
         println!("[host] Using tree JSON path: {}", tree_path);
         let read_start = Instant::now();
@@ -309,66 +534,131 @@ fn main() {
             preview,
             if tree_json.len() > 120 { "..." } else { "" }
         );
-    }
 
-    println!("\n[host] Building zkVM executor environment...");
-    let mut builder = ExecutorEnv::builder();
-    builder.write(&use_opt_flag).unwrap();
-    builder.write(&model_type).unwrap();
+        if let Some(max_feature) = spec::max_tree_feature(&tree_json).unwrap_or_else(|e| panic!("{}", e)) {
+            if max_feature >= spec.num_features {
+                panic!(
+                    "Tree splits on feature {} but num_features is {}",
+                    max_feature, spec.num_features
+                );
+            }
+        }
 
-    if model_type == 5 {
-        builder.write(&tree_path).unwrap();
-        builder.write(&tree_json).unwrap();
-    } else {
-        builder.write(&weights).unwrap();
-        builder.write(&b).unwrap();
+        let send_json = std::env::var("VERSE_TREE_FORMAT")
+            .map(|v| v.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        let leaf_values = flat_tree::LeafValues::for_model(model_type);
+        tree_input = Some(if send_json {
+            println!("[host] Sending tree as JSON (VERSE_TREE_FORMAT=json)");
+            TreeInput::Json(tree_json.clone())
+        } else if use_opt_flag == 1 {
+            let (words, scale_bits) =
+                flat_tree::quantize_tree_json(&tree_json, leaf_values).unwrap_or_else(|e| panic!("{}", e));
+            println!(
+                "[host] Quantized tree thresholds to fixed point (2^{} scale), {} words ({} bytes)",
+                scale_bits,
+                words.len(),
+                words.len() * 4
+            );
+            TreeInput::Quantized(words)
+        } else {
+            let words = flat_tree::flatten_tree_json(&tree_json, leaf_values).unwrap_or_else(|e| panic!("{}", e));
+            println!("[host] Flattened tree to {} words ({} bytes)", words.len(), words.len() * 4);
+            TreeInput::Flat(words)
+        });
     }
 
-    let env = builder.build().unwrap();
-
+    println!("\n[host] Building zkVM executor environment...");
+    let limits = Limits::from_env().unwrap_or_else(|e| panic!("{}", e));
+    println!("[host] Limits: {}", limits.describe());
+    let receipt_kind = ReceiptKind::from_env().unwrap_or_else(|e| panic!("{}", e));
+    let encoding = receipt::Encoding::from_env().unwrap_or_else(|e| panic!("{}", e));
+    let env = build_env(use_opt_flag, &spec, &tree_path, tree_input.as_ref(), &limits);
+    let quantized = matches!(tree_input, Some(TreeInput::Quantized(_)));
+
+    if std::env::var("VERSE_EXECUTE_ONLY").is_ok_and(|v| v == "1") {
+        execute_only(env, &guest_elf, &spec, quantized, &limits);
+        return;
+    }
 
     let prover = default_prover();
-    println!("[host] Starting proof generation...");
+    println!("[host] Starting proof generation ({} receipt)...", receipt_kind);
     let start = Instant::now();
-    let prove_info = prover.prove(env, &guest_elf).expect("Prove failed");
+    let watchdog = limits.start_watchdog(start);
+    let prove_info = prover.prove(env, &guest_elf).unwrap_or_else(|e| match limits.classify(&e) {
+        Some(tripped) => tripped.exit(),
+        None => panic!("Prove failed: {:#}", e),
+    });
     let elapsed = start.elapsed();
 
     let receipt = prove_info.receipt;
-
-    if model_type == 5 {
-        println!("[host] Decoding journal to predictions...");
-        let predictions: Vec<(Vec<f64>, u32)> = from_slice(receipt.journal.bytes.as_slice()).expect("Failed decode journal");
-        println!("[host] Decoded {} predictions", predictions.len());
-
-        println!("\nSample | PredClass | Prob    | Expected");
-        println!("---------------------------------------");
-        for (i, (probs, expected)) in predictions.iter().enumerate().take(5) {
-            let (pred_idx, pred_p) = probs
-                .iter()
-                .enumerate()
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-                .map(|(idx, p)| (idx, *p))
-                .unwrap_or((usize::MAX, f64::NAN));
-            println!("{:<6} | {:<9} | {:<6.3} | {}", i, pred_idx, pred_p, expected);
-        }
-    } else {
-        let output: Vec<(f32, f32)> = from_slice(receipt.journal.bytes.as_slice()).expect("Failed decode journal");
-        println!("\n=== Results (first 5) ===");
-        for (i, (p, t)) in output.iter().enumerate().take(5) {
-            println!("{}: pred={:.6}, true={:.6}", i, p, t);
-        }
+    if let Err(tripped) = limits.check_journal(&receipt.journal.bytes) {
+        tripped.exit();
     }
 
+    print_outputs(&spec, &receipt.journal.bytes, quantized);
+
     println!("\n=== Benchmark ===");
     println!("Prove time: {:?}", elapsed);
     println!("Cycle count: {}", prove_info.stats.total_cycles);
     println!("Journal size: {} bytes", receipt.journal.bytes.len());
+    if let Some(TreeInput::Flat(words) | TreeInput::Quantized(words)) = tree_input.as_ref() {
+        println!(
+            "Tree input: {}, {} bytes (JSON: {} bytes)",
+            if quantized { "quantized" } else { "flat" },
+            words.len() * 4,
+            tree_json.len()
+        );
+        // With VERSE_BENCH_JSON=1, execute (not prove) the same guest with the
+        // JSON input to show what the flattened format saves. Off by default:
+        // it runs the untrusted guest a second time.
+        if std::env::var("VERSE_BENCH_JSON").is_ok_and(|v| v == "1") {
+            let json_env = build_env(use_opt_flag, &spec, &tree_path, Some(&TreeInput::Json(tree_json.clone())), &limits);
+            match default_executor().execute(json_env, &guest_elf) {
+                Ok(session) => {
+                    let flat_cycles = prove_info.stats.user_cycles;
+                    let json_cycles = session.cycles();
+                    println!(
+                        "User cycles: {} (JSON tree input: {}, saved {} / {:.1}%)",
+                        flat_cycles,
+                        json_cycles,
+                        json_cycles.saturating_sub(flat_cycles),
+                        100.0 * json_cycles.saturating_sub(flat_cycles) as f64 / json_cycles.max(1) as f64
+                    );
+                }
+                Err(e) => println!("JSON tree comparison run failed: {}", e),
+            }
+        }
+    }
 
-    let proof_json = serde_json::to_string(&receipt).expect("serialize failed");
-    fs::write("proof.json", &proof_json).expect("failed write proof");
-    println!("✅ Proof saved to proof.json");
-    fs::write("combined_outputs.json", &proof_json).expect("Write failed");
-    println!("📄 Combined outputs saved to combined_outputs.json");
+    let composite_size = receipt::encode(&receipt, encoding).unwrap_or_else(|e| panic!("{}", e)).len();
+    if receipt_kind != ReceiptKind::Composite {
+        println!("\n[host] Compressing to a {} receipt...", receipt_kind);
+    }
+    let compress_start = Instant::now();
+    let receipt = receipt_kind
+        .convert(prover.as_ref(), receipt)
+        .unwrap_or_else(|e| panic!("Receipt compression failed: {:#}", e));
+    // proving is over; the timeout must not cut the receipt write short
+    watchdog.stop();
+
+    let encoded = receipt::encode(&receipt, encoding).unwrap_or_else(|e| panic!("{}", e));
+    println!(
+        "Receipt: {}, {} encoding, {} bytes",
+        receipt::kind_name(&receipt),
+        encoding,
+        encoded.len()
+    );
+    if receipt_kind != ReceiptKind::Composite {
+        println!(
+            "Compression: {:?}, composite receipt was {} bytes ({:.1}% smaller)",
+            compress_start.elapsed(),
+            composite_size,
+            100.0 * (1.0 - encoded.len() as f64 / composite_size.max(1) as f64)
+        );
+    }
+    fs::write(encoding.file_name(), &encoded).expect("failed write proof");
+    println!("✅ Proof saved to {}", encoding.file_name());
 }
 
 
@@ -0,0 +1,40 @@
+// Preprocessing published with the model (verse-common/src/preprocess.rs has
+// the spec and its checks). The verifier's CLI applies the encoders while
+// ingesting the dataset; the host only turns the scalers into one
+// (offset, factor) pair per model input, and the guest computes
+// (x - offset) * factor on every embedded row.
+
+use verse_common::preprocess::{Preprocessing, Scaler};
+
+// (offset, factor) per model input; encoded and unscaled inputs pass through
+// as (0, 1).
+pub fn affine(p: &Preprocessing) -> Vec<(f32, f32)> {
+    let mut out = Vec::with_capacity(p.output_width());
+    for col in p.columns.iter() {
+        match &col.scale {
+            Some(Scaler::Standard { mean, std }) => out.push((*mean as f32, (1.0 / std) as f32)),
+            Some(Scaler::MinMax { min, max }) => out.push((*min as f32, (1.0 / (max - min)) as f32)),
+            None => out.extend(std::iter::repeat_n((0.0, 1.0), col.width())),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalers_become_offset_and_factor() {
+        let p = Preprocessing::parse(
+            r#"{ "columns": [
+                { "name": "age", "scale": { "standard": { "mean": 40, "std": 8 } } },
+                { "name": "region", "encode": { "one_hot": { "levels": ["n", "s"] } } },
+                { "name": "bmi", "scale": { "min_max": { "min": 10, "max": 60 } } },
+                { "name": "size", "encode": { "ordinal": { "levels": ["S", "M", "L"] } } } ] }"#,
+        )
+        .unwrap();
+        assert_eq!(affine(&p), [(40.0, 0.125), (0.0, 1.0), (0.0, 1.0), (10.0, 0.02), (0.0, 1.0)]);
+        assert_eq!(affine(&p).len(), p.output_width());
+    }
+}
@@ -0,0 +1,62 @@
+// Receipt kinds Zk-host can hand back, picked with VERSE_RECEIPT_KIND (set
+// by `verse prove --receipt-kind`):
+//
+//   composite  what the prover returns: one STARK per segment; grows with cycles
+//   succinct   the composite receipt compressed by recursion into a single
+//              STARK of constant size, on the local prover
+//
+// Both verify against the same image ID.
+//
+// Receipts are written with VERSE_RECEIPT_ENCODING; the file format lives in
+// verse_common::receipt, shared with the readers.
+
+use risc0_zkvm::{Prover, ProverOpts, Receipt};
+use std::fmt;
+use std::str::FromStr;
+
+pub use verse_common::receipt::{encode, kind_name, Encoding};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReceiptKind {
+    #[default]
+    Composite,
+    Succinct,
+}
+
+impl FromStr for ReceiptKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "composite" => Ok(ReceiptKind::Composite),
+            "succinct" => Ok(ReceiptKind::Succinct),
+            other => Err(format!("Unknown receipt kind '{}' (expected composite or succinct)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ReceiptKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReceiptKind::Composite => write!(f, "composite"),
+            ReceiptKind::Succinct => write!(f, "succinct"),
+        }
+    }
+}
+
+impl ReceiptKind {
+    pub fn from_env() -> Result<ReceiptKind, String> {
+        match std::env::var("VERSE_RECEIPT_KIND") {
+            Ok(v) if !v.trim().is_empty() => v.parse(),
+            _ => Ok(ReceiptKind::default()),
+        }
+    }
+
+    // Turns a freshly proven (composite) receipt into this kind.
+    pub fn convert(&self, prover: &dyn Prover, receipt: Receipt) -> anyhow::Result<Receipt> {
+        match self {
+            ReceiptKind::Composite => Ok(receipt),
+            ReceiptKind::Succinct => prover.compress(&ProverOpts::succinct(), &receipt),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// Model spec file: everything the guest needs to know about a model, so owners
+// don't have to type weights into the prompts. Example:
+//
+//   { "model_type": 2, "num_features": 3, "weights": [1.0, 2.0, 3.0], "bias": 0.5 }
+//   { "model_type": 5, "num_features": 4, "tree_path": "tree.json" }
+//   { "model_type": 8, "num_features": 4, "tree_path": "forest.json" }
+//   { "model_type": 9, "num_features": 3, "tree_path": "price_tree.json" }
+//   { "model_type": 6, "num_features": 4, "num_classes": 3,
+//     "weights": [...12 values, row-major...], "biases": [0.1, 0.0, -0.1] }
+//   { "model_type": 7, "num_features": 4, "head": "softmax",
+//     "layers": [ { "weights": [...8x4...], "biases": [...8...], "activation": "relu" },
+//                 { "weights": [...3x8...], "biases": [...3...] } ] }
+//
+// model-owner/mlp2json.py writes model 7 specs from an ONNX export.
+//
+// Any spec may name its inputs and, for classifiers, its classes in index
+// order; reports then show the names instead of bare indices:
+//   "feature_names": ["sepal_length", "sepal_width", "petal_length", "petal_width"],
+//   "class_labels": ["Iris-setosa", "Iris-versicolor", "Iris-virginica"]
+// and carry the preprocessing published with the model
+// (verse-common/src/preprocess.rs); its scaling is sent to the guest and
+// applied to the embedded dataset:
+//   "preprocessing": { "columns": [ { "name": "age", "scale": { "standard": { "mean": 41.2, "std": 12.5 } } }, ... ] }
+// Model 5 reads a list of tree nodes; models 8 (classifier) and 9 (regressor)
+// also accept an ensemble document written by model-owner/tree2json.py:
+//   { "aggregate": "SUM", "post_transform": "NONE", "base_values": [],
+//     "nodes": [ { "tree_id": 0, "id": 0, "feature": 2, "threshold": 2.45, ... } ] }
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use verse_common::preprocess::Preprocessing;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelSpec {
+    pub model_type: u32,
+    pub num_features: usize,
+    #[serde(default)]
+    pub weights: Vec<f32>,
+    #[serde(default)]
+    pub bias: f32,
+    #[serde(default)]
+    pub num_classes: u32,
+    #[serde(default)]
+    pub biases: Vec<f32>,
+    #[serde(default)]
+    pub tree_path: Option<String>,
+    #[serde(default)]
+    pub layers: Vec<DenseLayer>,
+    #[serde(default)]
+    pub head: Head,
+    #[serde(default)]
+    pub feature_names: Vec<String>,
+    #[serde(default)]
+    pub class_labels: Vec<String>,
+    #[serde(default)]
+    pub preprocessing: Option<Preprocessing>,
+}
+
+// One dense layer: weights is out_dim x in_dim row-major, one bias per output.
+#[derive(Debug, Deserialize)]
+pub struct DenseLayer {
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+    #[serde(default)]
+    pub activation: Activation,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+    #[default]
+    Identity,
+    Relu,
+    Sigmoid,
+    Tanh,
+}
+
+impl Activation {
+    // Code the guest reads for each layer.
+    pub fn code(self) -> u32 {
+        match self {
+            Activation::Identity => 0,
+            Activation::Relu => 1,
+            Activation::Sigmoid => 2,
+            Activation::Tanh => 3,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Head {
+    #[default]
+    Linear,
+    Softmax,
+}
+
+impl Head {
+    pub fn code(self) -> u32 {
+        match self {
+            Head::Linear => 0,
+            Head::Softmax => 1,
+        }
+    }
+}
+
+impl ModelSpec {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read model spec ({}): {}", path, e))?;
+        let mut spec: ModelSpec = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid model spec ({}): {}", path, e))?;
+
+        // tree_path is relative to the spec file
+        if let Some(tree_path) = spec.tree_path.as_mut() {
+            if Path::new(tree_path).is_relative() {
+                if let Some(dir) = Path::new(path).parent() {
+                    *tree_path = dir.join(&*tree_path).to_string_lossy().into_owned();
+                }
+            }
+        }
+        Ok(spec)
+    }
+
+    // Check that the parameters agree with num_features. The guest repeats the
+    // width check against its embedded dataset.
+    pub fn validate(&self) -> Result<(), String> {
+        let d = self.num_features;
+        if d == 0 {
+            return Err("num_features must be >= 1".into());
+        }
+        match self.model_type {
+            1 => {
+                if d != 1 {
+                    return Err(format!("Model 1 is a single-feature regression, got num_features {}", d));
+                }
+                if self.weights.len() != 1 {
+                    return Err(format!("Model 1 expects 1 weight, got {}", self.weights.len()));
+                }
+            }
+            2 | 4 => {
+                if self.weights.len() != d {
+                    return Err(format!(
+                        "Model {} expects one weight per feature ({}), got {}",
+                        self.model_type, d, self.weights.len()
+                    ));
+                }
+            }
+            3 => {
+                if d != 1 {
+                    return Err(format!("Model 3 is a single-feature polynomial, got num_features {}", d));
+                }
+                if self.weights.is_empty() {
+                    return Err("Polynomial expects >=1 coeff".into());
+                }
+            }
+            5 | 8 | 9 => {}
+            6 => {
+                let k = self.num_classes as usize;
+                if k < 2 {
+                    return Err("Softmax regression expects >=2 classes".into());
+                }
+                if self.weights.len() != k * d {
+                    return Err(format!(
+                        "Softmax regression expects num_classes x num_features = {} weights, got {}",
+                        k * d,
+                        self.weights.len()
+                    ));
+                }
+                if self.biases.len() != k {
+                    return Err(format!(
+                        "Softmax regression expects one bias per class ({}), got {}",
+                        k,
+                        self.biases.len()
+                    ));
+                }
+            }
+            7 => {
+                if self.layers.is_empty() {
+                    return Err("MLP expects at least one layer".into());
+                }
+                let mut in_dim = d;
+                for (i, layer) in self.layers.iter().enumerate() {
+                    let out_dim = layer.biases.len();
+                    if out_dim == 0 {
+                        return Err(format!("Layer {} has no outputs", i));
+                    }
+                    if layer.weights.len() != out_dim * in_dim {
+                        return Err(format!(
+                            "Layer {} expects {} x {} = {} weights, got {}",
+                            i,
+                            out_dim,
+                            in_dim,
+                            out_dim * in_dim,
+                            layer.weights.len()
+                        ));
+                    }
+                    in_dim = out_dim;
+                }
+            }
+            other => return Err(format!("Unknown model type {}", other)),
+        }
+
+        if !self.feature_names.is_empty() && self.feature_names.len() != d {
+            return Err(format!(
+                "feature_names has {} names for {} features",
+                self.feature_names.len(),
+                d
+            ));
+        }
+        if let Some(p) = self.preprocessing.as_ref() {
+            p.validate().map_err(|e| format!("preprocessing: {}", e))?;
+            if p.output_width() != d {
+                return Err(format!("preprocessing produces {} features, the model expects {}", p.output_width(), d));
+            }
+        }
+        if !self.class_labels.is_empty() {
+            if !self.is_classifier() {
+                return Err(format!("class_labels given for model {}, which is not a classifier", self.model_type));
+            }
+            let expected = match self.model_type {
+                6 => Some(self.num_classes as usize),
+                7 => self.layers.last().map(|l| l.biases.len()),
+                // tree leaf widths are only known once the tree is read
+                _ => None,
+            };
+            if let Some(k) = expected.filter(|&k| k != self.class_labels.len()) {
+                return Err(format!("class_labels has {} names for {} classes", self.class_labels.len(), k));
+            }
+            for (i, label) in self.class_labels.iter().enumerate() {
+                if self.class_labels[..i].contains(label) {
+                    return Err(format!("class_labels repeats {:?}", label));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Model kinds that read tree JSON instead of weights.
+    pub fn uses_tree(&self) -> bool {
+        matches!(self.model_type, 5 | 8 | 9)
+    }
+
+    // Whether the guest commits class scores (Vec<(Vec<f64>, u32)>) rather than
+    // (prediction, target) pairs.
+    pub fn is_classifier(&self) -> bool {
+        match self.model_type {
+            5 | 6 | 8 => true,
+            7 => {
+                let out_dim = self.layers.last().map(|l| l.biases.len()).unwrap_or(0);
+                self.head == Head::Softmax || out_dim > 1
+            }
+            _ => false,
+        }
+    }
+}
+
+// Highest feature index referenced by a tree JSON (list of nodes, or an ensemble
+// document with a "nodes" list). Leaves carry no feature.
+pub fn max_tree_feature(tree_json: &str) -> Result<Option<usize>, String> {
+    let doc: serde_json::Value =
+        serde_json::from_str(tree_json).map_err(|e| format!("Invalid tree JSON: {}", e))?;
+    let nodes = doc
+        .get("nodes")
+        .unwrap_or(&doc)
+        .as_array()
+        .ok_or("Tree JSON must be a list of nodes or an object with \"nodes\"")?;
+    Ok(nodes
+        .iter()
+        .filter_map(|n| n.get("feature").and_then(|f| f.as_u64()))
+        .map(|f| f as usize)
+        .max())
+}
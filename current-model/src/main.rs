@@ -1,63 +1,82 @@
-use ort::{session::{Session, builder::GraphOptimizationLevel}, value::{Tensor, DynValue, MapValueType}};
-use std::collections::HashMap;
+// Runs one sample through an ONNX model with onnxruntime. Input and output
+// names are read from the session, so any skl2onnx export works, not only
+// iris_tree_model.onnx. For whole CSV / NDJSON datasets and a metric report use
+// `verse model eval --onnx <FILE> --dataset <FILE>`.
+//
+// Usage: current-model [model.onnx] [comma-separated features]
+use ort::{session::{Session, builder::GraphOptimizationLevel}, tensor::TensorElementType, value::{Tensor, MapValueType, ValueType}};
+use std::collections::BTreeMap;
 fn main() -> anyhow::Result<()> {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().collect();
+    let model_path = args.get(1).map(String::as_str).unwrap_or("iris_tree_model.onnx");
+    let features = args.get(2).map(String::as_str).unwrap_or("5.1,3.5,1.4,0.2");
 
     let mut model = Session::builder()?
     .with_optimization_level(GraphOptimizationLevel::Level3)?
     .with_intra_threads(4)?
-    .commit_from_file("iris_tree_model.onnx")?;
+    .commit_from_file(model_path)?;
 
-    let input_data: Vec<f32> = vec![5.1, 3.5, 1.4, 0.2];
-    let shape = [1usize, 4usize];
+    println!("Model: {}", model_path);
+    for input in model.inputs.iter() {
+        println!("  input   {:<20} {}", input.name, input.input_type);
+    }
+    for output in model.outputs.iter() {
+        println!("  output  {:<20} {}", output.name, output.output_type);
+    }
 
-    let input_tensor = Tensor::from_array((shape, input_data.into_boxed_slice()))?;
-    let mut outputs = model.run(ort::inputs!["float_input" => input_tensor])?;
-    let label_value: DynValue = outputs
-        .remove("output_label")
-        .expect("missing output_label");
-    let prob_value: DynValue = outputs
-        .remove("output_probability")
-        .expect("missing output_probability");
+    let input_data: Vec<f32> = features
+        .split(',')
+        .map(|v| v.trim().parse::<f32>())
+        .collect::<Result<_, _>>()?;
+    let input_name = model.inputs.first().map(|i| i.name.clone()).ok_or_else(|| anyhow::anyhow!("model has no inputs"))?;
+    if let Some(&width) = model.inputs[0].input_type.tensor_shape().and_then(|s| s.last()) {
+        anyhow::ensure!(width <= 0 || width as usize == input_data.len(), "model expects {} features, got {}", width, input_data.len());
+    }
+    let output_names: Vec<String> = model.outputs.iter().map(|o| o.name.clone()).collect();
 
+    let shape = [1usize, input_data.len()];
+    let input_tensor = Tensor::from_array((shape, input_data.into_boxed_slice()))?;
+    let mut outputs = model.run(ort::inputs![input_name.as_str() => input_tensor])?;
+    let values: Vec<_> = output_names.iter().filter_map(|name| outputs.remove(name.as_str()).map(|v| (name.clone(), v))).collect();
     drop(outputs);
 
-    println!("Predicted label: {:?}", label_value.try_extract_array::<i64>()?);
-
     let allocator = model.allocator();
-    let prob_sequence = prob_value.try_extract_sequence::<MapValueType<i64, f32>>(allocator)?;
-    println!("Predicted probabilities: {:?}", prob_sequence);
+    for (name, value) in values.iter() {
+        match value.dtype() {
+            ValueType::Tensor { ty: TensorElementType::Int64, .. } => {
+                println!("{}: {:?}", name, value.try_extract_tensor::<i64>()?.1);
+            }
+            ValueType::Tensor { ty: TensorElementType::String, .. } => {
+                println!("{}: {:?}", name, value.try_extract_strings()?.1);
+            }
+            ValueType::Tensor { ty: TensorElementType::Float32, .. } => {
+                println!("{}: {:?}", name, value.try_extract_tensor::<f32>()?.1);
+            }
+            ValueType::Sequence(inner) if matches!(inner.as_ref(), ValueType::Map { key: TensorElementType::Int64, .. }) => {
+                for map_val in value.try_extract_sequence::<MapValueType<i64, f32>>(allocator)?.iter() {
+                    let prob_map: BTreeMap<i64, f32> = map_val.try_extract_key_values::<i64, f32>()?.into_iter().collect();
+                    print_probabilities(name, &prob_map);
+                }
+            }
+            ValueType::Sequence(inner) if matches!(inner.as_ref(), ValueType::Map { key: TensorElementType::String, .. }) => {
+                for map_val in value.try_extract_sequence::<MapValueType<String, f32>>(allocator)?.iter() {
+                    let prob_map: BTreeMap<String, f32> = map_val.try_extract_key_values::<String, f32>()?.into_iter().collect();
+                    print_probabilities(name, &prob_map);
+                }
+            }
+            other => println!("{}: {} (not printed)", name, other),
+        }
+    }
 
-    for (i, map_val) in prob_sequence.iter().enumerate() {
-    let prob_map: HashMap<i64, f32> = map_val.try_extract_map::<i64, f32>()?;
-    println!("--- Probability map {} ---", i + 1);
+    Ok(())
+}
 
-    for (class, prob) in &prob_map {
+fn print_probabilities<K: std::fmt::Display>(name: &str, prob_map: &BTreeMap<K, f32>) {
+    println!("--- {} ---", name);
+    for (class, prob) in prob_map {
         println!("Class {} → Probability {:.3}", class, prob);
     }
-
     if let Some((best_class, best_prob)) = prob_map.iter().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()) {
         println!("Most likely class: {}, Probability: {:.3}", best_class, best_prob);
     }
 }
-
-    // for (i, map_val) in prob_sequence.iter().enumerate() {
-    //     let prob_map = map_val.try_extract_map::<i64, f32>(allocator)?;
-    //     println!("--- Probability map {} ---", i + 1);
-
-    //     for (class, prob) in prob_map.iter() {
-    //         println!("Class {} → Probability {:.3}", class, prob);
-    //     }
-
-    //     if let Some((best_class, best_prob)) =
-    //         prob_map.iter().max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-    //     {
-    //         println!(
-    //             "Most likely class: {}, Probability: {:.3}",
-    //             best_class, best_prob
-    //         );
-    //     }
-    // }
-
-    Ok(())
-}
\ No newline at end of file
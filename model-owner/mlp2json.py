@@ -0,0 +1,124 @@
+import json
+import sys
+
+import onnx
+from onnx import numpy_helper
+
+# Convert an ONNX MLP export (sklearn MLPClassifier/MLPRegressor via skl2onnx,
+# or a torch nn.Sequential of Linear + activations) into a model 7 spec for Zk-host.
+#
+# usage: python mlp2json.py model.onnx [mlp.json]
+
+onnx_path = sys.argv[1] if len(sys.argv) > 1 else "mlp.onnx"
+out_path = sys.argv[2] if len(sys.argv) > 2 else "mlp.json"
+
+model = onnx.load(onnx_path)
+graph = model.graph
+inits = {t.name: numpy_helper.to_array(t) for t in graph.initializer}
+
+def get_attr(node, name, default=None):
+    for attr in node.attribute:
+        if attr.name == name:
+            return onnx.helper.get_attribute_value(attr)
+    return default
+
+ACTIVATIONS = {"Relu": "relu", "Sigmoid": "sigmoid", "Tanh": "tanh"}
+# Shape/dtype plumbing that doesn't change values
+PASSTHROUGH = {"Identity", "Cast", "Flatten", "Reshape"}
+# Label/probability post-processing added by skl2onnx after the network.
+POSTPROCESS = {"ArgMax", "ArrayFeatureExtractor", "ZipMap"}
+
+layers = []
+head = "linear"
+done = False
+skipped = []
+# A binary classifier ends in one sigmoid unit p followed by Sub (1 - p) and
+# Concat [1 - p, p]: the names p goes by, and 1 - p once seen
+sigmoid_out = set()
+complement = None
+binary_head = False
+
+def is_one(name):
+    return name in inits and bool((inits[name] == 1).all())
+
+for node in graph.node:
+    op = node.op_type
+    if done:
+        skipped.append(op)
+        continue
+    if op == "Gemm":
+        w = inits[node.input[1]].astype(float)
+        if get_attr(node, "transA", 0):
+            raise SystemExit("Gemm with transA is not supported")
+        # our layout is out_dim x in_dim, Gemm's B is in x out unless transB
+        if not get_attr(node, "transB", 0):
+            w = w.T
+        w = w * get_attr(node, "alpha", 1.0)
+        if len(node.input) > 2 and node.input[2] in inits:
+            b = inits[node.input[2]].astype(float).reshape(-1) * get_attr(node, "beta", 1.0)
+        else:
+            b = [0.0] * w.shape[0]
+        layers.append({"weights": w.reshape(-1).tolist(), "biases": list(map(float, b)), "activation": "identity"})
+        sigmoid_out = set()
+    elif op == "MatMul":
+        w = inits[node.input[1]].astype(float).T
+        sigmoid_out = set()
+        layers.append({"weights": w.reshape(-1).tolist(), "biases": [0.0] * w.shape[0], "activation": "identity"})
+    elif op == "Add" and layers and node.input[1] in inits:
+        b = inits[node.input[1]].astype(float).reshape(-1)
+        layers[-1]["biases"] = [x + float(y) for x, y in zip(layers[-1]["biases"], b)]
+    elif op in ACTIVATIONS and layers:
+        layers[-1]["activation"] = ACTIVATIONS[op]
+        if op == "Sigmoid" and len(layers[-1]["biases"]) == 1:
+            sigmoid_out = set(node.output)
+    elif op == "Softmax":
+        head = "softmax"
+        done = True
+    elif op == "Sub" and len(node.input) == 2 and node.input[1] in sigmoid_out and is_one(node.input[0]):
+        complement = node.output[0]
+    elif op == "Concat" and len(node.input) == 2 and node.input[0] == complement and node.input[1] in sigmoid_out:
+        binary_head = True
+        done = True
+    elif op in PASSTHROUGH:
+        if any(name in sigmoid_out for name in node.input):
+            sigmoid_out.update(node.output)
+        continue
+    elif op in POSTPROCESS and layers:
+        done = True
+        skipped.append(op)
+    else:
+        raise SystemExit(f"Unsupported op for MLP export: {op} ({node.name})")
+
+if not layers:
+    raise SystemExit("No dense layers found in the graph")
+
+if binary_head:
+    # softmax([-z/2, z/2]) = [1 - sigmoid(z), sigmoid(z)], so [1 - p, p] becomes
+    # a two-class softmax head, which Zk-host scores as a classifier
+    last = layers[-1]
+    last["weights"] = [-v / 2 for v in last["weights"]] + [v / 2 for v in last["weights"]]
+    last["biases"] = [-last["biases"][0] / 2, last["biases"][0] / 2]
+    last["activation"] = "identity"
+    head = "softmax"
+
+num_features = len(layers[0]["weights"]) // len(layers[0]["biases"])
+
+spec = {
+    "model_type": 7,
+    "num_features": num_features,
+    "head": head,
+    "layers": layers,
+}
+
+with open(out_path, "w") as f:
+    json.dump(spec, f, indent=2)
+
+shapes = " -> ".join(
+    [str(num_features)] + [f'{len(l["biases"])} ({l["activation"]})' for l in layers]
+)
+print(f"MLP {shapes}, head {head}")
+if binary_head:
+    print("Binary sigmoid output [1 - p, p] written as a two-class softmax head")
+if skipped:
+    print(f"Ignored post-processing ops: {', '.join(skipped)}")
+print(f"Model spec saved as {out_path}")
@@ -1,60 +1,117 @@
-import onnx
 import json
+import sys
+from collections import defaultdict
+
+import onnx
+
+# Convert an ONNX TreeEnsembleClassifier/Regressor (single tree, random forest or
+# gradient boosting) into the flat ensemble document read by the guest (model 8):
+#
+#   { "aggregate": "SUM", "post_transform": "NONE", "base_values": [...],
+#     "nodes": [ { "tree_id", "id", "feature", "threshold", "mode",
+#                  "missing_tracks_true", "left", "right", "value" }, ... ] }
+#
+# "left" is the true branch, "right" the false branch. Leaves have no feature and
+# carry their per-class (or per-target) weights in value[0].
+#
+# usage: python tree2json.py [model.onnx] [tree.json]
 
-model = onnx.load("iris_tree_copy.onnx")
+onnx_path = sys.argv[1] if len(sys.argv) > 1 else "iris_tree_copy.onnx"
+out_path = sys.argv[2] if len(sys.argv) > 2 else "tree.json"
+
+model = onnx.load(onnx_path)
 
 # Get the tree node
 tree_node = next(
     n for n in model.graph.node if n.op_type in ["TreeEnsembleClassifier", "TreeEnsembleRegressor"]
 )
+is_classifier = tree_node.op_type == "TreeEnsembleClassifier"
 
 # Extract attributes
-def get_attr(node, name):
+def get_attr(node, name, default=None):
     for attr in node.attribute:
         if attr.name == name:
-            return attr
-    return None
-
-node_ids = list(get_attr(tree_node, "nodes_nodeids").ints)
-tree_ids = list(get_attr(tree_node, "nodes_treeids").ints)
-features = list(get_attr(tree_node, "nodes_featureids").ints)
-thresholds = list(get_attr(tree_node, "nodes_values").floats)
-modes = [s.decode() for s in get_attr(tree_node, "nodes_modes").strings]
-true_ids = list(get_attr(tree_node, "nodes_truenodeids").ints)
-false_ids = list(get_attr(tree_node, "nodes_falsenodeids").ints)
-leaf_weights_attr = get_attr(tree_node, "target_weights")
-leaf_weights = list(leaf_weights_attr.floats) if leaf_weights_attr else [None] * len(node_ids)
-
-# Build node dictionary
-nodes = {}
+            return onnx.helper.get_attribute_value(attr)
+    return default
+
+def decode(v):
+    return v.decode() if isinstance(v, bytes) else v
+
+node_ids = list(get_attr(tree_node, "nodes_nodeids"))
+tree_ids = list(get_attr(tree_node, "nodes_treeids"))
+features = list(get_attr(tree_node, "nodes_featureids"))
+thresholds = list(get_attr(tree_node, "nodes_values"))
+modes = [decode(s) for s in get_attr(tree_node, "nodes_modes")]
+true_ids = list(get_attr(tree_node, "nodes_truenodeids"))
+false_ids = list(get_attr(tree_node, "nodes_falsenodeids"))
+missing = list(get_attr(tree_node, "nodes_missing_value_tracks_true", [0] * len(node_ids)))
+
+# Leaf weights live in parallel arrays keyed by (tree id, node id)
+prefix = "class" if is_classifier else "target"
+leaf_tree_ids = list(get_attr(tree_node, f"{prefix}_treeids", []))
+leaf_node_ids = list(get_attr(tree_node, f"{prefix}_nodeids", []))
+leaf_out_ids = list(get_attr(tree_node, f"{prefix}_ids", []))
+leaf_weights = list(get_attr(tree_node, f"{prefix}_weights", []))
+
+if is_classifier:
+    labels = get_attr(tree_node, "classlabels_int64s") or get_attr(tree_node, "classlabels_strings")
+    n_outputs = len(labels)
+    aggregate = "SUM"
+else:
+    n_outputs = get_attr(tree_node, "n_targets", 1)
+    aggregate = decode(get_attr(tree_node, "aggregate_function", b"SUM"))
+
+# A binary classifier may carry weights for a single class (class 1 for random
+# forests, class 0 for GradientBoosting, XGBoost and LightGBM); the guest
+# expands the single score back to two like onnxruntime
+if is_classifier and n_outputs == 2 and len(set(leaf_out_ids)) == 1:
+    n_outputs = 1
+    leaf_out_ids = [0] * len(leaf_out_ids)
+
+leaf_values = defaultdict(lambda: [0.0] * n_outputs)
+for t, n, k, w in zip(leaf_tree_ids, leaf_node_ids, leaf_out_ids, leaf_weights):
+    leaf_values[(t, n)][k] += w
+
+# Build flat node list
+nodes = []
 for i, nid in enumerate(node_ids):
-    nodes[nid] = {
-        "node_id": nid,
-        "feature": features[i],
-        "threshold": thresholds[i],
-        "mode": modes[i],
-        "left": true_ids[i] if true_ids[i] != -1 else None,
-        "right": false_ids[i] if false_ids[i] != -1 else None,
-        "weight": leaf_weights[i]
-    }
-
-# Recursive function to build nested tree
-def build_tree(node_id):
-    node = nodes[node_id]
-    if node["mode"] == "LEAF":
-        return {"weight": node["weight"]}
-    return {
-        "feature": node["feature"],
-        "threshold": node["threshold"],
-        "left": build_tree(node["left"]) if node["left"] is not None else None,
-        "right": build_tree(node["right"]) if node["right"] is not None else None
-    }
-
-# Assuming tree_id 0 and root node_id 0
-nested_tree = build_tree(0)
+    tid = tree_ids[i]
+    if modes[i] == "LEAF":
+        nodes.append({
+            "tree_id": tid,
+            "id": nid,
+            "feature": None,
+            "threshold": None,
+            "mode": "LEAF",
+            "missing_tracks_true": False,
+            "left": None,
+            "right": None,
+            "value": [leaf_values[(tid, nid)]],
+        })
+    else:
+        nodes.append({
+            "tree_id": tid,
+            "id": nid,
+            "feature": features[i],
+            "threshold": thresholds[i],
+            "mode": modes[i],
+            "missing_tracks_true": bool(missing[i]),
+            "left": true_ids[i],
+            "right": false_ids[i],
+            "value": [],
+        })
+
+ensemble = {
+    "aggregate": aggregate,
+    "post_transform": decode(get_attr(tree_node, "post_transform", b"NONE")),
+    "base_values": list(get_attr(tree_node, "base_values", [])),
+    "nodes": nodes,
+}
 
 # Save JSON
-with open("tree.json", "w") as f:
-    json.dump(nested_tree, f, indent=4)
+with open(out_path, "w") as f:
+    json.dump(ensemble, f, indent=4)
 
-print("Nested decision tree saved as tree.json")
+print(f"{len(set(tree_ids))} tree(s), {len(nodes)} nodes, {n_outputs} output(s), "
+      f"aggregate {ensemble['aggregate']}, post_transform {ensemble['post_transform']}")
+print(f"Tree ensemble saved as {out_path}")
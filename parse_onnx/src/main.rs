@@ -1,6 +1,7 @@
 use std::fs;
 use std::collections::HashMap;
 use serde::Deserialize;
+use verse_common::tree::{takes_true_branch, SplitMode};
 
 #[derive(Debug, Deserialize)]
 struct Node {
@@ -10,11 +11,15 @@ struct Node {
     left: Option<usize>,
     right: Option<usize>,
     value: Option<Vec<Vec<f32>>>,
+    #[serde(default)]
+    mode: SplitMode,
+    #[serde(default)]
+    missing_tracks_true: bool,
 }
 
 fn predict(tree: &HashMap<usize, Node>, features: &[f32], node_id: usize) -> usize {
     let node = &tree[&node_id];
-    if node.feature.is_none() {
+    if node.feature.is_none() || node.mode == SplitMode::Leaf {
         let row = &node.value.as_ref().unwrap()[0];
         let (idx, _) = row.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap();
         return idx;
@@ -22,7 +27,8 @@ fn predict(tree: &HashMap<usize, Node>, features: &[f32], node_id: usize) -> usi
 
     let feature_idx = node.feature.unwrap();
     let threshold = node.threshold.unwrap();
-    if features[feature_idx] <= threshold {
+    let x = features[feature_idx] as f64;
+    if takes_true_branch(node.mode, node.missing_tracks_true, x, threshold as f64) {
         predict(tree, features, node.left.unwrap())
     } else {
         predict(tree, features, node.right.unwrap())
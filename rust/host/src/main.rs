@@ -3,6 +3,7 @@ use methods::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
 use risc0_zkvm::{default_prover, ExecutorEnv};
 use std::fs;
 use std::time::Instant;
+use verse_common::flat_tree;
 
 fn main() {
     // Get tree path from first CLI arg or default to "tree.json"
@@ -19,11 +20,27 @@ fn main() {
     let preview: String = tree_json.chars().take(120).collect();
     println!("[host] JSON preview: {}{}", preview, if tree_json.len() > 120 { "..." } else { "" });
 
+    // The guest walks a flattened tree unless VERSE_TREE_FORMAT=json
+    let send_json = std::env::var("VERSE_TREE_FORMAT")
+        .map(|v| v.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
     println!("[host] Building zkVM executor environment...");
-    let env = ExecutorEnv::builder()
-        .write(&tree_path).unwrap()
-        .write(&tree_json).unwrap()
-        .build().unwrap();
+    let mut builder = ExecutorEnv::builder();
+    builder.write(&tree_path).unwrap();
+    if send_json {
+        println!("[host] Sending tree as JSON");
+        builder.write(&0u32).unwrap();
+        builder.write(&tree_json).unwrap();
+    } else {
+        let words = flat_tree::flatten_tree_json(&tree_json, flat_tree::LeafValues::ClassScores)
+            .unwrap_or_else(|e| panic!("{}", e));
+        println!("[host] Flattened tree to {} words ({} bytes)", words.len(), words.len() * 4);
+        builder.write(&1u32).unwrap();
+        builder.write(&(words.len() as u32)).unwrap();
+        builder.write_slice(&words);
+    }
+    let env = builder.build().unwrap();
     println!("[host] Executor environment ready.");
 
     let prover = default_prover();
@@ -33,6 +50,7 @@ fn main() {
         .prove(env, GUEST_CODE_FOR_ZK_PROOF_ELF)
         .unwrap();
     println!("[host] Proof generated in {:.2?}", prove_start.elapsed());
+    println!("[host] Cycle count: {} (user: {})", prove_info.stats.total_cycles, prove_info.stats.user_cycles);
 
     let receipt = prove_info.receipt;
     println!("[host] Decoding journal to predictions...");
@@ -7,6 +7,8 @@ extern crate alloc;
 use alloc::{vec, vec::Vec};
 use risc0_zkvm::guest::env;
 use serde::{Deserialize, Serialize};
+use verse_common::flat_tree::FlatTrees;
+use verse_common::tree::{takes_true_branch, SplitMode};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TreeNode {
@@ -16,6 +18,10 @@ pub struct TreeNode {
     pub left: Option<usize>,      // None if leaf
     pub right: Option<usize>,     // None if leaf
     pub value: Vec<Vec<f64>>,     // non-empty if leaf
+    #[serde(default)]
+    pub mode: SplitMode,          // BRANCH_LEQ unless the export says otherwise
+    #[serde(default)]
+    pub missing_tracks_true: bool, // NaN goes to `left` when set
 }
 
 #[derive(Debug)]
@@ -52,7 +58,7 @@ fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec
         if idx == usize::MAX { panic!("Unmapped node id"); }
         let node = &nodes[idx];
 
-        if node.feature.is_none() {
+        if node.feature.is_none() || node.mode == SplitMode::Leaf {
             return node.value[0].clone();
         }
 
@@ -60,7 +66,7 @@ fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec
         let thr = node.threshold.unwrap();
         let xf = x[feat];
 
-        if xf <= thr {
+        if takes_true_branch(node.mode, node.missing_tracks_true, xf, thr) {
             current_id = node.left.expect("Missing left child");
         } else {
             current_id = node.right.expect("Missing right child");
@@ -68,22 +74,41 @@ fn traverse_tree(nodes: &Vec<TreeNode>, id_index: &Vec<usize>, x: &[f64]) -> Vec
     }
 }
 
+// Flattened tree sent by the host (verse-common/src/flat_tree.rs has the
+// layout and the walk): the host resolves node ids and leaf values so the tree
+// is walked in place rather than parsed from JSON. Only single trees are
+// handled here.
+const TREE_FORMAT_FLAT: u32 = 1;
+
 risc0_zkvm::guest::entry!(main);
 
 fn main() {
     let _tree_path: alloc::string::String = env::read();
-    let tree_json: alloc::string::String = env::read();
-    let tree: Vec<TreeNode> = match serde_json::from_str(&tree_json) {
-        Ok(t) => t,
-        Err(_) => panic!("Failed to parse tree JSON in guest"),
-    };
-    let id_index = build_id_index(&tree);
+    let tree_format: u32 = env::read();
     let dataset = get_dataset();
     let mut predictions = Vec::new();
 
-    for sample in dataset.iter() {
-        let pred = traverse_tree(&tree, &id_index, &sample.features);
-        predictions.push((pred, sample.expected));
+    if tree_format == TREE_FORMAT_FLAT {
+        let len: u32 = env::read();
+        let mut words = vec![0u32; len as usize];
+        env::read_slice(&mut words);
+        let trees = FlatTrees::parse(&words).unwrap_or_else(|e| panic!("{}", e));
+        assert!(trees.num_trees == 1, "Expected a single tree");
+        for sample in dataset.iter() {
+            let leaf = trees.find_leaf(0, &sample.features).unwrap_or_else(|e| panic!("{}", e));
+            predictions.push((trees.leaf_values(leaf), sample.expected));
+        }
+    } else {
+        let tree_json: alloc::string::String = env::read();
+        let tree: Vec<TreeNode> = match serde_json::from_str(&tree_json) {
+            Ok(t) => t,
+            Err(_) => panic!("Failed to parse tree JSON in guest"),
+        };
+        let id_index = build_id_index(&tree);
+        for sample in dataset.iter() {
+            let pred = traverse_tree(&tree, &id_index, &sample.features);
+            predictions.push((pred, sample.expected));
+        }
     }
 
     env::commit(&predictions);
@@ -1,6 +1,7 @@
 use std::fs;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use verse_common::tree::{takes_true_branch, SplitMode};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TreeNode {
@@ -14,6 +15,10 @@ struct TreeNode {
     #[serde(default)]
     right: Option<usize>,
     value: Vec<Vec<f64>>,
+    #[serde(default)]
+    mode: SplitMode,
+    #[serde(default)]
+    missing_tracks_true: bool,
 }
 
 fn main() {
@@ -39,7 +44,7 @@ fn main() {
 
     #[derive(Debug, Clone)]
     struct Sample { features: Vec<f64>, expected: usize }
-    let test_set = vec![
+    let test_set = [
         Sample { features: vec![5.1, 3.5, 1.4, 0.2], expected: 0 },
         Sample { features: vec![4.9, 3.0, 1.4, 0.2], expected: 0 },
         Sample { features: vec![6.0, 2.2, 4.0, 1.0], expected: 1 },
@@ -52,13 +57,13 @@ fn main() {
         loop {
             let idx = *id_index.get(&current_id).expect("Tree refers to unknown node id");
             let node = &nodes[idx];
-            if node.feature.is_none() {
-                return node.value.get(0).cloned().unwrap_or_default();
+            if node.feature.is_none() || node.mode == SplitMode::Leaf {
+                return node.value.first().cloned().unwrap_or_default();
             }
             let f = node.feature.unwrap();
             let thr = node.threshold.unwrap_or(0.0);
-            let xf = x.get(f).copied().unwrap_or(0.0);
-            current_id = if xf <= thr {
+            let xf = x.get(f).copied().unwrap_or(f64::NAN);
+            current_id = if takes_true_branch(node.mode, node.missing_tracks_true, xf, thr) {
                 node.left.expect("Missing left child id")
             } else {
                 node.right.expect("Missing right child id")
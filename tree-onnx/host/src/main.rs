@@ -2,7 +2,8 @@
 use methods::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
 use ort::{
     session::{builder::GraphOptimizationLevel, Session},
-    value::{DynValue, Tensor},
+    tensor::TensorElementType,
+    value::{DynValue, MapValueType, Tensor, ValueType},
 };
 use risc0_zkvm::{default_prover, ExecutorEnv};
 use anyhow::Result;
@@ -14,7 +15,36 @@ struct Sample {
     expected: i64,
 }
 
+// Class names in index order, from the `class_labels` of a model spec (as
+// written by `verse model import`): the path given as the first argument,
+// else spec.json when it exists (this directory ships one for
+// iris_tree_model.onnx). Without one, classes print as indices.
+fn load_class_labels() -> Result<Vec<String>> {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None if std::path::Path::new("spec.json").exists() => "spec.json".to_string(),
+        None => return Ok(Vec::new()),
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| anyhow::anyhow!("failed to read {}: {}", path, e))?;
+    let spec: serde_json::Value = serde_json::from_str(&text)?;
+    Ok(spec
+        .get("class_labels")
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|x| x.as_str().map(str::to_string)).collect())
+        .unwrap_or_default())
+}
+
+// "name (index)" when the class has a label, else "class index".
+fn class_name(labels: &[String], index: i64) -> String {
+    match usize::try_from(index).ok().and_then(|i| labels.get(i)) {
+        Some(name) => format!("{} ({})", name, index),
+        None => format!("class {}", index),
+    }
+}
+
 fn main() -> Result<()> {
+    let class_labels = load_class_labels()?;
+
     // Step 1: Run guest to get validation data
     let env = ExecutorEnv::builder().build()?;
     let prover = default_prover();
@@ -29,48 +59,59 @@ fn main() -> Result<()> {
         .with_optimization_level(GraphOptimizationLevel::Level3)?
         .commit_from_file("iris_tree_model.onnx")?;
 
-    let iris_classes = ["Iris-setosa", "Iris-versicolor", "Iris-virginica"];
+    // Input and output names come from the model: the label is the int64
+    // output, the probabilities the sequence of maps (ZipMap)
+    let input_name = model.inputs[0].name.clone();
+    let label_name = model
+        .outputs
+        .iter()
+        .find(|o| matches!(o.output_type, ValueType::Tensor { ty: TensorElementType::Int64, .. }))
+        .map(|o| o.name.clone())
+        .ok_or_else(|| anyhow::anyhow!("model has no int64 label output"))?;
+    let prob_name = model
+        .outputs
+        .iter()
+        .find(|o| matches!(o.output_type, ValueType::Sequence(_)))
+        .map(|o| o.name.clone());
     let mut correct = 0;
 
     // Step 4: Run inference for each sample
     for (i, sample) in validation_data.iter().enumerate() {
-        let shape = [1usize, 4usize];
+        let shape = [1usize, sample.features.len()];
         let input_tensor = Tensor::from_array((shape, sample.features.clone().into_boxed_slice()))?;
 
-        let mut outputs = model.run(ort::inputs!["float_input" => input_tensor])?;
+        let mut outputs = model.run(ort::inputs![input_name.as_str() => input_tensor])?;
 
-        let label_value: DynValue = outputs.remove("output_label").unwrap();
-        let prob_value: DynValue = outputs.remove("output_probability").unwrap();
+        let label_value: DynValue = outputs.remove(label_name.as_str()).unwrap();
+        let prob_value: Option<DynValue> = prob_name.as_deref().and_then(|name| outputs.remove(name));
+        drop(outputs);
 
-        // Extract predicted class index
+        // Extract predicted class label
         let predicted_indices = label_value.try_extract_array::<i64>()?;
-        let predicted_index = predicted_indices[0] as usize;
-
-        // Class mapping
-        let predicted_class = iris_classes
-            .get(predicted_index)
-            .unwrap_or(&"Unknown class");
+        let predicted = predicted_indices[0];
 
         // Check accuracy
-        if predicted_index == sample.expected as usize {
+        if predicted == sample.expected {
             correct += 1;
         }
 
         println!(
-            "\nSample {}:\n  Features: {:?}\n  Expected: {} ({})\n  Predicted: {} ({})",
+            "\nSample {}:\n  Features: {:?}\n  Expected: {}\n  Predicted: {}",
             i + 1,
             sample.features,
-            sample.expected,
-            iris_classes[sample.expected as usize],
-            predicted_index,
-            predicted_class
+            class_name(&class_labels, sample.expected),
+            class_name(&class_labels, predicted)
         );
 
-        // ✅ Fixed API call here
-        if let Ok(prob_map) = prob_value.try_extract_map::<i64, f32>() {
-            println!("  Probabilities:");
-            for (cls, prob) in prob_map.iter() {
-                println!("    {:<15} ({}) → {:.4}", iris_classes[*cls as usize], cls, prob);
+        if let Some(prob_value) = prob_value {
+            let prob_sequence = prob_value.try_extract_sequence::<MapValueType<i64, f32>>(model.allocator())?;
+            if let Some(map_val) = prob_sequence.first() {
+                let mut probs = map_val.try_extract_key_values::<i64, f32>()?;
+                probs.sort_by_key(|(cls, _)| *cls);
+                println!("  Probabilities:");
+                for (cls, prob) in probs.iter() {
+                    println!("    {:<24} → {:.4}", class_name(&class_labels, *cls), prob);
+                }
             }
         }
     }
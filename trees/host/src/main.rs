@@ -218,50 +218,122 @@
 
 
 
-use risc0_zkvm::{Prover, Receipt};
-use std::process::Command;
-use std::fs;
-use serde::Deserialize;
-use bincode;
-
-// Import the guest ELF and its ID
-use zkml_project::methods::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
+mod preprocess;
 
-// Structure matching the validation inputs JSON
-#[derive(Deserialize)]
-struct Inputs {
-    inputs: Vec<Vec<i32>>,
+use methods::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
+use preprocess::{preprocess_tree, quantize_sample};
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use std::fs;
+use std::time::Instant;
+
+// Validation CSV: one sample per row, the features followed by the expected
+// class. An empty feature cell is a missing value. A header row is skipped.
+fn load_validation_csv(path: &str) -> Result<(Vec<Vec<f64>>, Vec<u32>), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut inputs: Vec<Vec<f64>> = Vec::new();
+    let mut labels = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+        let (label, features) = cells.split_last().unwrap();
+        let label: u32 = match label.parse() {
+            Ok(l) => l,
+            Err(_) if line_no == 0 => continue,
+            Err(_) => return Err(format!("{}:{}: invalid class label {:?}", path, line_no + 1, label)),
+        };
+        let row = features
+            .iter()
+            .map(|c| {
+                if c.is_empty() {
+                    Ok(f64::NAN)
+                } else {
+                    c.parse::<f64>()
+                        .map_err(|_| format!("{}:{}: invalid feature {:?}", path, line_no + 1, c))
+                }
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        if let Some(first) = inputs.first() {
+            if row.len() != first.len() {
+                return Err(format!(
+                    "{}:{}: expected {} features, got {}",
+                    path,
+                    line_no + 1,
+                    first.len(),
+                    row.len()
+                ));
+            }
+        }
+        inputs.push(row);
+        labels.push(label);
+    }
+    if inputs.is_empty() {
+        return Err(format!("{} has no samples", path));
+    }
+    Ok((inputs, labels))
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 1️⃣ Run Python preprocessing
-    println!("Running Python preprocessing...");
-    let status = Command::new("python")
-        .arg("../python/preprocess.py") // adjust path if needed
-        .status()?;
-    if !status.success() {
-        panic!("Python preprocessing failed");
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    // usage: host [tree.json] [validation.csv]
+    let mut args = std::env::args().skip(1);
+    let tree_path = args.next().unwrap_or_else(|| "tree.json".to_string());
+    let csv_path = args.next().unwrap_or_else(|| "validation.csv".to_string());
+
+    let tree_json = fs::read_to_string(&tree_path).map_err(|e| format!("Failed to read {}: {}", tree_path, e))?;
+    let (inputs, labels) = load_validation_csv(&csv_path)?;
+    let num_features = inputs[0].len();
+
+    let pre = preprocess_tree(&tree_json, num_features)?;
+    let tree = &pre.tree;
+    println!(
+        "[host] {}: depth {}, {} internal positions, {} leaves, thresholds at 2^{} scale",
+        tree_path,
+        tree.depth,
+        tree.features.len(),
+        tree.leaves.len(),
+        tree.scale_bits
+    );
+
+    let samples: Vec<_> = inputs.iter().map(|x| quantize_sample(x, tree.scale_bits)).collect();
+    let changed = pre.changed_samples(&inputs, &samples);
+    if changed.is_empty() {
+        println!("[host] Quantization keeps the f64 prediction for all {} samples", samples.len());
+    } else {
+        println!("[host] Quantization changes the prediction for samples {:?}", changed);
     }
 
-    // 2️⃣ Read preprocessed validation inputs
-    let json_str = fs::read_to_string("../validation_inputs.json")?;
-    let inputs: Inputs = serde_json::from_str(&json_str)?;
-
-    // 3️⃣ Serialize inputs for guest
-    let input_bytes = bincode::serialize(&inputs)?;
+    let env = ExecutorEnv::builder().write(tree)?.write(&samples)?.build()?;
 
-    // 4️⃣ Create prover using the official guest ELF constant
-    println!("Running guest zkVM...");
-    let mut prover = Prover::new(GUEST_CODE_FOR_ZK_PROOF_ELF, &input_bytes)?;
-    let receipt: Receipt = prover.run()?;
+    println!("[host] Starting proof generation...");
+    let prove_start = Instant::now();
+    let prove_info = default_prover().prove(env, GUEST_CODE_FOR_ZK_PROOF_ELF)?;
+    println!("[host] Proof generated in {:.2?}", prove_start.elapsed());
 
-    // 5️⃣ Verify proof using the ELF ID
-    println!("Verifying proof...");
-    receipt.verify_with_id(&GUEST_CODE_FOR_ZK_PROOF_ID)?;
+    let receipt = prove_info.receipt;
+    let predictions: Vec<u32> = receipt.journal.decode()?;
 
-    // 6️⃣ Read predictions from guest journal
-    let predictions: Vec<i32> = bincode::deserialize(&receipt.get_journal_bytes()?)?;
-    println!("Verified predictions: {:?}", predictions);
+    println!("Sample | Predicted | Expected");
+    println!("---------------------------");
+    let mut correct = 0;
+    for (i, (pred, expected)) in predictions.iter().zip(labels.iter()).enumerate() {
+        println!("{:<6} | {:<9} | {}", i, pred, expected);
+        if pred == expected {
+            correct += 1;
+        }
+    }
+    println!("Accuracy: {}/{}", correct, predictions.len());
+    println!(
+        "Cycle count: {} (user: {}); every sample runs the same {} levels",
+        prove_info.stats.total_cycles, prove_info.stats.user_cycles, tree.depth
+    );
+
+    receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID)?;
+    println!("✅ Receipt verified");
 
     Ok(())
 }
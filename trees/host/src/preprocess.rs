@@ -0,0 +1,354 @@
+// Rust replacement for python/preprocess.py. Turns a decision tree JSON (the
+// sklearn node list in rust/tree.json, or a single-tree ensemble document from
+// model-owner/tree2json.py / `verse model import`) into the level arrays the
+// guest evaluates, and quantizes the validation inputs to match.
+//
+// The tree is padded to a complete binary tree of depth `depth` and stored in
+// heap order: node p has children 2p + 1 (left) and 2p + 2 (right), level l
+// holds positions 2^l - 1 .. 2^(l+1) - 2 and the 2^depth leaves follow the
+// last level. A leaf above the last level becomes pass-through nodes that always
+// go left, with copies of the leaf below them, so every sample takes exactly
+// `depth` steps.
+//
+// Every split is normalised to "go right iff x_q > threshold_q":
+//   BRANCH_LEQ  x <= t   left = true branch,  threshold_q = floor(t * 2^s)
+//   BRANCH_LT   x <  t   left = true branch,  threshold_q = floor(t * 2^s) - 1
+//   BRANCH_GTE  x >= t   children swapped,    threshold_q = floor(t * 2^s) - 1
+//   BRANCH_GT   x >  t   children swapped,    threshold_q = floor(t * 2^s)
+// with features quantized as x_q = floor(x * 2^s). BRANCH_EQ / BRANCH_NEQ have
+// no such form and are rejected. The quantized comparison can only disagree
+// with the f64 one when floor(x * 2^s) == floor(t * 2^s); `changed_samples`
+// replays both on the host and reports the samples whose predicted class differs.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use verse_common::tree::{takes_true_branch, LeafValues, SplitMode, TreeEnsemble, TreeNode};
+
+// Deepest tree accepted; the padded arrays grow as 2^depth.
+pub const MAX_DEPTH: usize = 20;
+// Largest |threshold_q|, leaving headroom for the -1 and for saturated features.
+const QUANT_LIMIT: f64 = (1u64 << 30) as f64;
+
+// Guest input; field order matches the guest's ObliviousTree.
+#[derive(Debug, Serialize)]
+pub struct ObliviousTree {
+    pub depth: u32,
+    pub num_features: u32,
+    pub scale_bits: u32,
+    // one entry per internal position, 2^depth - 1 of each
+    pub features: Vec<u32>,
+    pub thresholds: Vec<i32>,
+    pub missing_left: Vec<u32>,
+    // predicted class per leaf position, 2^depth
+    pub leaves: Vec<u32>,
+}
+
+// Guest input; field order matches the guest's QuantizedSample.
+// `missing` is 1 where the feature was NaN (its x_q is 0 and unused).
+#[derive(Debug, Serialize)]
+pub struct QuantizedSample {
+    pub x_q: Vec<i32>,
+    pub missing: Vec<u32>,
+}
+
+pub struct Preprocessed {
+    pub tree: ObliviousTree,
+    nodes: Vec<TreeNode>,
+    index: HashMap<usize, usize>,
+}
+
+fn argmax(values: &[f64]) -> u32 {
+    let mut best = 0;
+    for (i, v) in values.iter().enumerate() {
+        if *v > values[best] {
+            best = i;
+        }
+    }
+    best as u32
+}
+
+fn quantize(v: f64, scale_bits: u32) -> i32 {
+    // `as` saturates, so out-of-range features still compare correctly
+    (v * (1u64 << scale_bits) as f64).floor() as i32
+}
+
+fn is_leaf(n: &TreeNode) -> bool {
+    n.feature.is_none() || n.mode == SplitMode::Leaf
+}
+
+pub fn preprocess_tree(tree_json: &str, num_features: usize) -> Result<Preprocessed, String> {
+    let nodes = TreeEnsemble::from_json(tree_json)?.nodes;
+    if nodes.iter().any(|n| n.tree_id != 0) {
+        return Err("Tree ensembles are not supported here; give a single tree".into());
+    }
+    let mut index = HashMap::with_capacity(nodes.len());
+    for (pos, n) in nodes.iter().enumerate() {
+        if index.insert(n.id, pos).is_some() {
+            return Err(format!("Duplicate node id {}", n.id));
+        }
+    }
+    if !index.contains_key(&0) {
+        return Err("Tree has no root (node id 0)".into());
+    }
+
+    // Depth, and checks on every reachable node
+    let mut depth = 0usize;
+    let mut max_abs = 0.0f64;
+    let mut stack = vec![(0usize, 0usize)];
+    while let Some((id, d)) = stack.pop() {
+        if d > MAX_DEPTH {
+            return Err(format!("Tree is deeper than {} levels", MAX_DEPTH));
+        }
+        let n = &nodes[*index.get(&id).ok_or_else(|| format!("Unknown node id {}", id))?];
+        if is_leaf(n) {
+            if n.leaf_values(LeafValues::ClassScores).is_empty() {
+                return Err(format!("Leaf {} has no values", n.id));
+            }
+            depth = depth.max(d);
+            continue;
+        }
+        let feature = n.feature.unwrap_or_default();
+        if feature >= num_features {
+            return Err(format!("Tree splits on feature {} but inputs have {}", feature, num_features));
+        }
+        if matches!(n.mode, SplitMode::BranchEq | SplitMode::BranchNeq) {
+            return Err(format!("Node {}: {:?} splits are not supported", n.id, n.mode));
+        }
+        let thr = n.threshold.ok_or_else(|| format!("Node {} has no threshold", n.id))?;
+        if !thr.is_finite() {
+            return Err(format!("Node {} has a non-finite threshold", n.id));
+        }
+        max_abs = max_abs.max(thr.abs());
+        let left = n.left.ok_or_else(|| format!("Node {} is missing a child", n.id))?;
+        let right = n.right.ok_or_else(|| format!("Node {} is missing a child", n.id))?;
+        stack.push((left, d + 1));
+        stack.push((right, d + 1));
+    }
+    if max_abs >= QUANT_LIMIT {
+        return Err(format!("Threshold {} is too large to quantize to i32", max_abs));
+    }
+    let mut scale_bits = 30u32;
+    while scale_bits > 0 && max_abs * (1u64 << scale_bits) as f64 >= QUANT_LIMIT {
+        scale_bits -= 1;
+    }
+
+    let internal = (1usize << depth) - 1;
+    let mut tree = ObliviousTree {
+        depth: depth as u32,
+        num_features: num_features as u32,
+        scale_bits,
+        features: vec![0; internal],
+        thresholds: vec![i32::MAX; internal],
+        missing_left: vec![1; internal],
+        leaves: vec![0; 1 << depth],
+    };
+
+    // Fill positions top-down. Pass-through nodes keep the defaults
+    // (feature 0, threshold i32::MAX, missing goes left) so they always go left.
+    let mut stack = vec![(0usize, 0usize)];
+    while let Some((pos, id)) = stack.pop() {
+        let n = &nodes[index[&id]];
+        if pos >= internal {
+            tree.leaves[pos - internal] = argmax(&n.leaf_values(LeafValues::ClassScores));
+            continue;
+        }
+        if is_leaf(n) {
+            stack.push((2 * pos + 1, id));
+            stack.push((2 * pos + 2, id));
+            continue;
+        }
+        let t_q = quantize(n.threshold.unwrap(), scale_bits);
+        let (threshold_q, swap) = match n.mode {
+            SplitMode::BranchLeq => (t_q, false),
+            SplitMode::BranchLt => (t_q - 1, false),
+            SplitMode::BranchGte => (t_q - 1, true),
+            SplitMode::BranchGt => (t_q, true),
+            _ => unreachable!("checked above"),
+        };
+        let (true_id, false_id) = (n.left.unwrap(), n.right.unwrap());
+        let (left_id, right_id) = if swap { (false_id, true_id) } else { (true_id, false_id) };
+        tree.features[pos] = n.feature.unwrap() as u32;
+        tree.thresholds[pos] = threshold_q;
+        // NaN fails every comparison, so it follows the false branch unless
+        // missing_tracks_true sends it to the true branch
+        tree.missing_left[pos] = (n.missing_tracks_true != swap) as u32;
+        stack.push((2 * pos + 1, left_id));
+        stack.push((2 * pos + 2, right_id));
+    }
+
+    Ok(Preprocessed { tree, nodes, index })
+}
+
+pub fn quantize_sample(x: &[f64], scale_bits: u32) -> QuantizedSample {
+    QuantizedSample {
+        x_q: x.iter().map(|&v| if v.is_nan() { 0 } else { quantize(v, scale_bits) }).collect(),
+        missing: x.iter().map(|v| v.is_nan() as u32).collect(),
+    }
+}
+
+impl Preprocessed {
+    // Class from walking the original tree with f64 comparisons.
+    pub fn predict_f64(&self, x: &[f64]) -> u32 {
+        let mut n = &self.nodes[self.index[&0]];
+        while !is_leaf(n) {
+            let take_true = takes_true_branch(n.mode, n.missing_tracks_true, x[n.feature.unwrap()], n.threshold.unwrap());
+            let next = if take_true { n.left.unwrap() } else { n.right.unwrap() };
+            n = &self.nodes[self.index[&next]];
+        }
+        argmax(&n.leaf_values(LeafValues::ClassScores))
+    }
+
+    // Class from the level arrays, as the guest computes it (without the
+    // oblivious selection).
+    pub fn predict_quantized(&self, sample: &QuantizedSample) -> u32 {
+        let t = &self.tree;
+        let mut pos = 0usize;
+        for _ in 0..t.depth {
+            let f = t.features[pos] as usize;
+            let go_right = if sample.missing[f] == 1 {
+                t.missing_left[pos] == 0
+            } else {
+                sample.x_q[f] > t.thresholds[pos]
+            };
+            pos = 2 * pos + 1 + go_right as usize;
+        }
+        t.leaves[pos - t.features.len()]
+    }
+
+    // Samples whose quantized routing ends in a different class than f64 routing.
+    pub fn changed_samples(&self, inputs: &[Vec<f64>], samples: &[QuantizedSample]) -> Vec<usize> {
+        inputs
+            .iter()
+            .zip(samples.iter())
+            .enumerate()
+            .filter(|(_, (x, q))| self.predict_f64(x) != self.predict_quantized(q))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MODES: [SplitMode; 4] = [SplitMode::BranchLeq, SplitMode::BranchLt, SplitMode::BranchGte, SplitMode::BranchGt];
+
+    fn mode_name(mode: SplitMode) -> &'static str {
+        match mode {
+            SplitMode::BranchLeq => "BRANCH_LEQ",
+            SplitMode::BranchLt => "BRANCH_LT",
+            SplitMode::BranchGte => "BRANCH_GTE",
+            SplitMode::BranchGt => "BRANCH_GT",
+            SplitMode::BranchEq => "BRANCH_EQ",
+            SplitMode::BranchNeq => "BRANCH_NEQ",
+            SplitMode::Leaf => "LEAF",
+        }
+    }
+
+    // x[0] split at `threshold`: class 0 on the true branch, class 1 on the
+    // false branch.
+    fn stump(mode: SplitMode, missing_tracks_true: bool, threshold: f64) -> Preprocessed {
+        let tree_json = json!([
+            {"id": 0, "feature": 0, "threshold": threshold, "left": 1, "right": 2,
+             "mode": mode_name(mode), "missing_tracks_true": missing_tracks_true},
+            {"id": 1, "mode": "LEAF", "value": [[1.0, 0.0]]},
+            {"id": 2, "mode": "LEAF", "value": [[0.0, 1.0]]}
+        ]);
+        preprocess_tree(&tree_json.to_string(), 1).unwrap()
+    }
+
+    fn assert_agrees(pre: &Preprocessed, x: &[f64]) -> u32 {
+        let class = pre.predict_f64(x);
+        assert_eq!(pre.predict_quantized(&quantize_sample(x, pre.tree.scale_bits)), class, "x {:?}", x);
+        class
+    }
+
+    #[test]
+    fn normalises_every_split_mode() {
+        for mode in MODES {
+            let pre = stump(mode, false, 0.5);
+            let t = &pre.tree;
+            let t_q = 1 << (t.scale_bits - 1);
+            let swapped = matches!(mode, SplitMode::BranchGte | SplitMode::BranchGt);
+            let expected_q = if matches!(mode, SplitMode::BranchLt | SplitMode::BranchGte) { t_q - 1 } else { t_q };
+            assert_eq!(t.thresholds, [expected_q], "{:?}", mode);
+            assert_eq!(t.leaves, if swapped { [1, 0] } else { [0, 1] }, "{:?}", mode);
+
+            // one quantization step either side of the threshold, and on it
+            let step = 1.0 / (1u64 << t.scale_bits) as f64;
+            for x in [-3.0, 0.5 - step, 0.5, 0.5 + step, 2.0, 1e12, -1e12] {
+                let class = assert_agrees(&pre, &[x]);
+                assert_eq!(class, !takes_true_branch(mode, false, x, 0.5) as u32, "{:?} x {}", mode, x);
+            }
+        }
+    }
+
+    #[test]
+    fn routes_missing_values_by_missing_tracks_true() {
+        for mode in MODES {
+            for missing_tracks_true in [false, true] {
+                let pre = stump(mode, missing_tracks_true, 0.5);
+                let swapped = matches!(mode, SplitMode::BranchGte | SplitMode::BranchGt);
+                assert_eq!(pre.tree.missing_left, [(missing_tracks_true != swapped) as u32], "{:?}", mode);
+                let class = assert_agrees(&pre, &[f64::NAN]);
+                assert_eq!(class, !missing_tracks_true as u32, "{:?} {}", mode, missing_tracks_true);
+            }
+        }
+    }
+
+    #[test]
+    fn pads_an_unbalanced_tree_with_pass_through_nodes() {
+        // x[0] <= 0 is a leaf (class 2); otherwise x[1] < 1 picks class 0 or 1
+        let tree_json = json!({"nodes": [
+            {"id": 0, "feature": 0, "threshold": 0.0, "left": 3, "right": 1, "missing_tracks_true": true},
+            {"id": 1, "feature": 1, "threshold": 1.0, "left": 2, "right": 4, "mode": "BRANCH_LT"},
+            {"id": 2, "mode": "LEAF", "value": [[1.0, 0.0, 0.0]]},
+            {"id": 3, "mode": "LEAF", "value": [[0.0, 0.0, 1.0]]},
+            {"id": 4, "mode": "LEAF", "value": [[0.0, 1.0, 0.0]]}
+        ]});
+        let pre = preprocess_tree(&tree_json.to_string(), 2).unwrap();
+        let t = &pre.tree;
+        assert_eq!(t.depth, 2);
+        assert_eq!(t.features, [0, 0, 1]);
+        assert_eq!(t.thresholds[1], i32::MAX);
+        assert_eq!(t.missing_left, [1, 1, 0]);
+        assert_eq!(t.leaves, [2, 2, 0, 1]);
+        for (x, class) in [
+            ([-1.0, 5.0], 2),
+            ([0.0, f64::NAN], 2),
+            ([f64::NAN, 0.0], 2),
+            ([1.0, 0.5], 0),
+            ([1.0, 1.0], 1),
+            ([1.0, f64::NAN], 1),
+            ([1e12, -1e12], 0),
+        ] {
+            assert_eq!(assert_agrees(&pre, &x), class, "x {:?}", x);
+        }
+    }
+
+    #[test]
+    fn reports_samples_whose_class_changes() {
+        // 0.1 is not exact in binary, so a feature just above it floors to the
+        // same integer and goes left, where f64 goes right
+        let pre = stump(SplitMode::BranchLeq, false, 0.1);
+        let inputs = vec![vec![0.05], vec![0.1 + 1e-12], vec![0.2], vec![f64::NAN]];
+        let samples: Vec<_> = inputs.iter().map(|x| quantize_sample(x, pre.tree.scale_bits)).collect();
+        assert_eq!(pre.predict_f64(&inputs[1]), 1);
+        assert_eq!(pre.predict_quantized(&samples[1]), 0);
+        assert_eq!(pre.changed_samples(&inputs, &samples), [1]);
+    }
+
+    #[test]
+    fn rejects_unsupported_trees() {
+        let eq = json!([
+            {"id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2, "mode": "BRANCH_EQ"},
+            {"id": 1, "mode": "LEAF", "value": [[1.0]]},
+            {"id": 2, "mode": "LEAF", "value": [[1.0]]}
+        ]);
+        let err = preprocess_tree(&eq.to_string(), 1).err().unwrap();
+        assert!(err.contains("BranchEq splits are not supported"), "{}", err);
+        let err = preprocess_tree(&eq.to_string().replace("BRANCH_EQ", "BRANCH_LEQ"), 0).err().unwrap();
+        assert!(err.contains("splits on feature 0 but inputs have 0"), "{}", err);
+    }
+}
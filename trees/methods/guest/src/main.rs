@@ -256,75 +256,126 @@
 
 
 
-use risc0_zkvm::serde::from_slice;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use bincode;
-
-#[derive(Serialize, Deserialize)]
-pub struct Inputs {
-    pub inputs: Vec<Vec<i32>>, // Validation dataset
+#![no_main]
+
+// Data-oblivious decision tree evaluation. The host (host/src/preprocess.rs)
+// pads the tree to a complete binary tree of fixed depth in heap order and
+// quantizes thresholds and features. Each sample then takes `depth` steps; at
+// every step the current node is picked by scanning the whole level, and its
+// feature by scanning the whole row, with masks instead of indexing or
+// branching, and the leaf is picked the same way. The work per sample depends
+// only on the tree shape, so the cycle count does not reveal the path taken.
+
+use risc0_zkvm::guest::env;
+use serde::Deserialize;
+
+// Field order matches the host's ObliviousTree.
+#[derive(Deserialize)]
+struct ObliviousTree {
+    depth: u32,
+    num_features: u32,
+    scale_bits: u32,
+    features: Vec<u32>,
+    thresholds: Vec<i32>,
+    missing_left: Vec<u32>,
+    leaves: Vec<u32>,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Tree {
-    feature: Vec<i32>,
-    threshold: Vec<i32>,
-    children_left: Vec<i32>,
-    children_right: Vec<i32>,
-    values: Vec<Vec<i32>>, // Leaf class counts
+// Field order matches the host's QuantizedSample.
+#[derive(Deserialize)]
+struct QuantizedSample {
+    x_q: Vec<i32>,
+    missing: Vec<u32>,
 }
 
-// Step function
-fn step(x: i32) -> i32 {
-    if x >= 0 { 1 } else { 0 }
+// All ones when a == b, zero otherwise.
+#[inline(always)]
+fn eq_mask(a: u32, b: u32) -> u32 {
+    ((a == b) as u32).wrapping_neg()
 }
 
-// Recursively predict for one row
-fn predict_node(tree: &Tree, node: usize, x: &[i32]) -> i32 {
-    if tree.feature[node] == -2 {
-        // Leaf node: return argmax class
-        let leaf_counts = &tree.values[node];
-        let mut max_idx = 0;
-        let mut max_val = leaf_counts[0];
-        for (i, &v) in leaf_counts.iter().enumerate() {
-            if v > max_val {
-                max_val = v;
-                max_idx = i;
-            }
-        }
-        return max_idx as i32;
+// values[index], reading every element.
+#[inline(always)]
+fn select(values: &[u32], index: u32) -> u32 {
+    let mut out = 0u32;
+    for (i, &v) in values.iter().enumerate() {
+        out |= v & eq_mask(i as u32, index);
     }
-
-    let feature_idx = tree.feature[node] as usize;
-    let threshold = tree.threshold[node] as i32;
-
-    let b = step(threshold - x[feature_idx]);
-    let left_val = predict_node(tree, tree.children_left[node] as usize, x);
-    let right_val = predict_node(tree, tree.children_right[node] as usize, x);
-
-    // Arithmetic if-else
-    b * left_val + (1 - b) * right_val
-}
-
-// Predict all rows
-pub fn predict_tree(tree: &Tree, inputs: &Vec<Vec<i32>>) -> Vec<i32> {
-    inputs.iter().map(|x| predict_node(tree, 0, x)).collect()
+    out
 }
 
-#[no_mangle]
-risc0_zkvm::guest::entry!(guest_main);
-pub extern "C" fn guest_main(input_bytes: &[u8]) -> Vec<u8> {
-    // Deserialize inputs
-    let inputs: Inputs = from_slice(input_bytes).unwrap();
-
-    // Load tree JSON (could be embedded or read)
-    let tree_json = include_bytes!("tree.json");
-    let tree: Tree = serde_json::from_slice(tree_json).unwrap();
+fn predict(tree: &ObliviousTree, sample: &QuantizedSample) -> u32 {
+    let x_q: Vec<u32> = sample.x_q.iter().map(|&v| v as u32).collect();
+    let mut pos = 0u32;
+    for level in 0..tree.depth {
+        let start = (1usize << level) - 1;
+        let end = (1usize << (level + 1)) - 1;
+        let at = pos - start as u32;
+
+        let mut feature = 0u32;
+        let mut threshold = 0u32;
+        let mut missing_left = 0u32;
+        for i in start..end {
+            let m = eq_mask((i - start) as u32, at);
+            feature |= tree.features[i] & m;
+            threshold |= tree.thresholds[i] as u32 & m;
+            missing_left |= tree.missing_left[i] & m;
+        }
 
-    // Predict
-    let predictions = predict_tree(&tree, &inputs.inputs);
+        let x = select(&x_q, feature) as i32;
+        let missing = select(&sample.missing, feature);
+        let greater = (x > threshold as i32) as u32;
+        let go_right = (missing & (missing_left ^ 1)) | ((missing ^ 1) & greater);
+        pos = 2 * pos + 1 + go_right;
+    }
+    select(&tree.leaves, pos - tree.features.len() as u32)
+}
 
-    // Serialize predictions to journal (Vec<u8>)
-    bincode::serialize(&predictions).unwrap()
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let tree: ObliviousTree = env::read();
+    let samples: Vec<QuantizedSample> = env::read();
+    let internal = (1usize << tree.depth) - 1;
+    assert!(
+        tree.features.len() == internal
+            && tree.thresholds.len() == internal
+            && tree.missing_left.len() == internal
+            && tree.leaves.len() == internal + 1,
+        "Tree arrays do not match depth {}",
+        tree.depth
+    );
+    for sample in samples.iter() {
+        assert!(
+            sample.x_q.len() == tree.num_features as usize && sample.missing.len() == tree.num_features as usize,
+            "Sample has {} features but the tree expects {}",
+            sample.x_q.len(),
+            tree.num_features
+        );
+    }
+    assert!(
+        tree.features.iter().all(|&f| f < tree.num_features),
+        "Tree splits on a feature the samples do not have"
+    );
+
+    let mut predictions = Vec::with_capacity(samples.len());
+    let mut min_cycles = u64::MAX;
+    let mut max_cycles = 0u64;
+    for sample in samples.iter() {
+        let start = env::cycle_count();
+        predictions.push(predict(&tree, sample));
+        let cycles = env::cycle_count() - start;
+        min_cycles = min_cycles.min(cycles);
+        max_cycles = max_cycles.max(cycles);
+    }
+    env::log(&format!(
+        "depth {}, thresholds at 2^{} scale, {} samples, cycles per sample: min {} max {}",
+        tree.depth,
+        tree.scale_bits,
+        samples.len(),
+        min_cycles,
+        max_cycles
+    ));
+
+    env::commit(&predictions);
 }
@@ -1,20 +1,76 @@
-use risc0_zkvm::Receipt;
-use serde_json;
 use std::fs;
+use verse_common::{envelope, receipt};
+
+// Unwrapping a signed envelope without VERSE_OWNER_KEY needs this flag.
+const NO_VERIFY_SIGNATURE: &str = "--no-verify-signature";
+
 fn main() {
     const METHOD_ID: [u32; 8] =  [8615346, 3088364040, 3309643489, 2403364783, 529424834, 3266678953, 590165670, 1240344216];
 
 
-    //  deserialize the proof.json
-    let data = fs::read_to_string("proof.json")
-        .expect("Failed to read proof.json");
+    //  deserialize the receipt: the path given, else the first of proof.envelope,
+    //  proof.receipt and proof.json that exists
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let skip_signature = args.iter().any(|a| a == NO_VERIFY_SIGNATURE);
+    let path = args.into_iter().find(|a| a != NO_VERIFY_SIGNATURE).unwrap_or_else(|| {
+        ["proof.envelope", "proof.receipt"]
+            .into_iter()
+            .find(|p| fs::metadata(p).is_ok())
+            .unwrap_or("proof.json")
+            .to_string()
+    });
+    let mut data = match fs::read(&path) {
+        Ok(d) => d,
+        Err(e) => { eprintln!("Failed to read {}: {}", path, e); std::process::exit(1); }
+    };
+
+    //  unwrap a signed envelope, checking its signature against VERSE_OWNER_KEY
+    //  (the owner's hex public key) unless --no-verify-signature is given
+    if envelope::is_envelope(&data) {
+        let owner_key = std::env::var("VERSE_OWNER_KEY").ok().filter(|k| !k.trim().is_empty());
+        let opened = match (owner_key.as_deref(), skip_signature) {
+            (Some(key), _) => envelope::parse_public_key(key).and_then(|key| envelope::open(&data, &key)),
+            (None, true) => envelope::open_unverified(&data),
+            (None, false) => {
+                eprintln!(
+                    "❌ {} is signed; set VERSE_OWNER_KEY to the model owner's public key to check it, or pass {} to skip the check",
+                    path, NO_VERIFY_SIGNATURE
+                );
+                std::process::exit(1);
+            }
+        };
+        let (h, inner) = match opened {
+            Ok(opened) => opened,
+            Err(e) => { eprintln!("❌ Envelope check failed for {}: {}", path, e); std::process::exit(1); }
+        };
+        if h.image_id != METHOD_ID {
+            eprintln!("❌ Envelope is for image ID {:?}, not this verifier's METHOD_ID", h.image_id);
+            std::process::exit(1);
+        }
+        println!(
+            "Envelope: request {}, model {}, signed by {} at Unix time {} with {}",
+            h.request_id, h.model_id, hex::encode(h.public_key), h.timestamp, h.tool_version
+        );
+        if owner_key.is_some() {
+            println!("Signature checked against VERSE_OWNER_KEY");
+        } else {
+            println!("⚠️  Signature not checked ({})", NO_VERIFY_SIGNATURE);
+        }
+        data = inner;
+    }
+
+    let (receipt, encoding) = match receipt::decode(&data) {
+        Ok(r) => r,
+        Err(e) => { eprintln!("Failed to parse receipt from {}: {}", path, e); std::process::exit(1); }
+    };
 
-    let receipt: Receipt = serde_json::from_str(&data)
-        .expect("Failed to parse receipt");
+    //  composite or succinct, depending on `verse prove --receipt-kind`
+    let kind = receipt::kind_name(&receipt);
+    println!("Receipt kind: {} ({} encoding, {} bytes)", kind, encoding, data.len());
 
     //  Verify against method_id
     match receipt.verify(METHOD_ID) {
-        Ok(_) => println!("✅ Proof verified successfully!"),
-        Err(e) => println!("❌ Verification failed: {:?}", e),
+        Ok(_) => println!("✅ Proof verified successfully! (checked a {} receipt)", kind),
+        Err(e) => { eprintln!("❌ Verification failed: {:?}", e); std::process::exit(1); }
     }
 }
\ No newline at end of file
@@ -0,0 +1,285 @@
+// Signed proof envelopes. A receipt proves the computation; the envelope says
+// who produced it, for which request and when. `verse prove` wraps the
+// encoded receipt file (see receipt.rs) in one and uploads it as
+// proof.envelope:
+//
+//   bytes 0..4  magic "VRSE"
+//   byte  4     format version (1)
+//   bytes 5..8  reserved, zero
+//   rest        bincode of Envelope
+//
+// The signature covers bincode(header) followed by the receipt bytes, so
+// neither the metadata nor the receipt can be swapped without breaking it.
+// `verse verify` and the verifier crate check it against the model owner's
+// published public key.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+const MAGIC: &[u8; 4] = b"VRSE";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+
+pub const FILE_NAME: &str = "proof.envelope";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub request_id: String,
+    pub model_id: String,
+    pub image_id: [u32; 8],
+    // seconds since the Unix epoch
+    pub timestamp: u64,
+    pub tool_version: String,
+    pub public_key: [u8; 32],
+}
+
+impl Header {
+    // Checks that a verified envelope belongs to the request being verified.
+    pub fn check(&self, request_id: &str, model_id: &str, image_id: [u32; 8]) -> Result<(), String> {
+        if self.request_id != request_id {
+            return Err(format!("Envelope is for request {}, not {}", self.request_id, request_id));
+        }
+        if !model_id.is_empty() && self.model_id != model_id {
+            return Err(format!("Envelope is for model {}, not {}", self.model_id, model_id));
+        }
+        if self.image_id != image_id {
+            return Err("Envelope image ID does not match the request's proofHash".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    header: Header,
+    receipt: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+fn signed_message(header: &Header, receipt: &[u8]) -> Vec<u8> {
+    let mut message = bincode::serialize(header).expect("serialize envelope header");
+    message.extend_from_slice(receipt);
+    message
+}
+
+// Parses a hex-encoded Ed25519 public key, as stored on the server.
+pub fn parse_public_key(s: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = hex::decode(s.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("Public key must be 64 hex characters, got '{}'", s))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid Ed25519 public key: {}", e))
+}
+
+// Whether `bytes` is an envelope rather than a bare receipt.
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+// Signs `receipt` (an encoded receipt file) with `key`; `header.public_key`
+// is filled in from the key.
+pub fn seal(mut header: Header, receipt: Vec<u8>, key: &SigningKey) -> Vec<u8> {
+    header.public_key = key.verifying_key().to_bytes();
+    let signature = key.sign(&signed_message(&header, &receipt));
+    let envelope = Envelope { header, receipt, signature: signature.to_bytes().to_vec() };
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&[FORMAT_VERSION, 0, 0, 0]);
+    out.extend_from_slice(&bincode::serialize(&envelope).expect("serialize envelope"));
+    out
+}
+
+fn decode(bytes: &[u8]) -> Result<Envelope, String> {
+    if !is_envelope(bytes) || bytes.len() < HEADER_LEN {
+        return Err("Not a proof envelope".into());
+    }
+    if bytes[4] != FORMAT_VERSION {
+        return Err(format!(
+            "Envelope format version {} is not supported (this reader supports version {}); update verse",
+            bytes[4], FORMAT_VERSION
+        ));
+    }
+    bincode::deserialize(&bytes[HEADER_LEN..]).map_err(|e| format!("Failed to decode envelope: {}", e))
+}
+
+// Checks the envelope's signature against `owner_key` and returns its header
+// and the receipt bytes inside.
+pub fn open(bytes: &[u8], owner_key: &VerifyingKey) -> Result<(Header, Vec<u8>), String> {
+    let envelope = decode(bytes)?;
+    if envelope.header.public_key != owner_key.to_bytes() {
+        return Err(format!(
+            "Envelope was signed with key {}, not the model owner's published key {}",
+            hex::encode(envelope.header.public_key),
+            hex::encode(owner_key.as_bytes())
+        ));
+    }
+    let signature = Signature::from_slice(&envelope.signature).map_err(|e| format!("Malformed signature: {}", e))?;
+    owner_key
+        .verify(&signed_message(&envelope.header, &envelope.receipt), &signature)
+        .map_err(|_| "Signature does not match the envelope contents".to_string())?;
+    Ok((envelope.header, envelope.receipt))
+}
+
+// Unwraps the envelope without checking who signed it. Only for callers that
+// have been told explicitly to skip the check.
+pub fn open_unverified(bytes: &[u8]) -> Result<(Header, Vec<u8>), String> {
+    let envelope = decode(bytes)?;
+    Ok((envelope.header, envelope.receipt))
+}
+
+// What `verse verify` found when it opened a downloaded proof.
+#[derive(Debug)]
+pub enum Opened {
+    // an envelope whose signature matched the owner's published key
+    Signed(Header),
+    // an envelope, but the owner has published no key to check it against
+    Unchecked(Header),
+    // a bare receipt from an owner who has published no key
+    Unsigned,
+}
+
+// Unwraps a downloaded proof according to the model owner's published key.
+// Once a key is published, only envelopes signed with it are accepted, so a
+// stripped signature does not pass; without one, signed and unsigned proofs
+// are both let through and the caller warns that the signer is unchecked.
+pub fn open_for_owner(bytes: &[u8], owner_key: Option<&VerifyingKey>) -> Result<(Opened, Vec<u8>), String> {
+    match (is_envelope(bytes), owner_key) {
+        (true, Some(owner_key)) => open(bytes, owner_key).map(|(h, receipt)| (Opened::Signed(h), receipt)),
+        (true, None) => open_unverified(bytes).map(|(h, receipt)| (Opened::Unchecked(h), receipt)),
+        (false, Some(_)) => {
+            Err("Proof is not signed, but the model owner has published a signing key; refusing an unsigned proof".into())
+        }
+        (false, None) => Ok((Opened::Unsigned, bytes.to_vec())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_ID: [u32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn header() -> Header {
+        Header {
+            request_id: "req-1".into(),
+            model_id: "model-1".into(),
+            image_id: IMAGE_ID,
+            timestamp: 1_700_000_000,
+            tool_version: "0.1.0".into(),
+            public_key: [0; 32],
+        }
+    }
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn opens_what_it_seals() {
+        let key = key(1);
+        let sealed = seal(header(), b"VRSR receipt".to_vec(), &key);
+        assert!(is_envelope(&sealed));
+        let (header, receipt) = open(&sealed, &key.verifying_key()).unwrap();
+        assert_eq!(receipt, b"VRSR receipt");
+        assert_eq!(header.public_key, key.verifying_key().to_bytes());
+        header.check("req-1", "model-1", IMAGE_ID).unwrap();
+        header.check("req-1", "", IMAGE_ID).unwrap();
+    }
+
+    #[test]
+    fn rejects_tampered_envelopes() {
+        let key = key(1);
+        let sealed = seal(header(), b"VRSR receipt".to_vec(), &key);
+        // the last receipt byte comes right before the signature (an 8-byte
+        // length and 64 bytes)
+        let mut tampered = sealed.clone();
+        let at = tampered.len() - 64 - 8 - 1;
+        tampered[at] ^= 1;
+        let err = open(&tampered, &key.verifying_key()).unwrap_err();
+        assert!(err.contains("Signature does not match"), "{}", err);
+
+        // a byte of the signature itself
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let err = open(&tampered, &key.verifying_key()).unwrap_err();
+        assert!(err.contains("Signature does not match"), "{}", err);
+
+        let mut tampered = sealed;
+        tampered[4] = 9;
+        assert!(open(&tampered, &key.verifying_key()).unwrap_err().contains("version 9 is not supported"));
+        assert!(open(b"VRSR receipt", &key.verifying_key()).unwrap_err().contains("Not a proof envelope"));
+    }
+
+    #[test]
+    fn rejects_other_keys() {
+        let sealed = seal(header(), b"VRSR receipt".to_vec(), &key(1));
+        let err = open(&sealed, &key(2).verifying_key()).unwrap_err();
+        assert!(err.contains("not the model owner's published key"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_a_swapped_public_key() {
+        // a header claiming the owner's key over another key's signature
+        let mut header = header();
+        header.public_key = key(1).verifying_key().to_bytes();
+        let receipt = b"VRSR receipt".to_vec();
+        let signature = key(2).sign(&signed_message(&header, &receipt));
+        let envelope = Envelope { header, receipt, signature: signature.to_bytes().to_vec() };
+        let mut forged = MAGIC.to_vec();
+        forged.extend_from_slice(&[FORMAT_VERSION, 0, 0, 0]);
+        forged.extend_from_slice(&bincode::serialize(&envelope).unwrap());
+        let err = open(&forged, &key(1).verifying_key()).unwrap_err();
+        assert!(err.contains("Signature does not match"), "{}", err);
+    }
+
+    #[test]
+    fn opens_unverified_without_a_key() {
+        let sealed = seal(header(), b"VRSR receipt".to_vec(), &key(1));
+        let (header, receipt) = open_unverified(&sealed).unwrap();
+        assert_eq!(header.request_id, "req-1");
+        assert_eq!(receipt, b"VRSR receipt");
+        assert!(open_unverified(b"VRSR receipt").unwrap_err().contains("Not a proof envelope"));
+    }
+
+    #[test]
+    fn opens_for_owner_by_envelope_and_key() {
+        let owner = key(1).verifying_key();
+        let sealed = seal(header(), b"VRSR receipt".to_vec(), &key(1));
+        let bare = b"VRSR receipt".to_vec();
+
+        let (opened, receipt) = open_for_owner(&sealed, Some(&owner)).unwrap();
+        assert!(matches!(opened, Opened::Signed(h) if h.request_id == "req-1"));
+        assert_eq!(receipt, bare);
+
+        let (opened, receipt) = open_for_owner(&sealed, None).unwrap();
+        assert!(matches!(opened, Opened::Unchecked(h) if h.public_key == owner.to_bytes()));
+        assert_eq!(receipt, bare);
+
+        let err = open_for_owner(&bare, Some(&owner)).unwrap_err();
+        assert!(err.contains("refusing an unsigned proof"), "{}", err);
+
+        let (opened, receipt) = open_for_owner(&bare, None).unwrap();
+        assert!(matches!(opened, Opened::Unsigned));
+        assert_eq!(receipt, bare);
+
+        let err = open_for_owner(&sealed, Some(&key(2).verifying_key())).unwrap_err();
+        assert!(err.contains("not the model owner's published key"), "{}", err);
+    }
+
+    #[test]
+    fn parses_hex_public_keys() {
+        let key = key(1).verifying_key();
+        assert_eq!(parse_public_key(&format!(" {} ", hex::encode(key.as_bytes()))).unwrap(), key);
+        assert!(parse_public_key("abcd").unwrap_err().contains("64 hex characters"));
+        assert!(parse_public_key(&"zz".repeat(32)).unwrap_err().contains("64 hex characters"));
+    }
+
+    #[test]
+    fn checks_the_header_against_the_request() {
+        let header = header();
+        assert!(header.check("req-2", "model-1", IMAGE_ID).unwrap_err().contains("not req-2"));
+        assert!(header.check("req-1", "model-2", IMAGE_ID).unwrap_err().contains("not model-2"));
+        assert!(header.check("req-1", "model-1", [0; 8]).unwrap_err().contains("image ID"));
+    }
+}
@@ -0,0 +1,591 @@
+// Flattened tree format. The guests used to receive tree JSON and run
+// serde_json inside the zkVM, then build an id -> position index before any
+// prediction; on the zkVM that costs far more cycles than the traversal itself.
+// Here the host resolves ids, picks the leaf values and lays everything out as a
+// u32 array the guest walks in place.
+//
+// Layout (all u32 words, f64 values stored as two words, low word first):
+//
+//   header   MAGIC, aggregate, post_transform, num_trees, num_nodes,
+//            leaf_width, num_base_values
+//   roots    num_trees node positions
+//   base     num_base_values f64
+//   nodes    num_nodes x NODE_WORDS:
+//              feature      split feature, LEAF_FEATURE for leaves
+//              flags        mode code | missing_tracks_true << 8
+//              thr_lo, thr_hi
+//              left         true branch position; leaf number for leaves
+//              right        false branch position; 0 for leaves
+//   leaves   leaf_width f64 per leaf, in leaf number order
+//
+// Mode, aggregate and post_transform codes match the guest enums' order.
+// Set VERSE_TREE_FORMAT=json to send the JSON instead.
+//
+// The quantized variant (optimized mode) starts with MAGIC_QUANTIZED, adds
+// scale_bits as an eighth header word and a seventh node word, thr_q =
+// floor(threshold * 2^scale_bits) as i32. The guest floors each feature the same
+// way and compares integers. With both sides floored, the integer and float
+// comparisons can only disagree when x_q == thr_q; the guest checks those ties
+// against the f64 threshold and reports the samples whose routing changed.
+//
+// FlatTrees reads the words back the way the guest does (same checks, same
+// walk), so the encoding can be tested on the host.
+
+use crate::tree::{takes_true_branch, Aggregate, PostTransform, SplitMode, TreeEnsemble};
+use std::collections::HashMap;
+
+pub use crate::tree::LeafValues;
+
+pub const MAGIC: u32 = 0x5654_4631; // "VTF1"
+pub const HEADER_WORDS: usize = 7;
+pub const NODE_WORDS: usize = 6;
+pub const LEAF_FEATURE: u32 = u32::MAX;
+pub const MAGIC_QUANTIZED: u32 = 0x5654_5131; // "VTQ1"
+pub const QUANT_HEADER_WORDS: usize = 8;
+pub const QUANT_NODE_WORDS: usize = 7;
+// Largest |thr_q|, leaving headroom so saturated features never tie.
+const QUANT_LIMIT: f64 = (1u64 << 30) as f64;
+
+fn push_f64(words: &mut Vec<u32>, v: f64) {
+    let bits = v.to_bits();
+    words.push(bits as u32);
+    words.push((bits >> 32) as u32);
+}
+
+fn word_f64(words: &[u32], at: usize) -> f64 {
+    f64::from_bits(words[at] as u64 | (words[at + 1] as u64) << 32)
+}
+
+pub fn flatten_tree_json(tree_json: &str, leaf_values: LeafValues) -> Result<Vec<u32>, String> {
+    build(TreeEnsemble::from_json(tree_json)?, leaf_values, None)
+}
+
+// Quantized variant; returns the words and the scale chosen, the largest
+// power of two (up to 2^30) that keeps every |thr_q| below 2^30.
+pub fn quantize_tree_json(tree_json: &str, leaf_values: LeafValues) -> Result<(Vec<u32>, u32), String> {
+    let ensemble = TreeEnsemble::from_json(tree_json)?;
+    let mut max_abs = 0.0f64;
+    for n in ensemble.nodes.iter() {
+        if let (Some(_), Some(thr)) = (n.feature, n.threshold) {
+            if !thr.is_finite() {
+                return Err(format!("Tree {} node {} has a non-finite threshold", n.tree_id, n.id));
+            }
+            max_abs = max_abs.max(thr.abs());
+        }
+    }
+    let mut scale_bits = 30u32;
+    while scale_bits > 0 && max_abs * (1u64 << scale_bits) as f64 >= QUANT_LIMIT {
+        scale_bits -= 1;
+    }
+    if max_abs >= QUANT_LIMIT {
+        return Err(format!("Threshold {} is too large to quantize to i32", max_abs));
+    }
+    let words = build(ensemble, leaf_values, Some(scale_bits))?;
+    Ok((words, scale_bits))
+}
+
+fn build(ensemble: TreeEnsemble, leaf_values: LeafValues, scale_bits: Option<u32>) -> Result<Vec<u32>, String> {
+    let nodes = &ensemble.nodes;
+    if nodes.is_empty() {
+        return Err("Tree JSON has no nodes".into());
+    }
+
+    let num_trees = nodes.iter().map(|n| n.tree_id + 1).max().unwrap_or(0);
+    let mut positions: HashMap<(usize, usize), u32> = HashMap::with_capacity(nodes.len());
+    for (pos, n) in nodes.iter().enumerate() {
+        if positions.insert((n.tree_id, n.id), pos as u32).is_some() {
+            return Err(format!("Tree {} has duplicate node id {}", n.tree_id, n.id));
+        }
+    }
+    let child = |tree_id: usize, id: Option<usize>, parent: usize| -> Result<u32, String> {
+        let id = id.ok_or_else(|| format!("Tree {} node {} is missing a child", tree_id, parent))?;
+        positions
+            .get(&(tree_id, id))
+            .copied()
+            .ok_or_else(|| format!("Tree {} node {} points to unknown node {}", tree_id, parent, id))
+    };
+
+    let mut roots = Vec::with_capacity(num_trees);
+    for t in 0..num_trees {
+        let root = positions
+            .get(&(t, 0))
+            .ok_or_else(|| format!("Tree {} has no root (node id 0)", t))?;
+        roots.push(*root);
+    }
+
+    let node_len = if scale_bits.is_some() { QUANT_NODE_WORDS } else { NODE_WORDS };
+    let mut node_words: Vec<u32> = Vec::with_capacity(nodes.len() * node_len);
+    let mut leaf_words: Vec<u32> = Vec::new();
+    let mut leaf_width: Option<usize> = None;
+    let mut num_leaves = 0u32;
+    for n in nodes.iter() {
+        match n.feature {
+            Some(feature) if n.mode != SplitMode::Leaf => {
+                let threshold = n
+                    .threshold
+                    .ok_or_else(|| format!("Tree {} node {} has no threshold", n.tree_id, n.id))?;
+                node_words.push(feature as u32);
+                node_words.push(n.mode as u32 | (n.missing_tracks_true as u32) << 8);
+                push_f64(&mut node_words, threshold);
+                node_words.push(child(n.tree_id, n.left, n.id)?);
+                node_words.push(child(n.tree_id, n.right, n.id)?);
+                if let Some(bits) = scale_bits {
+                    let thr_q = (threshold * (1u64 << bits) as f64).floor() as i32;
+                    node_words.push(thr_q as u32);
+                }
+            }
+            _ => {
+                let values = n.leaf_values(leaf_values);
+                if values.is_empty() {
+                    return Err(format!("Tree {} leaf {} has no values", n.tree_id, n.id));
+                }
+                match leaf_width {
+                    None => leaf_width = Some(values.len()),
+                    Some(w) if w != values.len() => {
+                        return Err("Leaf value width differs between leaves".into())
+                    }
+                    Some(_) => {}
+                }
+                node_words.extend_from_slice(&[LEAF_FEATURE, SplitMode::Leaf as u32, 0, 0, num_leaves, 0]);
+                if scale_bits.is_some() {
+                    node_words.push(0);
+                }
+                for v in values {
+                    push_f64(&mut leaf_words, v);
+                }
+                num_leaves += 1;
+            }
+        }
+    }
+    let leaf_width = leaf_width.ok_or("Tree JSON has no leaves")?;
+    if !ensemble.base_values.is_empty() && ensemble.base_values.len() != leaf_width {
+        return Err("base_values width differs from leaf values".into());
+    }
+
+    let header_len = if scale_bits.is_some() { QUANT_HEADER_WORDS } else { HEADER_WORDS };
+    let mut words = Vec::with_capacity(
+        header_len + roots.len() + 2 * ensemble.base_values.len() + node_words.len() + leaf_words.len(),
+    );
+    words.extend_from_slice(&[
+        if scale_bits.is_some() { MAGIC_QUANTIZED } else { MAGIC },
+        ensemble.aggregate as u32,
+        ensemble.post_transform as u32,
+        num_trees as u32,
+        nodes.len() as u32,
+        leaf_width as u32,
+        ensemble.base_values.len() as u32,
+    ]);
+    if let Some(bits) = scale_bits {
+        words.push(bits);
+    }
+    words.extend_from_slice(&roots);
+    for &b in ensemble.base_values.iter() {
+        push_f64(&mut words, b);
+    }
+    words.extend_from_slice(&node_words);
+    words.extend_from_slice(&leaf_words);
+    Ok(words)
+}
+
+// One split on the way to a leaf, as reported by FlatTrees::find_leaf_with.
+// `node` is the node's position, i.e. its index in the tree JSON's node list.
+#[derive(Debug, Clone, Copy)]
+pub struct Split {
+    pub node: usize,
+    pub feature: usize,
+    pub x: f64,
+    pub threshold: f64,
+    pub mode: SplitMode,
+    pub took_true: bool,
+}
+
+// Per-tree leaf vectors -> scores, as the guest's combine_leaves does:
+// aggregate across trees, add base_values, then apply post_transform.
+pub fn combine_leaves(
+    leaves: impl Iterator<Item = Vec<f64>>,
+    aggregate: Aggregate,
+    base_values: &[f64],
+    post_transform: PostTransform,
+) -> Result<Vec<f64>, String> {
+    let mut scores: Vec<f64> = Vec::new();
+    let mut num_trees = 0usize;
+    for leaf in leaves {
+        num_trees += 1;
+        if num_trees == 1 {
+            scores = leaf;
+            continue;
+        }
+        if leaf.len() != scores.len() {
+            return Err("Leaf value width differs between trees".into());
+        }
+        for (s, v) in scores.iter_mut().zip(leaf.iter()) {
+            *s = match aggregate {
+                Aggregate::Sum | Aggregate::Average => *s + v,
+                Aggregate::Min => s.min(*v),
+                Aggregate::Max => s.max(*v),
+            };
+        }
+    }
+    if aggregate == Aggregate::Average && num_trees > 0 {
+        let n = num_trees as f64;
+        for s in scores.iter_mut() {
+            *s /= n;
+        }
+    }
+    if !base_values.is_empty() {
+        if base_values.len() != scores.len() {
+            return Err("base_values width differs from leaf values".into());
+        }
+        for (s, b) in scores.iter_mut().zip(base_values.iter()) {
+            *s += b;
+        }
+    }
+    Ok(match post_transform {
+        PostTransform::None => scores,
+        PostTransform::Softmax => {
+            let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let exps: Vec<f64> = scores.iter().map(|&z| (z - max).exp()).collect();
+            let sum: f64 = exps.iter().sum();
+            exps.iter().map(|&e| e / sum).collect()
+        }
+        PostTransform::Logistic => scores.iter().map(|&z| 1.0 / (1.0 + (-z).exp())).collect(),
+    })
+}
+
+// A flattened tree read back as the guest reads it (read_flat_trees).
+pub struct FlatTrees<'a> {
+    words: &'a [u32],
+    pub aggregate: Aggregate,
+    pub post_transform: PostTransform,
+    pub base_values: Vec<f64>,
+    pub num_trees: usize,
+    pub leaf_width: usize,
+    pub scale_bits: Option<u32>,
+    roots_at: usize,
+    node_len: usize,
+    nodes_at: usize,
+    leaves_at: usize,
+}
+
+fn split_mode_from_code(code: u32) -> Result<SplitMode, String> {
+    Ok(match code {
+        0 => SplitMode::BranchLeq,
+        1 => SplitMode::BranchLt,
+        2 => SplitMode::BranchGte,
+        3 => SplitMode::BranchGt,
+        4 => SplitMode::BranchEq,
+        5 => SplitMode::BranchNeq,
+        6 => SplitMode::Leaf,
+        other => return Err(format!("Unknown split mode code {}", other)),
+    })
+}
+
+// Integer form of takes_true_branch for a present feature.
+fn takes_true_branch_q(mode: SplitMode, x_q: i32, thr_q: i32) -> bool {
+    match mode {
+        SplitMode::BranchLeq => x_q <= thr_q,
+        SplitMode::BranchLt => x_q < thr_q,
+        SplitMode::BranchGte => x_q >= thr_q,
+        SplitMode::BranchGt => x_q > thr_q,
+        SplitMode::BranchEq => x_q == thr_q,
+        SplitMode::BranchNeq => x_q != thr_q,
+        SplitMode::Leaf => false,
+    }
+}
+
+impl<'a> FlatTrees<'a> {
+    pub fn parse(words: &'a [u32]) -> Result<Self, String> {
+        if words.len() < HEADER_WORDS {
+            return Err("Not a flattened tree".into());
+        }
+        let quantized = match words[0] {
+            MAGIC => false,
+            MAGIC_QUANTIZED => true,
+            _ => return Err("Not a flattened tree".into()),
+        };
+        let (header_len, node_len) = if quantized {
+            (QUANT_HEADER_WORDS, QUANT_NODE_WORDS)
+        } else {
+            (HEADER_WORDS, NODE_WORDS)
+        };
+        if words.len() < header_len {
+            return Err("Flattened tree is truncated".into());
+        }
+        let aggregate = match words[1] {
+            0 => Aggregate::Sum,
+            1 => Aggregate::Average,
+            2 => Aggregate::Min,
+            3 => Aggregate::Max,
+            other => return Err(format!("Unknown aggregate code {}", other)),
+        };
+        let post_transform = match words[2] {
+            0 => PostTransform::None,
+            1 => PostTransform::Softmax,
+            2 => PostTransform::Logistic,
+            other => return Err(format!("Unknown post_transform code {}", other)),
+        };
+        let num_trees = words[3] as usize;
+        let num_nodes = words[4] as usize;
+        let leaf_width = words[5] as usize;
+        let num_base_values = words[6] as usize;
+
+        let roots_at = header_len;
+        let base_at = roots_at + num_trees;
+        let nodes_at = base_at + 2 * num_base_values;
+        let leaves_at = nodes_at + num_nodes * node_len;
+        if leaves_at > words.len() {
+            return Err("Flattened tree is truncated".into());
+        }
+        Ok(FlatTrees {
+            words,
+            aggregate,
+            post_transform,
+            base_values: (0..num_base_values).map(|i| word_f64(words, base_at + 2 * i)).collect(),
+            num_trees,
+            leaf_width,
+            scale_bits: quantized.then(|| words[7]),
+            roots_at,
+            node_len,
+            nodes_at,
+            leaves_at,
+        })
+    }
+
+    // Leaf number reached by tree `t`.
+    pub fn find_leaf(&self, t: usize, x: &[f64]) -> Result<usize, String> {
+        self.find_leaf_with(t, x, |_| {})
+    }
+
+    // find_leaf, calling `on_split` for every split on the way.
+    pub fn find_leaf_with(&self, t: usize, x: &[f64], mut on_split: impl FnMut(Split)) -> Result<usize, String> {
+        let w = self.words;
+        let mut pos = w[self.roots_at + t] as usize;
+        loop {
+            let node = self.nodes_at + pos * self.node_len;
+            let feature = w[node];
+            if feature == LEAF_FEATURE {
+                return Ok(w[node + 4] as usize);
+            }
+            let feature = feature as usize;
+            let xf = *x
+                .get(feature)
+                .ok_or_else(|| format!("Tree {} splits on feature {} but the row has {}", t, feature, x.len()))?;
+            let flags = w[node + 1];
+            let mode = split_mode_from_code(flags & 0xff)?;
+            let threshold = word_f64(w, node + 2);
+            let took_true = takes_true_branch(mode, flags >> 8 != 0, xf, threshold);
+            on_split(Split { node: pos, feature, x: xf, threshold, mode, took_true });
+            pos = if took_true { w[node + 4] as usize } else { w[node + 5] as usize };
+        }
+    }
+
+    // Leaf number reached by tree `t` comparing features floored to the
+    // quantized scale, and whether any split on the way went differently than
+    // the f64 comparison would.
+    pub fn find_leaf_quantized(&self, t: usize, x: &[f64]) -> Result<(usize, bool), String> {
+        let scale_bits = self.scale_bits.ok_or("Not a quantized tree")?;
+        let scale = (1u64 << scale_bits) as f64;
+        let w = self.words;
+        let mut pos = w[self.roots_at + t] as usize;
+        let mut changed = false;
+        loop {
+            let node = self.nodes_at + pos * self.node_len;
+            let feature = w[node];
+            if feature == LEAF_FEATURE {
+                return Ok((w[node + 4] as usize, changed));
+            }
+            let flags = w[node + 1];
+            let mode = split_mode_from_code(flags & 0xff)?;
+            let missing_tracks_true = flags >> 8 != 0;
+            let xf = x[feature as usize];
+            let go_true = if xf.is_nan() {
+                missing_tracks_true || mode == SplitMode::BranchNeq
+            } else {
+                let q = (xf * scale).floor() as i32;
+                let thr_q = w[node + 6] as i32;
+                let go_true = takes_true_branch_q(mode, q, thr_q);
+                if q == thr_q {
+                    changed |= go_true != takes_true_branch(mode, missing_tracks_true, xf, word_f64(w, node + 2));
+                }
+                go_true
+            };
+            pos = if go_true { w[node + 4] as usize } else { w[node + 5] as usize };
+        }
+    }
+
+    pub fn leaf_values(&self, leaf: usize) -> Vec<f64> {
+        let at = self.leaves_at + 2 * leaf * self.leaf_width;
+        (0..self.leaf_width).map(|i| word_f64(self.words, at + 2 * i)).collect()
+    }
+
+    // Scores for `x` with f64 comparisons, before any binary expansion.
+    pub fn predict(&self, x: &[f64]) -> Result<Vec<f64>, String> {
+        let mut leaves = Vec::with_capacity(self.num_trees);
+        for t in 0..self.num_trees {
+            leaves.push(self.leaf_values(self.find_leaf(t, x)?));
+        }
+        combine_leaves(leaves.into_iter(), self.aggregate, &self.base_values, self.post_transform)
+    }
+
+    // onnxruntime expands a single binary score s to [1 - s, s], except under
+    // post_transform NONE when some leaf weight is negative: then [-s, s].
+    pub fn signed_binary_scores(&self) -> bool {
+        self.post_transform == PostTransform::None
+            && (self.leaves_at..self.words.len()).step_by(2).any(|at| word_f64(self.words, at) < 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MODES: [&str; 6] = ["BRANCH_LEQ", "BRANCH_LT", "BRANCH_GTE", "BRANCH_GT", "BRANCH_EQ", "BRANCH_NEQ"];
+
+    // One stump per split mode and missing-value direction, splitting feature
+    // 0 at 0.5 with leaves 10t + 1 (true) and 10t + 2 (false), plus a
+    // two-level tree on features 0 and 1.
+    fn ensemble_json() -> String {
+        let mut nodes = Vec::new();
+        let mut t = 0;
+        for mode in MODES {
+            for missing_tracks_true in [false, true] {
+                nodes.push(json!({"tree_id": t, "id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2,
+                                  "mode": mode, "missing_tracks_true": missing_tracks_true}));
+                nodes.push(json!({"tree_id": t, "id": 1, "mode": "LEAF", "value": [[10.0 * t as f64 + 1.0]]}));
+                nodes.push(json!({"tree_id": t, "id": 2, "mode": "LEAF", "value": [[10.0 * t as f64 + 2.0]]}));
+                t += 1;
+            }
+        }
+        // node ids out of order, so positions differ from ids
+        nodes.push(json!({"tree_id": t, "id": 2, "mode": "LEAF", "value": [[-3.0]]}));
+        nodes.push(json!({"tree_id": t, "id": 0, "feature": 1, "threshold": -1.25, "left": 1, "right": 2,
+                          "mode": "BRANCH_GT"}));
+        nodes.push(json!({"tree_id": t, "id": 1, "feature": 0, "threshold": 0.75, "left": 3, "right": 4,
+                          "mode": "BRANCH_LT", "missing_tracks_true": true}));
+        nodes.push(json!({"tree_id": t, "id": 3, "mode": "LEAF", "value": [[-1.0]]}));
+        nodes.push(json!({"tree_id": t, "id": 4, "mode": "LEAF", "value": [[-2.0]]}));
+        json!({"aggregate": "AVERAGE", "post_transform": "LOGISTIC", "base_values": [0.5], "nodes": nodes}).to_string()
+    }
+
+    // The leaf value tree `t` reaches, walking the JSON nodes by id.
+    fn reference_leaf(ensemble: &TreeEnsemble, t: usize, x: &[f64]) -> Vec<f64> {
+        let node = |id: usize| ensemble.nodes.iter().find(|n| n.tree_id == t && n.id == id).unwrap();
+        let mut n = node(0);
+        while n.mode != SplitMode::Leaf {
+            let f = n.feature.unwrap();
+            let next = if takes_true_branch(n.mode, n.missing_tracks_true, x[f], n.threshold.unwrap()) {
+                n.left
+            } else {
+                n.right
+            };
+            n = node(next.unwrap());
+        }
+        n.leaf_values(LeafValues::ClassScores)
+    }
+
+    fn rows() -> Vec<[f64; 2]> {
+        let mut rows = Vec::new();
+        for x0 in [0.25, 0.5, 0.75, 1.0, f64::NAN] {
+            for x1 in [-2.0, -1.25, 0.0, f64::NAN] {
+                rows.push([x0, x1]);
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn flat_round_trip_reaches_the_same_leaves() {
+        let tree_json = ensemble_json();
+        let ensemble = TreeEnsemble::from_json(&tree_json).unwrap();
+        let words = flatten_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+        let flat = FlatTrees::parse(&words).unwrap();
+        assert_eq!(flat.aggregate, Aggregate::Average);
+        assert_eq!(flat.post_transform, PostTransform::Logistic);
+        assert_eq!(flat.base_values, [0.5]);
+        assert_eq!(flat.num_trees, 2 * MODES.len() + 1);
+        assert_eq!(flat.leaf_width, 1);
+        assert_eq!(flat.scale_bits, None);
+        for x in rows() {
+            for t in 0..flat.num_trees {
+                let leaf = flat.find_leaf(t, &x).unwrap();
+                assert_eq!(flat.leaf_values(leaf), reference_leaf(&ensemble, t, &x), "tree {} x {:?}", t, x);
+            }
+        }
+    }
+
+    #[test]
+    fn quantized_round_trip_reaches_the_same_leaves() {
+        let tree_json = ensemble_json();
+        let ensemble = TreeEnsemble::from_json(&tree_json).unwrap();
+        let (words, scale_bits) = quantize_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+        assert_eq!(scale_bits, 29);
+        let flat = FlatTrees::parse(&words).unwrap();
+        assert_eq!(flat.scale_bits, Some(scale_bits));
+        for x in rows() {
+            for t in 0..flat.num_trees {
+                // every threshold and feature here is exact at this scale
+                let (leaf, changed) = flat.find_leaf_quantized(t, &x).unwrap();
+                assert!(!changed, "tree {} x {:?}", t, x);
+                assert_eq!(flat.leaf_values(leaf), reference_leaf(&ensemble, t, &x), "tree {} x {:?}", t, x);
+            }
+        }
+    }
+
+    #[test]
+    fn quantized_reports_a_changed_route() {
+        // 0.1 is not exact in binary, so a feature just above it floors to the
+        // same integer and takes the other branch than in f64
+        let tree_json = json!([
+            {"id": 0, "feature": 0, "threshold": 0.1, "left": 1, "right": 2, "mode": "BRANCH_LEQ"},
+            {"id": 1, "mode": "LEAF", "value": [[1.0]]},
+            {"id": 2, "mode": "LEAF", "value": [[2.0]]}
+        ])
+        .to_string();
+        let (words, _) = quantize_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+        let flat = FlatTrees::parse(&words).unwrap();
+        let x = [0.1 + 1e-12];
+        assert_eq!(flat.leaf_values(flat.find_leaf(0, &x).unwrap()), [2.0]);
+        let (leaf, changed) = flat.find_leaf_quantized(0, &x).unwrap();
+        assert!(changed);
+        assert_eq!(flat.leaf_values(leaf), [1.0]);
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_truncated_words() {
+        let tree_json = ensemble_json();
+        let words = flatten_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+
+        let mut bad_magic = words.clone();
+        bad_magic[0] = 0x5654_4632;
+        assert_eq!(FlatTrees::parse(&bad_magic).err().unwrap(), "Not a flattened tree");
+        assert_eq!(FlatTrees::parse(&words[..HEADER_WORDS - 1]).err().unwrap(), "Not a flattened tree");
+        assert_eq!(FlatTrees::parse(&words[..HEADER_WORDS + 3]).err().unwrap(), "Flattened tree is truncated");
+
+        let (quantized, _) = quantize_tree_json(&tree_json, LeafValues::ClassScores).unwrap();
+        assert_eq!(FlatTrees::parse(&quantized[..HEADER_WORDS]).err().unwrap(), "Flattened tree is truncated");
+        let nodes_end = quantized.len() - 2 * (2 * 2 * MODES.len() + 3);
+        assert!(FlatTrees::parse(&quantized[..nodes_end]).is_ok());
+        assert_eq!(FlatTrees::parse(&quantized[..nodes_end - 1]).err().unwrap(), "Flattened tree is truncated");
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        let leaf = |id: usize| json!({"id": id, "mode": "LEAF", "value": [[1.0]]});
+        let split = json!({"id": 0, "feature": 0, "threshold": 0.5, "left": 1, "right": 2});
+        let cases = [
+            (json!([]), "no nodes"),
+            (json!([split, leaf(1)]), "points to unknown node 2"),
+            (json!([split, leaf(1), leaf(1)]), "duplicate node id 1"),
+            (json!([leaf(1)]), "has no root"),
+            (json!([{"id": 0, "feature": 0, "left": 1, "right": 2}, leaf(1), leaf(2)]), "has no threshold"),
+            (json!([split, leaf(1), {"id": 2, "mode": "LEAF", "value": [[1.0, 2.0]]}]), "width differs"),
+        ];
+        for (doc, expected) in cases {
+            let err = flatten_tree_json(&doc.to_string(), LeafValues::ClassScores).unwrap_err();
+            assert!(err.contains(expected), "{}: got {:?}", doc, err);
+        }
+        let huge = json!([{"id": 0, "feature": 0, "threshold": 2e9, "left": 1, "right": 2}, leaf(1), leaf(2)]);
+        assert!(quantize_tree_json(&huge.to_string(), LeafValues::ClassScores).unwrap_err().contains("too large"));
+    }
+}
@@ -0,0 +1,12 @@
+// Code shared by the verse crates (CLI, Zk-host, rust/host and the verifier),
+// used through path dependencies so each keeps a single copy. The tree guests
+// and tools take the split rule from tree.rs without default features.
+
+#[cfg(feature = "envelope")]
+pub mod envelope;
+pub mod flat_tree;
+pub mod preprocess;
+#[cfg(feature = "receipt")]
+pub mod receipt;
+pub mod report;
+pub mod tree;
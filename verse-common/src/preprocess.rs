@@ -0,0 +1,270 @@
+// Preprocessing spec published with a model: how raw dataset columns become
+// the model's input vector. Each entry names a raw column, optionally encodes
+// it, and optionally scales the result; the model input is the entries'
+// outputs concatenated in order. Example:
+//
+//   { "columns": [
+//       { "name": "age",    "scale": { "standard": { "mean": 41.2, "std": 12.5 } } },
+//       { "name": "bmi",    "scale": { "min_max": { "min": 10, "max": 60 } } },
+//       { "name": "region", "encode": { "one_hot": { "levels": ["north", "south", "east"] } } },
+//       { "name": "size",   "encode": { "ordinal": { "levels": ["S", "M", "L"] } } },
+//       { "name": "city",   "encode": { "hashing": { "buckets": 8 } } } ] }
+//
+// one_hot gives one 0/1 feature per level, ordinal the level's index, and
+// hashing a 0/1 indicator in bucket fnv1a32(value) % buckets. Scaling applies
+// to single-output columns (no encoder, or ordinal): standard is
+// (x - mean) / std, min_max is (x - min) / (max - min).
+//
+// `verse request` applies the encoders while ingesting the verifier's dataset;
+// scaling is left to the guest, which gets it from the owner's model spec and
+// applies it in-circuit to the embedded rows. Zk-host's preprocess.rs turns the
+// scalers into the (offset, factor) pairs the guest reads.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preprocessing {
+    pub columns: Vec<ColumnStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnStep {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encode: Option<Encoder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Scaler>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoder {
+    OneHot { levels: Vec<String> },
+    Ordinal { levels: Vec<String> },
+    Hashing { buckets: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scaler {
+    Standard { mean: f64, std: f64 },
+    MinMax { min: f64, max: f64 },
+}
+
+// 32-bit FNV-1a, the hashing encoder's bucket hash.
+pub fn fnv1a32(s: &str) -> u32 {
+    let mut h: u32 = 0x811c_9dc5;
+    for b in s.bytes() {
+        h ^= b as u32;
+        h = h.wrapping_mul(0x0100_0193);
+    }
+    h
+}
+
+impl ColumnStep {
+    // Number of model inputs the column produces.
+    pub fn width(&self) -> usize {
+        match &self.encode {
+            Some(Encoder::OneHot { levels }) => levels.len(),
+            Some(Encoder::Hashing { buckets }) => *buckets as usize,
+            Some(Encoder::Ordinal { .. }) | None => 1,
+        }
+    }
+
+    // Encoded (unscaled) values of one raw cell.
+    pub fn encode(&self, cell: &str) -> Result<Vec<f32>, String> {
+        match &self.encode {
+            None => match cell.parse::<f32>() {
+                Ok(v) if v.is_finite() => Ok(vec![v]),
+                Ok(_) => Err(format!("'{}' is not a finite number", cell)),
+                Err(_) => Err(format!("'{}' is not a number", cell)),
+            },
+            Some(Encoder::Ordinal { levels }) => levels
+                .iter()
+                .position(|l| l == cell)
+                .map(|i| vec![i as f32])
+                .ok_or_else(|| format!("'{}' is not one of {}", cell, levels.join("|"))),
+            Some(Encoder::OneHot { levels }) => {
+                let i = levels
+                    .iter()
+                    .position(|l| l == cell)
+                    .ok_or_else(|| format!("'{}' is not one of {}", cell, levels.join("|")))?;
+                let mut out = vec![0.0; levels.len()];
+                out[i] = 1.0;
+                Ok(out)
+            }
+            Some(Encoder::Hashing { buckets }) => {
+                let mut out = vec![0.0; *buckets as usize];
+                out[(fnv1a32(cell) % buckets) as usize] = 1.0;
+                Ok(out)
+            }
+        }
+    }
+}
+
+impl Preprocessing {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let p: Preprocessing = serde_json::from_str(text).map_err(|e| format!("Invalid preprocessing spec: {}", e))?;
+        p.validate()?;
+        Ok(p)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.columns.is_empty() {
+            return Err("Preprocessing spec has no columns".into());
+        }
+        for (i, col) in self.columns.iter().enumerate() {
+            if self.columns[..i].iter().any(|c| c.name == col.name) {
+                return Err(format!("Column '{}' appears twice in the preprocessing spec", col.name));
+            }
+            match &col.encode {
+                Some(Encoder::OneHot { levels }) | Some(Encoder::Ordinal { levels }) => {
+                    if levels.is_empty() {
+                        return Err(format!("Column '{}': encoder has no levels", col.name));
+                    }
+                    if let Some(dup) = levels.iter().enumerate().find(|(j, l)| levels[..*j].contains(l)) {
+                        return Err(format!("Column '{}': level '{}' is repeated", col.name, dup.1));
+                    }
+                }
+                Some(Encoder::Hashing { buckets }) if *buckets == 0 => {
+                    return Err(format!("Column '{}': hashing needs at least 1 bucket", col.name));
+                }
+                _ => {}
+            }
+            match &col.scale {
+                Some(_) if col.width() != 1 => {
+                    return Err(format!("Column '{}': only single-output columns can be scaled", col.name));
+                }
+                Some(Scaler::Standard { std, .. }) if !std.is_finite() || *std <= 0.0 => {
+                    return Err(format!("Column '{}': standard scaling needs std > 0", col.name));
+                }
+                Some(Scaler::MinMax { min, max }) if !(min.is_finite() && max.is_finite()) || max <= min => {
+                    return Err(format!("Column '{}': min_max scaling needs max > min", col.name));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Width of the model input the columns produce.
+    pub fn output_width(&self) -> usize {
+        self.columns.iter().map(ColumnStep::width).sum()
+    }
+
+    pub fn has_scaling(&self) -> bool {
+        self.columns.iter().any(|c| c.scale.is_some())
+    }
+
+    pub fn print(&self) {
+        for col in self.columns.iter() {
+            let encode = match &col.encode {
+                None => "numeric".to_string(),
+                Some(Encoder::OneHot { levels }) => format!("one_hot({})", levels.join("|")),
+                Some(Encoder::Ordinal { levels }) => format!("ordinal({})", levels.join("|")),
+                Some(Encoder::Hashing { buckets }) => format!("hashing({} buckets)", buckets),
+            };
+            let scale = match &col.scale {
+                None => String::new(),
+                Some(Scaler::Standard { mean, std }) => format!(", standard(mean {}, std {})", mean, std),
+                Some(Scaler::MinMax { min, max }) => format!(", min_max({}, {})", min, max),
+            };
+            println!("  {:<20} {}{}  -> {} feature(s)", col.name, encode, scale, col.width());
+        }
+    }
+
+    // Encodes the feature columns of `rows` (named by `names`); scaling is
+    // applied later, in the guest. Returns one encoded feature vector per row.
+    pub fn encode_rows(&self, names: &[String], rows: &[Vec<String>]) -> Result<Vec<Vec<f32>>, String> {
+        let sources: Vec<usize> = self
+            .columns
+            .iter()
+            .map(|col| {
+                names
+                    .iter()
+                    .position(|n| *n == col.name)
+                    .ok_or_else(|| format!("Preprocessing column '{}' is not in the dataset (columns: {})", col.name, names.join(", ")))
+            })
+            .collect::<Result<_, _>>()?;
+        rows.iter()
+            .enumerate()
+            .map(|(r, row)| {
+                let mut out = Vec::with_capacity(self.output_width());
+                for (col, &src) in self.columns.iter().zip(sources.iter()) {
+                    let values = col
+                        .encode(row[src].trim())
+                        .map_err(|e| format!("Data row {}, column '{}': {}", r + 1, col.name, e))?;
+                    out.extend(values);
+                }
+                Ok(out)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"{ "columns": [
+        { "name": "age", "scale": { "standard": { "mean": 40, "std": 8 } } },
+        { "name": "region", "encode": { "one_hot": { "levels": ["north", "south", "east"] } } },
+        { "name": "size", "encode": { "ordinal": { "levels": ["S", "M", "L"] } } },
+        { "name": "city", "encode": { "hashing": { "buckets": 4 } } } ] }"#;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn output_width_counts_encoded_features() {
+        let p = Preprocessing::parse(SPEC).unwrap();
+        assert_eq!(p.columns.iter().map(ColumnStep::width).collect::<Vec<_>>(), [1, 3, 1, 4]);
+        assert_eq!(p.output_width(), 9);
+        assert!(p.has_scaling());
+    }
+
+    #[test]
+    fn encodes_rows_by_column_name() {
+        let p = Preprocessing::parse(SPEC).unwrap();
+        // dataset columns in a different order, plus one the model ignores
+        let header = names(&["city", "id", "size", "region", "age"]);
+        let rows = vec![names(&["paris", "7", "L", "south", " 51.5 "])];
+        let bucket = (fnv1a32("paris") % 4) as usize;
+        let mut hashed = vec![0.0; 4];
+        hashed[bucket] = 1.0;
+        let mut want = vec![51.5, 0.0, 1.0, 0.0, 2.0];
+        want.extend(hashed);
+        assert_eq!(p.encode_rows(&header, &rows).unwrap(), [want]);
+        assert_eq!(fnv1a32(""), 0x811c_9dc5);
+        assert_eq!(fnv1a32("a"), 0xe40c_292c);
+    }
+
+    #[test]
+    fn rejects_cells_the_encoders_do_not_know() {
+        let p = Preprocessing::parse(SPEC).unwrap();
+        let header = names(&["age", "region", "size", "city"]);
+        let err = |row: &[&str]| p.encode_rows(&header, &[names(row)]).unwrap_err();
+        assert!(err(&["x", "north", "S", "a"]).contains("'x' is not a number"));
+        assert!(err(&["inf", "north", "S", "a"]).contains("not a finite number"));
+        assert!(err(&["1", "west", "S", "a"]).contains("'west' is not one of north|south|east"));
+        assert!(err(&["1", "north", "XL", "a"]).contains("column 'size'"));
+        let missing = p.encode_rows(&names(&["age", "region"]), &[]).unwrap_err();
+        assert!(missing.contains("'size' is not in the dataset"));
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        let err = |columns: &str| Preprocessing::parse(&format!(r#"{{ "columns": [{}] }}"#, columns)).unwrap_err();
+        assert!(err("").contains("no columns"));
+        assert!(err(r#"{ "name": "a" }, { "name": "a" }"#).contains("appears twice"));
+        assert!(err(r#"{ "name": "a", "encode": { "one_hot": { "levels": [] } } }"#).contains("no levels"));
+        assert!(err(r#"{ "name": "a", "encode": { "ordinal": { "levels": ["x", "x"] } } }"#).contains("repeated"));
+        assert!(err(r#"{ "name": "a", "encode": { "hashing": { "buckets": 0 } } }"#).contains("at least 1 bucket"));
+        assert!(err(r#"{ "name": "a", "encode": { "one_hot": { "levels": ["x", "y"] } },
+                       "scale": { "standard": { "mean": 0, "std": 1 } } }"#)
+            .contains("only single-output columns"));
+        assert!(err(r#"{ "name": "a", "scale": { "standard": { "mean": 0, "std": 0 } } }"#).contains("std > 0"));
+        assert!(err(r#"{ "name": "a", "scale": { "min_max": { "min": 2, "max": 2 } } }"#).contains("max > min"));
+    }
+}
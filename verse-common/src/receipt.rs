@@ -0,0 +1,191 @@
+// Receipt files as `verse prove` writes them and `verse verify` / the verifier
+// crate read them. Zk-host writes VERSE_RECEIPT_ENCODING (`verse prove
+// --receipt-encoding`): bincode (the default) or cbor go to proof.receipt
+// behind an 8-byte header, json goes to proof.json as plain serde JSON.
+//
+//   bytes 0..4  magic "VRSR"
+//   byte  4     format version (1)
+//   byte  5     encoding: 1 = bincode, 2 = cbor
+//   bytes 6..8  reserved, zero
+//
+// Readers tell the two apart by the magic; anything else is parsed as JSON.
+
+use risc0_zkvm::{InnerReceipt, Receipt};
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAGIC: &[u8; 4] = b"VRSR";
+pub const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Encoding {
+    Json,
+    #[default]
+    Bincode,
+    Cbor,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Encoding::Json),
+            "bincode" => Ok(Encoding::Bincode),
+            "cbor" => Ok(Encoding::Cbor),
+            other => Err(format!("Unknown receipt encoding '{}' (expected bincode, cbor or json)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::Json => write!(f, "json"),
+            Encoding::Bincode => write!(f, "bincode"),
+            Encoding::Cbor => write!(f, "cbor"),
+        }
+    }
+}
+
+impl Encoding {
+    pub fn from_env() -> Result<Encoding, String> {
+        match std::env::var("VERSE_RECEIPT_ENCODING") {
+            Ok(v) if !v.trim().is_empty() => v.parse(),
+            _ => Ok(Encoding::default()),
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Encoding::Json => "proof.json",
+            Encoding::Bincode | Encoding::Cbor => "proof.receipt",
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            Encoding::Json => 0,
+            Encoding::Bincode => 1,
+            Encoding::Cbor => 2,
+        }
+    }
+}
+
+// Name of the kind a receipt is.
+pub fn kind_name(receipt: &Receipt) -> &'static str {
+    match receipt.inner {
+        InnerReceipt::Composite(_) => "composite",
+        InnerReceipt::Succinct(_) => "succinct",
+        InnerReceipt::Groth16(_) => "groth16",
+        InnerReceipt::Fake(_) => "fake (dev mode)",
+        _ => "unknown",
+    }
+}
+
+// Whether a receipt file uses a binary encoding rather than JSON.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn encode(receipt: &Receipt, encoding: Encoding) -> Result<Vec<u8>, String> {
+    let payload = match encoding {
+        Encoding::Json => return serde_json::to_vec(receipt).map_err(|e| format!("JSON encoding failed: {}", e)),
+        Encoding::Bincode => bincode::serialize(receipt).map_err(|e| format!("bincode encoding failed: {}", e))?,
+        Encoding::Cbor => serde_cbor::to_vec(receipt).map_err(|e| format!("CBOR encoding failed: {}", e))?,
+    };
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&[FORMAT_VERSION, encoding.code(), 0, 0]);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+// Decodes a receipt file, detecting its encoding.
+pub fn decode(bytes: &[u8]) -> Result<(Receipt, Encoding), String> {
+    if !is_binary(bytes) {
+        return serde_json::from_slice(bytes)
+            .map(|r| (r, Encoding::Json))
+            .map_err(|e| format!("Not a binary receipt and not receipt JSON: {}", e));
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err("Receipt header is truncated".into());
+    }
+    if bytes[4] != FORMAT_VERSION {
+        return Err(format!(
+            "Receipt format version {} is not supported (this reader supports version {}); update the verifier",
+            bytes[4], FORMAT_VERSION
+        ));
+    }
+    if bytes[6..HEADER_LEN] != [0, 0] {
+        return Err("Receipt header has non-zero reserved bytes".into());
+    }
+    let payload = &bytes[HEADER_LEN..];
+    match bytes[5] {
+        1 => bincode::deserialize(payload)
+            .map(|r| (r, Encoding::Bincode))
+            .map_err(|e| format!("Failed to decode bincode receipt: {}", e)),
+        2 => serde_cbor::from_slice(payload)
+            .map(|r| (r, Encoding::Cbor))
+            .map_err(|e| format!("Failed to decode CBOR receipt: {}", e)),
+        code => Err(format!("Unknown receipt encoding code {}", code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::{FakeReceipt, ReceiptClaim};
+
+    fn fake_receipt() -> Receipt {
+        let journal = vec![7u8, 0, 0, 0, 42, 0, 0, 0];
+        let claim = ReceiptClaim::ok([3u32; 8], journal.clone());
+        Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal)
+    }
+
+    fn decode_err(bytes: &[u8]) -> String {
+        match decode(bytes) {
+            Ok((_, encoding)) => panic!("decoded as {}", encoding),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn round_trips_every_encoding() {
+        let receipt = fake_receipt();
+        for encoding in [Encoding::Bincode, Encoding::Cbor, Encoding::Json] {
+            let bytes = encode(&receipt, encoding).unwrap();
+            assert_eq!(is_binary(&bytes), encoding != Encoding::Json, "{}", encoding);
+            let (decoded, detected) = decode(&bytes).unwrap();
+            assert_eq!(detected, encoding);
+            assert_eq!(decoded.journal.bytes, receipt.journal.bytes, "{}", encoding);
+            assert_eq!(kind_name(&decoded), "fake (dev mode)");
+        }
+        assert_eq!(&encode(&receipt, Encoding::Cbor).unwrap()[..8], b"VRSR\x01\x02\x00\x00");
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let good = encode(&fake_receipt(), Encoding::Bincode).unwrap();
+        let with = |at: usize, byte: u8| {
+            let mut bytes = good.clone();
+            bytes[at] = byte;
+            bytes
+        };
+        assert!(decode_err(&with(0, b'X')).contains("not receipt JSON"));
+        assert!(decode_err(&with(4, 2)).contains("format version 2 is not supported"));
+        assert!(decode_err(&with(5, 9)).contains("Unknown receipt encoding code 9"));
+        assert!(decode_err(&with(6, 1)).contains("non-zero reserved bytes"));
+        assert!(decode_err(&with(7, 1)).contains("non-zero reserved bytes"));
+        assert!(decode_err(&good[..6]).contains("header is truncated"));
+        assert!(decode_err(&good[..good.len() - 1]).contains("Failed to decode bincode receipt"));
+    }
+
+    #[test]
+    fn parses_encoding_names() {
+        assert_eq!(" CBOR ".parse::<Encoding>().unwrap(), Encoding::Cbor);
+        assert_eq!("json".parse::<Encoding>().unwrap().file_name(), "proof.json");
+        assert!("xml".parse::<Encoding>().unwrap_err().contains("Unknown receipt encoding 'xml'"));
+    }
+}
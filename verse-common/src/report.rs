@@ -0,0 +1,164 @@
+// Metric reports for guest journals, printed by Zk-host after proving and by
+// `verse verify` / `verse model eval`.
+//
+// Journal layouts (see ZK-guest):
+//   classifiers         Vec<(Vec<f64>, u32)>   scores per class, expected class
+//   tree regressor      (Vec<(Vec<f64>, f64)>, RegressionMetrics)
+//   linear / MLP        Vec<(f32, f32)>        (prediction, target)
+// Quantized tree models append a RoutingReport to the first two.
+
+use serde::{Deserialize, Serialize};
+
+// Field order matches the guest's RegressionMetrics.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegressionMetrics {
+    pub mse: f64,
+    pub mae: f64,
+    pub r2: f64,
+}
+
+// Field order matches the guest's RoutingReport.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoutingReport {
+    pub scale_bits: u32,
+    pub changed_samples: Vec<u32>,
+}
+
+pub type ClassificationJournal = Vec<(Vec<f64>, u32)>;
+pub type RegressionJournal = (Vec<(Vec<f64>, f64)>, RegressionMetrics);
+
+pub fn argmax(scores: &[f64]) -> Option<(usize, f64)> {
+    scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(idx, p)| (idx, *p))
+}
+
+// Same definition as the guest: on (predicted, true) pairs, r2 is 0 when the
+// targets have no variance.
+pub fn regression_metrics(pairs: &[(f64, f64)]) -> RegressionMetrics {
+    let n = pairs.len().max(1) as f64;
+    let mean = pairs.iter().map(|(_, t)| t).sum::<f64>() / n;
+    let mut sse = 0.0;
+    let mut sae = 0.0;
+    let mut sst = 0.0;
+    for &(p, t) in pairs.iter() {
+        sse += (p - t) * (p - t);
+        sae += (p - t).abs();
+        sst += (t - mean) * (t - mean);
+    }
+    RegressionMetrics {
+        mse: sse / n,
+        mae: sae / n,
+        r2: if sst > 0.0 { 1.0 - sse / sst } else { 0.0 },
+    }
+}
+
+// Class name for an index: the model's class labels when known, else the index.
+pub fn class_name(labels: &[String], idx: usize) -> String {
+    labels.get(idx).cloned().unwrap_or_else(|| idx.to_string())
+}
+
+pub fn print_classification_report(predictions: &[(Vec<f64>, u32)], labels: &[String]) {
+    let name_width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let class_width = name_width.max(9);
+    println!("\nSample | {:<w$} | Prob    | Expected", "PredClass", w = class_width);
+    println!("{}", "-".repeat(30 + class_width));
+    for (i, (probs, expected)) in predictions.iter().enumerate().take(5) {
+        let (pred, pred_p) = match argmax(probs) {
+            Some((idx, p)) => (class_name(labels, idx), p),
+            None => ("-".to_string(), f64::NAN),
+        };
+        println!(
+            "{:<6} | {:<w$} | {:<6.3} | {}",
+            i,
+            pred,
+            pred_p,
+            class_name(labels, *expected as usize),
+            w = class_width
+        );
+    }
+
+    if predictions.is_empty() {
+        return;
+    }
+
+    let num_classes = predictions
+        .iter()
+        .map(|(probs, expected)| probs.len().max(*expected as usize + 1))
+        .max()
+        .unwrap_or(0);
+
+    // confusion[expected][predicted]
+    let mut confusion = vec![vec![0usize; num_classes]; num_classes];
+    let mut correct = 0usize;
+    for (probs, expected) in predictions.iter() {
+        let expected = *expected as usize;
+        if let Some((pred_idx, _)) = argmax(probs) {
+            confusion[expected][pred_idx] += 1;
+            if pred_idx == expected {
+                correct += 1;
+            }
+        }
+    }
+
+    println!(
+        "\nAccuracy: {}/{} ({:.2}%)",
+        correct,
+        predictions.len(),
+        100.0 * correct as f64 / predictions.len() as f64
+    );
+
+    let row_width = name_width.max(8);
+    let col_width = name_width.max(6);
+    println!("\nConfusion matrix (rows = expected, cols = predicted):");
+    print!("{:>w$}", "", w = row_width);
+    for c in 0..num_classes {
+        print!(" {:>w$}", class_name(labels, c), w = col_width);
+    }
+    println!();
+    for (expected, row) in confusion.iter().enumerate() {
+        print!("{:>w$}", class_name(labels, expected), w = row_width);
+        for count in row.iter() {
+            print!(" {:>w$}", count, w = col_width);
+        }
+        println!();
+    }
+}
+
+// `proven` marks metrics the guest computed, as opposed to a native run.
+pub fn print_regression_report(outputs: &[(Vec<f64>, f64)], metrics: &RegressionMetrics, proven: bool) {
+    println!("\n=== Results (first 5) ===");
+    for (i, (pred, t)) in outputs.iter().enumerate().take(5) {
+        if pred.len() == 1 {
+            println!("{}: pred={:.6}, true={:.6}", i, pred[0], t);
+        } else {
+            println!("{}: pred={:?}, true={:.6}", i, pred, t);
+        }
+    }
+
+    if proven {
+        println!("\n=== Regression metrics (proven in guest, target 0) ===");
+    } else {
+        println!("\n=== Regression metrics (target 0) ===");
+    }
+    println!("MSE:  {:.6}", metrics.mse);
+    println!("RMSE: {:.6}", metrics.mse.sqrt());
+    println!("MAE:  {:.6}", metrics.mae);
+    println!("R^2:  {:.6}", metrics.r2);
+}
+
+pub fn print_routing_report(routing: &RoutingReport, num_samples: usize) {
+    println!("\n=== Quantized routing (2^{} scale) ===", routing.scale_bits);
+    if routing.changed_samples.is_empty() {
+        println!("All {} samples take the same path as with f64 thresholds (proven in guest)", num_samples);
+    } else {
+        println!(
+            "Routing changed for {}/{} samples: {:?}",
+            routing.changed_samples.len(),
+            num_samples,
+            routing.changed_samples
+        );
+    }
+}
@@ -0,0 +1,169 @@
+// Tree JSON as the guests read it: a list of nodes (a single tree), or an
+// ensemble document from `verse model import` / model-owner/tree2json.py with
+// the nodes of every tree plus aggregate, post_transform and base_values.
+// flat_tree.rs lays it out for the guest; the CLI's tree_eval.rs walks it
+// natively with the same branch rule.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct TreeNode {
+    #[serde(default)]
+    pub tree_id: usize,
+    pub id: usize,
+    pub feature: Option<usize>,
+    pub threshold: Option<f64>,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    #[serde(default)]
+    pub value: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub mode: SplitMode,
+    #[serde(default)]
+    pub missing_tracks_true: bool,
+}
+
+// Variant order is the guest's mode code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SplitMode {
+    #[default]
+    BranchLeq,
+    BranchLt,
+    BranchGte,
+    BranchGt,
+    BranchEq,
+    BranchNeq,
+    Leaf,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Average,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PostTransform {
+    #[default]
+    None,
+    Softmax,
+    Logistic,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TreeEnsemble {
+    #[serde(default)]
+    pub aggregate: Aggregate,
+    #[serde(default)]
+    pub post_transform: PostTransform,
+    #[serde(default)]
+    pub base_values: Vec<f64>,
+    pub nodes: Vec<TreeNode>,
+}
+
+// How leaves are read: classifiers take the class-score row in value[0],
+// regressors every target value (value.concat()), as in the guest.
+#[derive(Debug, Clone, Copy)]
+pub enum LeafValues {
+    ClassScores,
+    Targets,
+}
+
+impl LeafValues {
+    pub fn for_model(model_type: u32) -> Self {
+        if model_type == 9 {
+            LeafValues::Targets
+        } else {
+            LeafValues::ClassScores
+        }
+    }
+}
+
+impl TreeNode {
+    pub fn leaf_values(&self, leaf_values: LeafValues) -> Vec<f64> {
+        match leaf_values {
+            LeafValues::ClassScores => self.value.first().cloned().unwrap_or_default(),
+            LeafValues::Targets => self.value.concat(),
+        }
+    }
+}
+
+impl TreeEnsemble {
+    // A plain node list (single tree) is read as an ensemble of one.
+    pub fn from_json(tree_json: &str) -> Result<Self, String> {
+        if let Ok(ensemble) = serde_json::from_str::<TreeEnsemble>(tree_json) {
+            return Ok(ensemble);
+        }
+        let nodes: Vec<TreeNode> =
+            serde_json::from_str(tree_json).map_err(|e| format!("Invalid tree JSON: {}", e))?;
+        Ok(TreeEnsemble {
+            aggregate: Aggregate::Sum,
+            post_transform: PostTransform::None,
+            base_values: Vec::new(),
+            nodes,
+        })
+    }
+}
+
+// Same rule as the guest and onnxruntime: NaN takes the true branch when
+// missing_tracks_true is set, otherwise it falls through the comparison, which
+// is false for NaN under every mode except BRANCH_NEQ.
+pub fn takes_true_branch(mode: SplitMode, missing_tracks_true: bool, x: f64, thr: f64) -> bool {
+    let cond = match mode {
+        SplitMode::BranchLeq => x <= thr,
+        SplitMode::BranchLt => x < thr,
+        SplitMode::BranchGte => x >= thr,
+        SplitMode::BranchGt => x > thr,
+        SplitMode::BranchEq => x == thr,
+        SplitMode::BranchNeq => x != thr,
+        SplitMode::Leaf => false,
+    };
+    cond || (missing_tracks_true && x.is_nan())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [SplitMode; 6] = [
+        SplitMode::BranchLeq,
+        SplitMode::BranchLt,
+        SplitMode::BranchGte,
+        SplitMode::BranchGt,
+        SplitMode::BranchEq,
+        SplitMode::BranchNeq,
+    ];
+
+    #[test]
+    fn compares_with_each_mode() {
+        // below, at and above the threshold
+        let want = [
+            [true, true, false],
+            [true, false, false],
+            [false, true, true],
+            [false, false, true],
+            [false, true, false],
+            [true, false, true],
+        ];
+        for (mode, want) in MODES.iter().zip(want) {
+            let got = [1.0, 2.0, 3.0].map(|x| takes_true_branch(*mode, false, x, 2.0));
+            assert_eq!(got, want, "{:?}", mode);
+        }
+        assert!(!takes_true_branch(SplitMode::Leaf, true, 1.0, 2.0));
+    }
+
+    #[test]
+    fn nan_goes_true_only_when_tracked_or_under_neq() {
+        for mode in MODES {
+            assert!(takes_true_branch(mode, true, f64::NAN, 2.0), "{:?}", mode);
+            let untracked = takes_true_branch(mode, false, f64::NAN, 2.0);
+            assert_eq!(untracked, mode == SplitMode::BranchNeq, "{:?}", mode);
+        }
+    }
+}
@@ -11,6 +11,9 @@ export type Model = {
     userId: string;
     description: string;
     vectorFormat: string;
+    featureNames: string[];
+    classLabels: string[];
+    preprocessing: Record<string, unknown> | null;
     createdAt: string;
     updatedAt: string;
 };